@@ -51,7 +51,7 @@ export class WebGPUEngine {
                 if (!hasWebGPU) {
                     console.log('WebGPU not detected, defaulting to WebGL');
                 }
-                app = await init_gpu(this.canvasId, forceWebGL);
+                app = await init_gpu(this.canvasId, forceWebGL, true);
                 console.log(`Graphics initialized via ${forceWebGL ? 'WebGL' : 'WebGPU'}`);
             } catch (e) {
                 // If WebGPU failed (even if checking navigator.gpu passed), try forcing WebGL
@@ -67,7 +67,7 @@ export class WebGPUEngine {
                 }
 
                 try {
-                    app = await init_gpu(this.canvasId, true);
+                    app = await init_gpu(this.canvasId, true, true);
                     console.log('Fallback to WebGL successful');
                 } catch (e2) {
                     console.error('WebGL fallback also failed:', e2);
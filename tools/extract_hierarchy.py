@@ -79,6 +79,19 @@ def main():
     lines.append("            None")
     lines.append("        }")
     lines.append("    }")
+    lines.append("")
+    lines.append("    /// snake_case name, as used for the pose fields in the JSON schema")
+    lines.append("    pub const fn name(self) -> &'static str {")
+    lines.append("        match self {")
+    for name, _ in HIERARCHY:
+        lines.append(f"            BoneId::{get_rust_enum_name(name)} => \"{name.lower()}\",")
+    lines.append("        }")
+    lines.append("    }")
+    lines.append("")
+    lines.append("    /// Look up a bone by its `name`")
+    lines.append("    pub fn from_name(name: &str) -> Option<Self> {")
+    lines.append("        Self::ALL.into_iter().find(|bone| bone.name() == name)")
+    lines.append("    }")
     lines.append("}")
     lines.append("")
 
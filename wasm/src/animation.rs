@@ -1,4 +1,6 @@
-use crate::bone::{AnimationId, RotationAnimationClip, RotationPose};
+use crate::bone::{AnimationId, BoneId, ConstraintClip, RotationAnimationClip, RotationPose};
+use glam::{Quat, Vec2, Vec3};
+use std::cell::RefCell;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
@@ -9,6 +11,93 @@ use wasm_bindgen::prelude::*;
 pub struct AnimationLibrary {
     // Fixed size array, indexed by AnimationId
     clips: [Option<RotationAnimationClip>; AnimationId::COUNT],
+    // IK-driven clips; an id holds at most one of the two kinds
+    constraint_clips: [Option<ConstraintClip>; AnimationId::COUNT],
+    // `content_hash` of each keyframe clip, computed once when it is added
+    clip_hashes: [u64; AnimationId::COUNT],
+    // Recent keyframe clip samples; `sample` takes `&self`, like pose FK caching
+    sample_cache: RefCell<SampleCache>,
+}
+
+/// Time step that sample cache lookups are rounded to, in seconds
+const SAMPLE_CACHE_QUANTUM: f32 = 1e-4;
+
+/// Default number of poses kept by the sample cache
+pub const DEFAULT_SAMPLE_CACHE_CAPACITY: usize = 8;
+
+/// Small least-recently-used cache of sampled poses
+///
+/// Crossfades and blend trees sample the same clip at the same time several
+/// times per frame. Entries are keyed by the clip's content hash and the
+/// quantized time, so a reloaded clip with different keyframes never hits
+/// a stale pose.
+#[derive(Debug)]
+pub struct SampleCache {
+    /// Oldest first; a hit moves the entry to the back
+    entries: Vec<((u64, i64), RotationPose)>,
+    capacity: usize,
+    /// Lookups answered from the cache
+    pub hits: u32,
+    /// Lookups that had to sample the clip
+    pub misses: u32,
+}
+
+impl SampleCache {
+    /// Create an empty cache holding up to `capacity` poses (0 disables it)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Cached pose for `clip_hash` at `time`, sampling with `sample` on a miss
+    pub fn get_or_insert(
+        &mut self,
+        clip_hash: u64,
+        time: f32,
+        sample: impl FnOnce() -> RotationPose,
+    ) -> RotationPose {
+        if self.capacity == 0 {
+            return sample();
+        }
+        let key = (clip_hash, (time / SAMPLE_CACHE_QUANTUM).round() as i64);
+        if let Some(index) = self.entries.iter().position(|(k, _)| *k == key) {
+            self.hits += 1;
+            let entry = self.entries.remove(index);
+            let pose = entry.1.clone();
+            self.entries.push(entry);
+            return pose;
+        }
+
+        self.misses += 1;
+        let pose = sample();
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, pose.clone()));
+        pose
+    }
+
+    /// Drop every entry sampled from the clip with this content hash
+    pub fn invalidate(&mut self, clip_hash: u64) {
+        self.entries.retain(|((hash, _), _)| *hash != clip_hash);
+    }
+
+    /// Change the capacity, dropping the oldest entries if it shrinks
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+        self.capacity = capacity;
+    }
+}
+
+impl Default for SampleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_CACHE_CAPACITY)
+    }
 }
 
 impl Default for AnimationLibrary {
@@ -21,16 +110,46 @@ impl AnimationLibrary {
     /// Create empty animation library
     pub fn new() -> Self {
         const NONE_CLIP: Option<RotationAnimationClip> = None;
+        const NONE_CONSTRAINT_CLIP: Option<ConstraintClip> = None;
         Self {
             clips: [NONE_CLIP; AnimationId::COUNT],
+            constraint_clips: [NONE_CONSTRAINT_CLIP; AnimationId::COUNT],
+            clip_hashes: [0; AnimationId::COUNT],
+            sample_cache: RefCell::new(SampleCache::default()),
         }
     }
 
     /// Add an animation clip to the library
     pub fn add_clip(&mut self, id: AnimationId, clip: RotationAnimationClip) {
+        self.remove_clip(id);
+        self.clip_hashes[id.index()] = clip.content_hash();
         self.clips[id.index()] = Some(clip);
     }
 
+    /// Add an IK-driven clip, replacing any clip loaded for `id`
+    pub fn add_constraint_clip(&mut self, id: AnimationId, clip: ConstraintClip) {
+        self.remove_clip(id);
+        self.constraint_clips[id.index()] = Some(clip);
+    }
+
+    /// Get an IK-driven clip by name
+    pub fn get_constraint_clip(&self, id: AnimationId) -> Option<&ConstraintClip> {
+        self.constraint_clips[id.index()].as_ref()
+    }
+
+    /// Sample whichever kind of clip is loaded for `id`
+    pub fn sample(&self, id: AnimationId, time: f32) -> Option<RotationPose> {
+        match (self.get_clip(id), self.get_constraint_clip(id)) {
+            (Some(clip), _) => {
+                let hash = self.clip_hashes[id.index()];
+                let mut cache = self.sample_cache.borrow_mut();
+                Some(cache.get_or_insert(hash, time, || clip.sample(time)))
+            }
+            (None, Some(clip)) => Some(clip.sample(time)),
+            (None, None) => None,
+        }
+    }
+
     /// Get a clip by name
     pub fn get_clip(&self, id: AnimationId) -> Option<&RotationAnimationClip> {
         self.clips[id.index()].as_ref()
@@ -38,7 +157,71 @@ impl AnimationLibrary {
 
     /// Check if a clip exists
     pub fn has_clip(&self, id: AnimationId) -> bool {
-        self.clips[id.index()].is_some()
+        self.clips[id.index()].is_some() || self.constraint_clips[id.index()].is_some()
+    }
+
+    /// Remove a clip from the library
+    /// Sampling `id` then falls back to the Placeholder clip if one is loaded,
+    /// otherwise to the bind pose. Returns false if no clip was loaded for `id`.
+    pub fn remove_clip(&mut self, id: AnimationId) -> bool {
+        let constraint = self.constraint_clips[id.index()].take();
+        let clip = self.clips[id.index()].take();
+        if clip.is_some() {
+            let hash = self.clip_hashes[id.index()];
+            self.sample_cache.get_mut().invalidate(hash);
+        }
+        clip.is_some() || constraint.is_some()
+    }
+
+    /// Sample cache hit and miss counts since the library was created
+    pub fn sample_cache_stats(&self) -> (u32, u32) {
+        let cache = self.sample_cache.borrow();
+        (cache.hits, cache.misses)
+    }
+
+    /// Set how many sampled poses are cached (0 disables caching)
+    pub fn set_sample_cache_capacity(&mut self, capacity: usize) {
+        self.sample_cache.get_mut().set_capacity(capacity);
+    }
+
+    /// Remove all clips
+    pub fn clear(&mut self) {
+        self.clips.iter_mut().for_each(|clip| *clip = None);
+        self.constraint_clips
+            .iter_mut()
+            .for_each(|clip| *clip = None);
+        self.sample_cache.get_mut().entries.clear();
+    }
+}
+
+/// Easing curve for generated motion (inbetweens, transitions)
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u8)]
+pub enum Easing {
+    #[default]
+    Linear = 0,
+    EaseIn = 1,
+    EaseOut = 2,
+    EaseInOut = 3,
+}
+
+impl Easing {
+    /// Map a linear fraction in [0, 1] to an eased fraction in [0, 1] (quadratic curves)
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - 2.0 * (1.0 - t) * (1.0 - t)
+                }
+            }
+        }
     }
 }
 
@@ -51,6 +234,12 @@ pub struct PlaybackState {
     pub exercise: Option<AnimationId>,
     /// Current time in seconds (modulo duration for looping)
     pub time: f32,
+    /// Remaps `time` before sampling; kept across exercise changes
+    pub time_warp: Option<TimeWarp>,
+    /// Turn applied to the sampled pose about its root (clips are authored facing +Z)
+    pub facing: Quat,
+    /// Show the nearest keyframe's pose as is instead of interpolating
+    pub snap_to_keyframe: bool,
 }
 
 impl PlaybackState {
@@ -59,6 +248,17 @@ impl PlaybackState {
         Self {
             exercise: Some(exercise),
             time: 0.0,
+            time_warp: None,
+            facing: Quat::IDENTITY,
+            snap_to_keyframe: false,
+        }
+    }
+
+    /// Clip time to sample at: `time` passed through the warp, if any
+    pub fn sample_time(&self) -> f32 {
+        match &self.time_warp {
+            Some(warp) => warp.apply(self.time),
+            None => self.time,
         }
     }
 
@@ -75,8 +275,533 @@ impl PlaybackState {
         PlaybackState {
             exercise: Some(exercise),
             time: 0.0,
+            ..self
+        }
+    }
+}
+
+/// Piecewise-linear playback time curve, e.g. to slow down at the peak of a rep
+///
+/// Control points map input (playback) time to output (clip) time. The curve
+/// starts at input 0 and repeats past its last point, each repetition offset by
+/// the last output time, so a warp authored over one rep applies to every rep.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeWarp {
+    points: Vec<(f32, f32)>,
+}
+
+impl TimeWarp {
+    /// Build from `(input, output)` points
+    /// Needs at least two points, the first at input 0, with strictly increasing
+    /// input and non-decreasing output times.
+    pub fn new(points: Vec<(f32, f32)>) -> Result<Self, &'static str> {
+        if points.len() < 2 {
+            return Err("time warp needs at least two points");
+        }
+        if points
+            .iter()
+            .any(|&(input, output)| !input.is_finite() || !output.is_finite())
+        {
+            return Err("time warp points must be finite");
+        }
+        if points[0].0 != 0.0 {
+            return Err("time warp must start at input time 0");
+        }
+        if points
+            .windows(2)
+            .any(|w| w[1].0 <= w[0].0 || w[1].1 < w[0].1)
+        {
+            return Err("time warp points must be increasing");
+        }
+        Ok(Self { points })
+    }
+
+    /// Map a playback time to a clip time
+    pub fn apply(&self, time: f32) -> f32 {
+        let (first, last) = (self.points[0], self.points[self.points.len() - 1]);
+        let period = last.0;
+        let cycles = (time / period).floor();
+        let local = time - cycles * period;
+
+        let next_idx = self
+            .points
+            .partition_point(|&(input, _)| input <= local)
+            .clamp(1, self.points.len() - 1);
+        let (a, b) = (self.points[next_idx - 1], self.points[next_idx]);
+        let t = (local - a.0) / (b.0 - a.0);
+        let warped = a.1 + (b.1 - a.1) * t;
+        cycles * (last.1 - first.1) + warped
+    }
+}
+
+/// Fixed-timestep accumulator for frame-rate independent stepping
+///
+/// Carries the leftover time between frames so a long frame is split into
+/// several fixed steps instead of one large jump through the keyframes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FixedTimestep {
+    /// Time not yet consumed by a whole step, in seconds
+    pub accumulator: f32,
+}
+
+impl FixedTimestep {
+    /// Add `delta_seconds` and consume as many `step_seconds` steps as fit
+    /// Returns the new accumulator and the number of steps to run.
+    pub fn accumulate(self, delta_seconds: f32, step_seconds: f32) -> (FixedTimestep, u32) {
+        if step_seconds <= 0.0 {
+            return (self, 0);
+        }
+        let total = self.accumulator + delta_seconds;
+        let steps = (total / step_seconds).floor().max(0.0);
+        let accumulator = total - steps * step_seconds;
+        (FixedTimestep { accumulator }, steps as u32)
+    }
+}
+
+/// Labeled points on clip timelines, reported as playback passes them
+#[derive(Debug, Clone)]
+pub struct EventMarkers {
+    /// (clip time in seconds, label) per clip, sorted by time; indexed by AnimationId
+    markers: [Vec<(f32, String)>; AnimationId::COUNT],
+}
+
+impl Default for EventMarkers {
+    fn default() -> Self {
+        Self {
+            markers: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+}
+
+impl EventMarkers {
+    /// Add a marker at clip time `time` (seconds)
+    pub fn add(&mut self, id: AnimationId, time: f32, label: &str) {
+        let markers = &mut self.markers[id.index()];
+        let index = markers.partition_point(|(t, _)| *t <= time);
+        markers.insert(index, (time, label.to_string()));
+    }
+
+    /// Remove every marker of a clip
+    pub fn clear(&mut self, id: AnimationId) {
+        self.markers[id.index()].clear();
+    }
+
+    /// Labels of the markers passed going from playback time `from` to `to`
+    ///
+    /// The range excludes `from` and includes `to`, so consecutive calls report
+    /// each marker once. Times loop every `duration` like sampling; a marker is
+    /// reported once per loop passed, in playback order.
+    pub fn crossed(&self, id: AnimationId, from: f32, to: f32, duration: f32) -> Vec<String> {
+        let markers = &self.markers[id.index()];
+        if markers.is_empty() || to <= from || duration <= 0.0 {
+            return Vec::new();
+        }
+        let first_loop = (from / duration).floor() as i64;
+        let last_loop = (to / duration).floor() as i64;
+        let mut labels = Vec::new();
+        for n in first_loop..=last_loop {
+            let offset = n as f32 * duration;
+            labels.extend(
+                markers
+                    .iter()
+                    .filter(|(t, _)| offset + t > from && offset + t <= to)
+                    .map(|(_, label)| label.clone()),
+            );
+        }
+        labels
+    }
+}
+
+/// Spring parameters for secondary motion
+#[derive(Clone, Copy, Debug)]
+pub struct SpringSettings {
+    /// Angular spring constant (1/s^2); higher follows the target more tightly
+    pub stiffness: f32,
+    /// Angular damping (1/s); `2 * sqrt(stiffness)` is critically damped
+    pub damping: f32,
+}
+
+/// Ambient wind layered on playback (see `RotationPose::apply_wind`)
+#[derive(Clone, Copy, Debug)]
+pub struct WindSettings {
+    /// 1 is a light breeze; 0 disables the effect
+    pub strength: f32,
+    /// World-space direction the wind blows toward (only its horizontal part matters)
+    pub direction: Vec3,
+}
+
+/// Spring-driven follow-through on top of a sampled pose
+///
+/// Each bone's local rotation chases the target rotation with a damped angular
+/// spring, so fast motions overshoot and settle instead of stopping dead.
+#[derive(Clone, Debug)]
+pub struct SecondaryMotion {
+    rotations: [Quat; BoneId::COUNT],
+    velocities: [Vec3; BoneId::COUNT],
+    initialized: bool,
+    /// Playback time of the last step, used to derive dt when driven by playback
+    pub last_time: Option<f32>,
+}
+
+impl Default for SecondaryMotion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecondaryMotion {
+    /// Create an uninitialized state; the first step snaps to its target
+    pub fn new() -> Self {
+        Self {
+            rotations: [Quat::IDENTITY; BoneId::COUNT],
+            velocities: [Vec3::ZERO; BoneId::COUNT],
+            initialized: false,
+            last_time: None,
+        }
+    }
+
+    /// Drop all spring state (e.g. when the exercise changes)
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Advance the springs by `dt` seconds toward `target` and return the driven pose
+    ///
+    /// Uses semi-implicit Euler on the rotation error (as a scaled axis), which
+    /// stays stable for the small frame steps used in playback.
+    pub fn step(
+        &mut self,
+        target: &RotationPose,
+        dt: f32,
+        stiffness: f32,
+        damping: f32,
+    ) -> RotationPose {
+        if !self.initialized {
+            self.rotations = target.local_rotations;
+            self.velocities = [Vec3::ZERO; BoneId::COUNT];
+            self.initialized = true;
+        }
+
+        let mut pose = target.clone();
+        for bone in BoneId::ALL {
+            let i = bone.index();
+            let current = self.rotations[i];
+
+            // Shortest-path rotation from current to target
+            let mut error = target.local_rotations[i] * current.conjugate();
+            if error.w < 0.0 {
+                error = -error;
+            }
+
+            let acceleration = error.to_scaled_axis() * stiffness - self.velocities[i] * damping;
+            self.velocities[i] += acceleration * dt;
+            self.rotations[i] =
+                (Quat::from_scaled_axis(self.velocities[i] * dt) * current).normalize();
+
+            pose = pose.with_rotation(bone, self.rotations[i]);
+        }
+        pose
+    }
+}
+
+/// Eased floor constraint for playback
+///
+/// `RotationPose::apply_floor_constraint_with_radius` lifts the root by the full
+/// penetration every frame, so a foot dipping below the floor between keyframes
+/// pops the whole body. This keeps the previous frame's lift and moves it toward
+/// the needed one with a first-order lag of `time_constant` seconds.
+#[derive(Clone, Debug, Default)]
+pub struct FloorSmoothing {
+    /// Seconds to close ~63% of a change in the needed lift; 0 snaps instantly
+    pub time_constant: f32,
+    /// Root lift applied by the last step
+    pub correction: f32,
+    initialized: bool,
+    /// Playback time of the last step, used to derive dt when driven by playback
+    pub last_time: Option<f32>,
+}
+
+impl FloorSmoothing {
+    /// Create a smoother with the given time constant (seconds)
+    pub fn new(time_constant: f32) -> Self {
+        Self {
+            time_constant,
+            ..Self::default()
+        }
+    }
+
+    /// Forget the previous lift; the next step snaps to the needed one
+    pub fn reset(&mut self) {
+        *self = Self::new(self.time_constant);
+    }
+
+    /// Advance the lift by `dt` seconds toward what `pose` needs and apply it
+    pub fn step(&mut self, pose: RotationPose, bone_radius: f32, dt: f32) -> RotationPose {
+        let target = pose.floor_penetration_with_radius(bone_radius);
+        if !self.initialized || self.time_constant <= 0.0 {
+            self.correction = target;
+            self.initialized = true;
+        } else {
+            let blend = 1.0 - (-dt.max(0.0) / self.time_constant).exp();
+            self.correction += (target - self.correction) * blend;
+        }
+        let root = pose.root_position + Vec3::Y * self.correction;
+        pose.with_root_position(root)
+    }
+}
+
+/// Breathing cycle of the procedural idle, in Hz
+const IDLE_BREATH_HZ: f32 = 0.25;
+
+/// Small deterministic PRNG (xorshift32) for seeding procedural motion
+struct IdleRng(u32);
+
+impl IdleRng {
+    fn new(seed: u32) -> Self {
+        // Scramble the seed so nearby seeds diverge; xorshift state must be non-zero
+        Self(seed.wrapping_mul(0x9E37_79B9) | 1)
+    }
+
+    /// Uniform value in [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        (self.0 >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Random phase in [0, 2*PI)
+    fn next_phase(&mut self) -> f32 {
+        self.next_f32() * std::f32::consts::TAU
+    }
+}
+
+/// Subtle breathing-like idle pose, a pure function of `time` (seconds) and `seed`
+///
+/// Layers small sine sways on the spine, head and arms. The seed only picks the
+/// phases, so different seeds give differently-timed but equally subtle motion.
+pub fn procedural_idle(time: f32, seed: u32) -> RotationPose {
+    let mut rng = IdleRng::new(seed);
+    let breath = time * IDLE_BREATH_HZ * std::f32::consts::TAU;
+    // (bone, axis, amplitude in degrees, frequency relative to breathing)
+    let layers: [(BoneId, Vec3, f32, f32); 10] = [
+        (BoneId::Spine1, Vec3::X, 1.5, 1.0),
+        (BoneId::Spine2, Vec3::X, 1.0, 1.0),
+        (BoneId::Spine2, Vec3::Z, 0.8, 0.5),
+        (BoneId::Spine3, Vec3::X, 1.0, 1.0),
+        (BoneId::Neck, Vec3::X, 1.5, 0.7),
+        (BoneId::Head, Vec3::Y, 3.0, 0.3),
+        (BoneId::LeftShoulder, Vec3::Z, 3.0, 0.5),
+        (BoneId::RightShoulder, Vec3::Z, 3.0, 0.5),
+        (BoneId::LeftElbow, Vec3::Y, 2.0, 0.4),
+        (BoneId::RightElbow, Vec3::Y, 2.0, 0.4),
+    ];
+
+    let mut pose = RotationPose::bind_pose();
+    for (bone, axis, amplitude, frequency) in layers {
+        let angle = (breath * frequency + rng.next_phase()).sin() * amplitude.to_radians();
+        let rotation = pose.local_rotations[bone.index()] * Quat::from_axis_angle(axis, angle);
+        pose = pose.with_rotation(bone, rotation);
+    }
+    pose
+}
+
+/// Make blend weights a partition of unity
+/// Negative (and NaN) weights become 0, the rest are divided by their sum; if
+/// nothing positive is left every entry gets an equal share.
+pub fn normalize_weights(weights: &mut [f32]) {
+    for weight in weights.iter_mut() {
+        // `max` returns the non-NaN operand
+        *weight = weight.max(0.0);
+    }
+    let total: f32 = weights.iter().sum();
+    if total > 0.0 && total.is_finite() {
+        weights.iter_mut().for_each(|weight| *weight /= total);
+    } else {
+        let share = 1.0 / weights.len() as f32;
+        weights.fill(share);
+    }
+}
+
+/// Sample several clips at `time` and blend them by weight
+/// Weights go through `normalize_weights`, so any non-negative values work.
+pub fn blend_clips(clips: &[(&RotationAnimationClip, f32)], time: f32) -> RotationPose {
+    let mut weights: Vec<f32> = clips.iter().map(|(_, weight)| *weight).collect();
+    normalize_weights(&mut weights);
+    let poses: Vec<(RotationPose, f32)> = clips
+        .iter()
+        .zip(weights)
+        .map(|((clip, _), weight)| (clip.sample(time), weight))
+        .collect();
+    let weighted: Vec<(&RotationPose, f32)> =
+        poses.iter().map(|(pose, weight)| (pose, *weight)).collect();
+    RotationPose::weighted_average(&weighted)
+}
+
+/// Clips placed at 2D parameter coordinates (e.g. forward speed x turn rate)
+///
+/// On a full grid a parameter is blended bilinearly from the four samples at
+/// the corners of its grid cell, and parameters outside the grid are clamped to
+/// its edge. Where a corner is missing (scattered layouts) it is blended
+/// barycentrically over the smallest triangle of samples containing it, or
+/// over the nearest edge of the samples' hull from outside.
+#[derive(Debug, Clone, Default)]
+pub struct BlendSpace2D {
+    samples: Vec<(Vec2, RotationAnimationClip)>,
+}
+
+impl BlendSpace2D {
+    /// Create an empty blend space
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place a clip at (x, y), replacing any clip already there
+    pub fn add_sample(&mut self, x: f32, y: f32, clip: RotationAnimationClip) {
+        let point = Vec2::new(x, y);
+        match self.samples.iter_mut().find(|(p, _)| *p == point) {
+            Some(sample) => sample.1 = clip,
+            None => self.samples.push((point, clip)),
         }
     }
+
+    /// Remove all samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Check if the space has no samples
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Blend weight of each sample (by index) at parameter (x, y)
+    /// Weights are non-negative and sum to 1; empty for an empty space.
+    pub fn weights(&self, x: f32, y: f32) -> Vec<(usize, f32)> {
+        let (xs, ys) = self.grid_lines();
+        if xs.is_empty() {
+            return Vec::new();
+        }
+        let (x0, x1, u) = enclosing_cell(&xs, x);
+        let (y0, y1, v) = enclosing_cell(&ys, y);
+
+        let corners = [
+            (Vec2::new(x0, y0), (1.0 - u) * (1.0 - v)),
+            (Vec2::new(x1, y0), u * (1.0 - v)),
+            (Vec2::new(x0, y1), (1.0 - u) * v),
+            (Vec2::new(x1, y1), u * v),
+        ];
+        let mut weights: Vec<(usize, f32)> = Vec::with_capacity(4);
+        for (corner, weight) in corners {
+            let Some(index) = self.samples.iter().position(|(p, _)| *p == corner) else {
+                return self.scattered_weights(Vec2::new(x, y));
+            };
+            // Degenerate cells list the same corner twice
+            match weights.iter_mut().find(|(i, _)| *i == index) {
+                Some(entry) => entry.1 += weight,
+                None => weights.push((index, weight)),
+            }
+        }
+        weights
+    }
+
+    /// Barycentric weights at `point` for samples not on a full grid
+    ///
+    /// Uses the smallest triangle of samples containing `point`. Outside all of
+    /// them, the triangle it is least far outside of, with the negative
+    /// coordinates clamped to 0, which projects onto the hull's nearest edge.
+    fn scattered_weights(&self, point: Vec2) -> Vec<(usize, f32)> {
+        let n = self.samples.len();
+        // (outside distance, area, indices, barycentric coordinates)
+        let mut best: Option<(f32, f32, [usize; 3], [f32; 3])> = None;
+        for a in 0..n {
+            for b in a + 1..n {
+                for c in b + 1..n {
+                    let [pa, pb, pc] = [a, b, c].map(|i| self.samples[i].0);
+                    let Some(coords) = barycentric(point, pa, pb, pc) else {
+                        continue;
+                    };
+                    let outside = -coords.iter().copied().fold(0.0, f32::min);
+                    let area = (pb - pa).perp_dot(pc - pa).abs();
+                    let better = best.is_none_or(|(best_outside, best_area, _, _)| {
+                        outside < best_outside - 1e-6
+                            || (outside <= best_outside + 1e-6 && area < best_area)
+                    });
+                    if better {
+                        best = Some((outside, area, [a, b, c], coords));
+                    }
+                }
+            }
+        }
+
+        let Some((_, _, indices, coords)) = best else {
+            // Fewer than three samples, or all on one line: nearest sample
+            let nearest = (0..n)
+                .min_by(|&a, &b| {
+                    let da = self.samples[a].0.distance_squared(point);
+                    let db = self.samples[b].0.distance_squared(point);
+                    da.total_cmp(&db)
+                })
+                .expect("non-empty blend space");
+            return vec![(nearest, 1.0)];
+        };
+        let mut weights = coords.map(|w| w.max(0.0));
+        normalize_weights(&mut weights);
+        indices
+            .into_iter()
+            .zip(weights)
+            .filter(|(_, w)| *w > 0.0)
+            .collect()
+    }
+
+    /// Blended pose at parameter (x, y) and clip time (bind pose if empty)
+    pub fn sample(&self, x: f32, y: f32, time: f32) -> RotationPose {
+        let clips: Vec<(&RotationAnimationClip, f32)> = self
+            .weights(x, y)
+            .into_iter()
+            .map(|(index, weight)| (&self.samples[index].1, weight))
+            .collect();
+        blend_clips(&clips, time)
+    }
+
+    /// Sorted distinct sample coordinates along each axis
+    fn grid_lines(&self) -> (Vec<f32>, Vec<f32>) {
+        let axis = |coord: fn(&Vec2) -> f32| {
+            let mut values: Vec<f32> = self.samples.iter().map(|(p, _)| coord(p)).collect();
+            values.sort_by(f32::total_cmp);
+            values.dedup();
+            values
+        };
+        (axis(|p| p.x), axis(|p| p.y))
+    }
+}
+
+/// Barycentric coordinates of `p` in triangle (a, b, c); None if degenerate
+fn barycentric(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> Option<[f32; 3]> {
+    let area = (b - a).perp_dot(c - a);
+    if area.abs() < crate::EPSILON {
+        return None;
+    }
+    let wb = (p - a).perp_dot(c - a) / area;
+    let wc = (b - a).perp_dot(p - a) / area;
+    Some([1.0 - wb - wc, wb, wc])
+}
+
+/// Grid cell `(lo, hi, t)` containing `value` along sorted grid `lines`
+/// Clamps to the outer lines; a single line gives a zero-width cell.
+fn enclosing_cell(lines: &[f32], value: f32) -> (f32, f32, f32) {
+    let (Some(&first), Some(&last)) = (lines.first(), lines.last()) else {
+        return (0.0, 0.0, 0.0);
+    };
+    let value = value.clamp(first, last);
+    let upper = lines
+        .partition_point(|&line| line <= value)
+        .clamp(1, lines.len() - 1);
+    if lines.len() == 1 {
+        return (first, first, 0.0);
+    }
+    let (lo, hi) = (lines[upper - 1], lines[upper]);
+    (lo, hi, (value - lo) / (hi - lo))
 }
 
 /// Sample animation
@@ -89,18 +814,22 @@ pub fn sample_animation(library: &AnimationLibrary, state: &PlaybackState) -> Ro
         None => return RotationPose::bind_pose(),
     };
 
-    // 1. Try to get the specific exercise clip
-    if let Some(clip) = library.get_clip(id) {
-        return clip.sample(state.time);
-    }
-
-    // 2. Fallback to master placeholder if specific clip not loaded
-    if let Some(clip) = library.get_clip(AnimationId::Placeholder) {
-        return clip.sample(state.time);
-    }
-
+    // 1. Try to get the specific exercise clip, then
+    // 2. Fallback to master placeholder if specific clip not loaded, then
     // 3. Absolute fallback is bind pose
-    RotationPose::bind_pose()
+    let time = state.sample_time();
+    let sample = |id| match library.get_clip(id) {
+        Some(clip) if state.snap_to_keyframe && !clip.keyframes.is_empty() => Some(
+            clip.keyframes[clip.nearest_keyframe_index(time)]
+                .pose
+                .clone(),
+        ),
+        _ => library.sample(id, time),
+    };
+    sample(id)
+        .or_else(|| sample(AnimationId::Placeholder))
+        .unwrap_or_else(RotationPose::bind_pose)
+        .with_facing(state.facing)
 }
 
 #[cfg(test)]
@@ -120,6 +849,195 @@ mod tests {
         assert_eq!(pose.root_position, RotationPose::bind_pose().root_position);
     }
 
+    /// Single-keyframe clip holding the bind pose at `root`
+    fn clip_at(root: Vec3) -> RotationAnimationClip {
+        RotationAnimationClip {
+            name: "test".to_string(),
+            duration: 1.0,
+            keyframes: vec![crate::bone::RotationKeyframe {
+                time: 0.0,
+                pose: RotationPose::bind_pose().with_root_position(root),
+                tcb: None,
+            }],
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sample_cache_hits_and_invalidates() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::ZERO));
+
+        let first = library.sample(AnimationId::PushUps, 0.25).unwrap();
+        let second = library.sample(AnimationId::PushUps, 0.25).unwrap();
+        assert_eq!(library.sample_cache_stats(), (1, 1));
+        assert_eq!(first.root_position, second.root_position);
+
+        // Reloading with different keyframes must not return the stale pose
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::X));
+        let reloaded = library.sample(AnimationId::PushUps, 0.25).unwrap();
+        assert_eq!(library.sample_cache_stats(), (1, 2));
+        assert_eq!(reloaded.root_position, Vec3::X);
+
+        // Disabled cache always samples
+        library.set_sample_cache_capacity(0);
+        library.sample(AnimationId::PushUps, 0.25);
+        assert_eq!(library.sample_cache_stats(), (1, 2));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_remove_clip() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::ZERO));
+
+        assert!(library.remove_clip(AnimationId::PushUps));
+        assert!(!library.has_clip(AnimationId::PushUps));
+        assert!(!library.remove_clip(AnimationId::PushUps));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_clear_library() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::ZERO));
+        library.add_clip(AnimationId::Placeholder, clip_at(Vec3::ZERO));
+
+        library.clear();
+        assert!(!library.has_clip(AnimationId::PushUps));
+        assert!(!library.has_clip(AnimationId::Placeholder));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_removed_playing_clip_without_placeholder_falls_back_to_bind_pose() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::new(1.0, 2.0, 3.0)));
+        let state = PlaybackState::new(AnimationId::PushUps);
+        assert_eq!(
+            sample_animation(&library, &state).root_position,
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+
+        library.remove_clip(AnimationId::PushUps);
+        assert_eq!(
+            sample_animation(&library, &state).root_position,
+            RotationPose::bind_pose().root_position
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_removed_playing_clip_falls_back_to_placeholder() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::new(1.0, 2.0, 3.0)));
+        library.add_clip(AnimationId::Placeholder, clip_at(Vec3::new(0.0, 5.0, 0.0)));
+        let state = PlaybackState::new(AnimationId::PushUps);
+
+        library.remove_clip(AnimationId::PushUps);
+        assert_eq!(
+            sample_animation(&library, &state).root_position,
+            Vec3::new(0.0, 5.0, 0.0)
+        );
+
+        // Removing the Placeholder too leaves only the bind pose
+        library.remove_clip(AnimationId::Placeholder);
+        assert_eq!(
+            sample_animation(&library, &state).root_position,
+            RotationPose::bind_pose().root_position
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_blend_space_three_samples() {
+        let mut space = BlendSpace2D::new();
+        space.add_sample(0.0, 0.0, clip_at(Vec3::new(0.0, 1.0, 0.0)));
+        space.add_sample(1.0, 0.0, clip_at(Vec3::new(1.0, 1.0, 0.0)));
+        space.add_sample(0.0, 1.0, clip_at(Vec3::new(0.0, 1.0, 1.0)));
+
+        // Inside the triangle: barycentric
+        let inside = space.sample(0.2, 0.3, 0.0);
+        assert!(inside
+            .root_position
+            .abs_diff_eq(Vec3::new(0.2, 1.0, 0.3), 1e-5));
+
+        // Near and at the missing (1, 1) corner: onto the hull's nearest edge,
+        // not the bind pose
+        for (x, y) in [(0.9, 0.9), (1.0, 1.0)] {
+            let weights = space.weights(x, y);
+            let total: f32 = weights.iter().map(|(_, w)| w).sum();
+            assert!((total - 1.0).abs() < 1e-6);
+            let pose = space.sample(x, y, 0.0);
+            assert!(pose
+                .root_position
+                .abs_diff_eq(Vec3::new(0.5, 1.0, 0.5), 1e-5));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_blend_space_bilinear() {
+        let corners = [
+            (0.0, 0.0, Vec3::new(0.0, 1.0, 0.0), 0.0),
+            (1.0, 0.0, Vec3::new(1.0, 1.0, 0.0), 0.4),
+            (0.0, 1.0, Vec3::new(0.0, 1.0, 1.0), 0.8),
+            (1.0, 1.0, Vec3::new(1.0, 1.0, 1.0), 1.2),
+        ];
+        let mut space = BlendSpace2D::new();
+        for (x, y, root, angle) in corners {
+            let mut clip = clip_at(root);
+            let pose = std::mem::take(&mut clip.keyframes[0].pose);
+            clip.keyframes[0].pose =
+                pose.with_rotation(BoneId::Spine1, Quat::from_rotation_x(angle));
+            space.add_sample(x, y, clip);
+        }
+
+        // Center: average of all four
+        let center = space.sample(0.5, 0.5, 0.0);
+        assert!(center
+            .root_position
+            .abs_diff_eq(Vec3::new(0.5, 1.0, 0.5), 1e-5));
+        let weights = space.weights(0.5, 0.5);
+        assert_eq!(weights.len(), 4);
+        assert!(weights.iter().all(|(_, w)| (w - 0.25).abs() < 1e-6));
+
+        // Corner: exactly that corner's clip
+        let corner = space.sample(1.0, 0.0, 0.0);
+        assert!(corner
+            .root_position
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+        assert!(corner.local_rotations[BoneId::Spine1.index()]
+            .abs_diff_eq(Quat::from_rotation_x(0.4), 1e-5));
+
+        // Outside the grid clamps to the edge
+        let clamped = space.sample(5.0, -3.0, 0.0);
+        assert!(clamped
+            .root_position
+            .abs_diff_eq(corner.root_position, 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_normalize_weights() {
+        let mut weights = [2.0, 2.0];
+        normalize_weights(&mut weights);
+        assert_eq!(weights, [0.5, 0.5]);
+
+        let mut weights = [0.0; 4];
+        normalize_weights(&mut weights);
+        assert_eq!(weights, [0.25; 4]);
+
+        // Negative and NaN weights drop out
+        let mut weights = [-1.0, 3.0, f32::NAN, 1.0];
+        normalize_weights(&mut weights);
+        assert_eq!(weights, [0.0, 0.75, 0.0, 0.25]);
+
+        let mut weights = [-2.0, -1.0];
+        normalize_weights(&mut weights);
+        assert_eq!(weights, [0.5, 0.5]);
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_playback_advance() {
@@ -136,54 +1054,638 @@ mod tests {
         let state = PlaybackState {
             exercise: Some(AnimationId::PushUps),
             time: 5.0,
+            time_warp: None,
+            facing: Quat::IDENTITY,
+            snap_to_keyframe: false,
         };
         let changed = state.set_exercise(AnimationId::PushUps);
 
         assert_eq!(changed.exercise, Some(AnimationId::PushUps));
         assert_eq!(changed.time, 0.0);
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_time_warp_remaps_sampling() {
+        let mut clip = clip_at(Vec3::ZERO);
+        clip.duration = 4.0;
+        clip.keyframes.push(crate::bone::RotationKeyframe {
+            time: 2.0,
+            pose: RotationPose::bind_pose().with_root_position(Vec3::new(4.0, 0.0, 0.0)),
+            tcb: None,
+        });
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip);
+
+        // Double speed for the first second, half speed for the next two
+        let warp = TimeWarp::new(vec![(0.0, 0.0), (1.0, 2.0), (3.0, 3.0)]).unwrap();
+        let mut state = PlaybackState::new(AnimationId::PushUps);
+        state.time_warp = Some(warp);
+
+        // Repeats past the last point, offset by its output time
+        for (time, expected) in [(0.5, 1.0), (1.0, 2.0), (2.0, 2.5), (3.5, 4.0)] {
+            state.time = time;
+            assert!((state.sample_time() - expected).abs() < 1e-6);
+            let pose = sample_animation(&library, &state);
+            let unwarped = library.sample(AnimationId::PushUps, expected).unwrap();
+            assert!(pose.root_position.abs_diff_eq(unwarped.root_position, 1e-5));
+        }
+        // Warped mid-point differs from linear playback
+        state.time = 0.5;
+        let linear = library.sample(AnimationId::PushUps, 0.5).unwrap();
+        assert!(!sample_animation(&library, &state)
+            .root_position
+            .abs_diff_eq(linear.root_position, 1e-3));
+
+        assert!(TimeWarp::new(vec![(0.0, 0.0)]).is_err());
+        assert!(TimeWarp::new(vec![(0.0, 0.0), (0.0, 1.0)]).is_err());
+        assert!(TimeWarp::new(vec![(0.5, 0.0), (1.0, 1.0)]).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_snap_to_keyframe_returns_nearest_pose() {
+        let keyframes: Vec<_> = (0..5)
+            .map(|i| crate::bone::RotationKeyframe {
+                time: i as f32 * 0.25,
+                pose: RotationPose::bind_pose()
+                    .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(i as f32 * 0.3))
+                    .with_root_position(Vec3::new(0.0, 1.0 + i as f32 * 0.1, 0.0)),
+                tcb: None,
+            })
+            .collect();
+        let clip = RotationAnimationClip {
+            name: "test".to_string(),
+            duration: 1.25,
+            keyframes: keyframes.clone(),
+        };
+        assert_eq!(clip.nearest_keyframe_index(0.6), 2);
+        assert_eq!(clip.nearest_keyframe_index(0.65), 3);
+        // Past the last keyframe the loop's first one is closer
+        assert_eq!(clip.nearest_keyframe_index(1.2), 0);
+
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip);
+        let mut state = PlaybackState::new(AnimationId::PushUps);
+        state.snap_to_keyframe = true;
+
+        let same_pose = |a: &RotationPose, b: &RotationPose| {
+            a.root_position == b.root_position && a.local_rotations == b.local_rotations
+        };
+        // 40% and 60% of the way from keyframe 2 to 3
+        state.time = 0.6;
+        assert!(same_pose(
+            &sample_animation(&library, &state),
+            &keyframes[2].pose
+        ));
+        state.time = 0.65;
+        assert!(same_pose(
+            &sample_animation(&library, &state),
+            &keyframes[3].pose
+        ));
+
+        state.snap_to_keyframe = false;
+        let interpolated = sample_animation(&library, &state);
+        assert!(!same_pose(&interpolated, &keyframes[3].pose));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_facing_turns_pose_about_root() {
+        let mut library = AnimationLibrary::new();
+        library.add_clip(AnimationId::PushUps, clip_at(Vec3::new(0.3, 1.0, -0.2)));
+        let mut state = PlaybackState::new(AnimationId::PushUps);
+        let forward = sample_animation(&library, &state);
+
+        state.facing = Quat::from_rotation_y(90f32.to_radians());
+        let turned = sample_animation(&library, &state);
+        assert_eq!(turned.root_position, forward.root_position);
+
+        // The hand swings a quarter turn around the root, at the same height
+        let root = forward.root_position;
+        let hand = forward.get_position(BoneId::LeftWrist) - root;
+        let turned_hand = turned.get_position(BoneId::LeftWrist) - root;
+        assert!(turned_hand.abs_diff_eq(Vec3::new(hand.z, hand.y, -hand.x), 1e-5));
+
+        for bone in BoneId::ALL {
+            let Some(parent) = crate::bone::BONE_HIERARCHY[bone.index()].parent else {
+                continue;
+            };
+            let length =
+                |pose: &RotationPose| pose.get_position(bone).distance(pose.get_position(parent));
+            assert!((length(&turned) - length(&forward)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fixed_timestep_carries_remainder() {
+        let (timestep, steps) = FixedTimestep::default().accumulate(0.100, 0.016);
+        assert_eq!(steps, 6);
+        assert!((timestep.accumulator - 0.004).abs() < 1e-6);
+
+        // Remainder carries into the next frame: 4ms + 12ms = one step
+        let (timestep, steps) = timestep.accumulate(0.012, 0.016);
+        assert_eq!(steps, 1);
+        assert!(timestep.accumulator.abs() < 1e-6);
+    }
+
+    /// Angle between the driven and target rotation of one bone, signed by overshoot
+    fn spine_angle(pose: &RotationPose) -> f32 {
+        let (axis, angle) = pose.local_rotations[BoneId::Spine2.index()].to_axis_angle();
+        angle * axis.x.signum()
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_floor_smoothing_eases_lift() {
+        let dt = 1.0 / 60.0;
+        let standing = RotationPose::bind_pose();
+        let clear = standing.floor_penetration_with_radius(crate::skeleton::BONE_RADIUS);
+        let sunk = standing
+            .clone()
+            .with_root_position(standing.root_position - Vec3::Y * 0.2);
+        let needed = sunk.floor_penetration_with_radius(crate::skeleton::BONE_RADIUS);
+        assert!(needed - clear > 0.1);
+
+        let mut floor = FloorSmoothing::new(0.1);
+        floor.step(standing.clone(), crate::skeleton::BONE_RADIUS, dt);
+        assert_eq!(floor.correction, clear);
+
+        // The step in needed lift is closed gradually and monotonically
+        let mut lifts = Vec::new();
+        for _ in 0..60 {
+            floor.step(sunk.clone(), crate::skeleton::BONE_RADIUS, dt);
+            lifts.push(floor.correction);
+        }
+        assert!(lifts[0] - clear < 0.5 * (needed - clear), "{}", lifts[0]);
+        assert!(lifts.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!((lifts[59] - needed).abs() < 1e-3, "{}", lifts[59]);
+
+        // A zero time constant keeps the instant constraint
+        let mut instant = FloorSmoothing::new(0.0);
+        instant.step(standing, crate::skeleton::BONE_RADIUS, dt);
+        let lifted = instant.step(sunk.clone(), crate::skeleton::BONE_RADIUS, dt);
+        let expected = sunk.apply_floor_constraint_with_radius(crate::skeleton::BONE_RADIUS);
+        assert!((lifted.root_position - expected.root_position).length() < 1e-6);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_secondary_motion_spring() {
+        let dt = 1.0 / 120.0;
+        let rest = RotationPose::bind_pose();
+        let target =
+            RotationPose::bind_pose().with_rotation(BoneId::Spine2, Quat::from_rotation_x(1.0));
+
+        // Stiff, critically damped spring tracks the target closely
+        let mut stiff = SecondaryMotion::new();
+        stiff.step(&rest, dt, 4000.0, 2.0 * 4000f32.sqrt());
+        let mut pose = rest.clone();
+        for _ in 0..24 {
+            pose = stiff.step(&target, dt, 4000.0, 2.0 * 4000f32.sqrt());
+        }
+        assert!(
+            (spine_angle(&pose) - 1.0).abs() < 0.01,
+            "Stiff spring lags: {}",
+            spine_angle(&pose)
+        );
+
+        // Soft, lightly damped spring overshoots, then settles on the target
+        let mut soft = SecondaryMotion::new();
+        soft.step(&rest, dt, 60.0, 3.0);
+        let mut max_angle = 0.0f32;
+        for _ in 0..1200 {
+            pose = soft.step(&target, dt, 60.0, 3.0);
+            max_angle = max_angle.max(spine_angle(&pose));
+        }
+        assert!(
+            max_angle > 1.1,
+            "Expected overshoot, peak was {}",
+            max_angle
+        );
+        assert!(
+            (spine_angle(&pose) - 1.0).abs() < 1e-3,
+            "Did not settle: {}",
+            spine_angle(&pose)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_procedural_idle_is_deterministic_and_subtle() {
+        for time in [0.0, 0.7, 3.2, 100.0] {
+            let a = procedural_idle(time, 42);
+            let b = procedural_idle(time, 42);
+            assert_eq!(a.local_rotations, b.local_rotations);
+            assert_eq!(a.root_position, b.root_position);
+
+            for rotation in a.local_rotations {
+                let angle = rotation.angle_between(Quat::IDENTITY).to_degrees();
+                assert!(angle < 5.0, "Idle rotation too large: {} deg", angle);
+            }
+        }
+
+        // Different seeds give different phases
+        assert_ne!(
+            procedural_idle(1.0, 1).local_rotations,
+            procedural_idle(1.0, 2).local_rotations
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_easing_endpoints_and_monotonic() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+            let mut prev = 0.0;
+            for i in 1..=10 {
+                let value = easing.apply(i as f32 / 10.0);
+                assert!(value > prev, "{:?} not increasing at step {}", easing, i);
+                prev = value;
+            }
+        }
+        assert!(Easing::EaseIn.apply(0.25) < 0.25);
+        assert!(Easing::EaseOut.apply(0.25) > 0.25);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_event_markers_crossed_once_per_loop() {
+        let mut markers = EventMarkers::default();
+        let id = AnimationId::PushUps;
+        markers.add(id, 0.75, "up");
+        markers.add(id, 0.25, "down");
+
+        assert_eq!(markers.crossed(id, 0.0, 0.25, 1.0), ["down"]);
+        // `from` is exclusive, so the next step doesn't repeat it
+        assert!(markers.crossed(id, 0.25, 0.5, 1.0).is_empty());
+        // Wrapping past the clip end, in playback order
+        assert_eq!(markers.crossed(id, 0.5, 1.5, 1.0), ["up", "down"]);
+        assert_eq!(markers.crossed(id, 0.5, 2.5, 1.0).len(), 4);
+        assert!(markers
+            .crossed(AnimationId::Burpees, 0.0, 1.0, 1.0)
+            .is_empty());
+
+        markers.clear(id);
+        assert!(markers.crossed(id, 0.0, 1.0, 1.0).is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_event_markers_fire_in_warped_clip_time() {
+        use crate::engine::Engine;
+
+        let mut engine = Engine::new();
+        let json = r#"{ "n": "clip", "d": 1.0, "kf": [{ "t": 0.0, "p": {} }] }"#;
+        engine.load_animation(AnimationId::PushUps, json).unwrap();
+        engine.set_exercise(AnimationId::PushUps);
+        engine
+            .event_markers
+            .add(AnimationId::PushUps, 0.5, "bottom");
+        // The first second of playback covers only the first quarter of the clip
+        engine.playback.time_warp =
+            Some(TimeWarp::new(vec![(0.0, 0.0), (1.0, 0.25), (1.5, 1.0)]).unwrap());
+
+        // Same steps as `App::advance_time`
+        let mut step = |delta: f32| {
+            let from = engine.playback.sample_time();
+            engine.advance_time(delta);
+            engine.crossed_events(from)
+        };
+        // Playback time passes 0.5, clip time doesn't
+        assert!(step(1.0).is_empty());
+        // Clip time 0.5 is reached at playback time 1 + 0.25 / 1.5
+        assert!(step(0.1).is_empty());
+        assert_eq!(step(0.1), ["bottom"]);
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test]
+    fn test_event_callback_receives_crossed_label() {
+        use crate::engine::Engine;
+        use std::rc::Rc;
+        use wasm_bindgen::JsCast;
+
+        let mut engine = Engine::new();
+        let json = r#"{ "n": "clip", "d": 1.0, "kf": [{ "t": 0.0, "p": {} }] }"#;
+        engine.load_animation(AnimationId::PushUps, json).unwrap();
+        engine.set_exercise(AnimationId::PushUps);
+        engine
+            .event_markers
+            .add(AnimationId::PushUps, 0.5, "bottom");
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&received);
+        let closure = Closure::<dyn FnMut(String)>::new(move |label| sink.borrow_mut().push(label));
+        EVENT_CALLBACK.set(Some(
+            closure.as_ref().unchecked_ref::<js_sys::Function>().clone(),
+        ));
+
+        // Same steps as `App::advance_time`
+        for delta in [0.25, 0.5] {
+            let from = engine.playback.sample_time();
+            engine.advance_time(delta);
+            dispatch_events(&engine.crossed_events(from));
+        }
+        EVENT_CALLBACK.set(None);
+        assert_eq!(*received.borrow(), ["bottom"]);
+    }
 }
 
 // App methods for animation
 #[cfg(target_arch = "wasm32")]
 use crate::state::App;
 
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// JS function receiving event marker labels (wasm runs on a single thread)
+    static EVENT_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Call the registered event callback with each label, in order
+/// The callback is cloned out first, so it may itself (un)register callbacks.
+fn dispatch_events(labels: &[String]) {
+    if labels.is_empty() {
+        return;
+    }
+    let Some(callback) = EVENT_CALLBACK.with_borrow(Clone::clone) else {
+        return;
+    };
+    for label in labels {
+        if let Err(err) = callback.call1(&JsValue::NULL, &JsValue::from_str(label)) {
+            log::warn!("Event callback for {:?} threw: {:?}", label, err);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+/// Bind pose with local rotations from flat [x, y, z, w] per bone
+fn pose_from_rotations(rotations: &[f32]) -> Result<RotationPose, JsValue> {
+    if rotations.len() != BoneId::COUNT * 4 {
+        return Err(JsValue::from_str("Expected one quaternion per bone"));
+    }
+    Ok(BoneId::ALL
+        .iter()
+        .zip(rotations.chunks_exact(4))
+        .fold(RotationPose::bind_pose(), |pose, (&bone, q)| {
+            pose.with_rotation(bone, Quat::from_slice(q).normalize())
+        }))
+}
+
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 impl App {
     /// Set the current exercise for animation
     pub fn set_exercise(&mut self, id: AnimationId) {
-        self.state.playback = self.state.playback.clone().set_exercise(id);
+        self.state.engine.set_exercise(id);
         log::info!("Exercise set to: {:?}", id);
     }
 
     /// Load an animation clip from JSON string
     /// Call this during startup for each exercise you want to animate
     pub fn load_animation(&mut self, id: AnimationId, json_data: String) -> Result<(), JsValue> {
-        // Use from_json helper because RotationAnimationClip doesn't impl Deserialize directly
-        let clip = RotationAnimationClip::from_json(&json_data)
-            .map_err(|e| JsValue::from_str(&format!("Failed to parse JSON: {}", e)))?;
-
-        self.state.animation_library.add_clip(id, clip);
-
-        Ok(())
+        self.state
+            .engine
+            .load_animation(id, &json_data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse JSON: {}", e)))
     }
 
     /// Load an animation clip from binary data
     /// This is the preferred method for production - smaller files, faster parsing
+    /// Accepts both the full and the delta-encoded format.
     pub fn load_animation_binary(&mut self, id: AnimationId, data: &[u8]) -> Result<(), JsValue> {
-        let name = format!("{:?}", id);
-        let clip = RotationAnimationClip::from_binary(data, name)
-            .map_err(|e| JsValue::from_str(&format!("Failed to parse binary: {}", e)))?;
+        self.state
+            .engine
+            .load_animation_binary(id, data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse binary: {}", e)))
+    }
 
-        self.state.animation_library.add_clip(id, clip);
+    /// Load an IK-driven clip (end-effector targets per keyframe) from JSON
+    /// Poses are solved at playback time; replaces any clip loaded for `id`.
+    pub fn load_constraint_animation(
+        &mut self,
+        id: AnimationId,
+        json_data: String,
+    ) -> Result<(), JsValue> {
+        self.state
+            .engine
+            .load_constraint_animation(id, &json_data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse JSON: {}", e)))
+    }
+
+    /// Export a loaded IK-driven clip as JSON
+    pub fn export_constraint_animation(&self, id: AnimationId) -> Result<String, JsValue> {
+        let clip = self
+            .state
+            .engine
+            .animation_library
+            .get_constraint_clip(id)
+            .ok_or_else(|| JsValue::from_str("No constraint clip loaded for this id"))?;
+        clip.to_json_string()
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize JSON: {}", e)))
+    }
+
+    /// Score a live pose against the current playback pose (1 = match, 0 = far off)
+    /// `rotations` holds one local [x, y, z, w] quaternion per bone, by bone index.
+    pub fn score_pose(&self, rotations: &[f32]) -> Result<f32, JsValue> {
+        let live = pose_from_rotations(rotations)?;
+        let engine = &self.state.engine;
+        Ok(live.similarity(&sample_animation(
+            &engine.animation_library,
+            &engine.playback,
+        )))
+    }
+
+    /// Index of the bone deviating most from the current playback pose
+    /// Same input as `score_pose`.
+    pub fn worst_pose_bone(&self, rotations: &[f32]) -> Result<usize, JsValue> {
+        let live = pose_from_rotations(rotations)?;
+        let engine = &self.state.engine;
+        let target = sample_animation(&engine.animation_library, &engine.playback);
+        Ok(live.worst_bone(&target).index())
+    }
+
+    /// Set how many sampled poses are cached for repeated same-time samples
+    /// 0 disables the cache. The default is `DEFAULT_SAMPLE_CACHE_CAPACITY`.
+    pub fn set_sample_cache_capacity(&mut self, capacity: usize) {
+        self.state
+            .engine
+            .animation_library
+            .set_sample_cache_capacity(capacity);
+    }
+
+    /// Unload an animation clip to free its memory
+    /// If it is the current exercise, playback falls back to the Placeholder
+    /// clip, or to the bind pose when no Placeholder is loaded.
+    pub fn unload_animation(&mut self, id: AnimationId) -> bool {
+        self.state.engine.animation_library.remove_clip(id)
+    }
+
+    /// Unload all animation clips
+    pub fn clear_animations(&mut self) {
+        self.state.engine.animation_library.clear();
+    }
 
+    /// Place a loaded animation clip in the 2D blend space at (x, y)
+    /// Returns false if the clip is not loaded.
+    pub fn add_blend_space_sample(&mut self, id: AnimationId, x: f32, y: f32) -> bool {
+        let Some(clip) = self.state.engine.animation_library.get_clip(id) else {
+            return false;
+        };
+        self.state.engine.blend_space.add_sample(x, y, clip.clone());
+        true
+    }
+
+    /// Remove all blend space samples
+    pub fn clear_blend_space(&mut self) {
+        self.state.engine.blend_space.clear();
+    }
+
+    /// Set the 2D blend parameter (e.g. forward speed, turn rate)
+    pub fn set_blend_parameter(&mut self, x: f32, y: f32) {
+        self.state.engine.blend_parameter = Vec2::new(x, y);
+    }
+
+    /// Update skeleton from the blend space at the current playback time
+    /// Call this every frame instead of `update_skeleton_from_playback` for locomotion.
+    pub fn update_skeleton_from_blend_space(&mut self) {
+        let parameter = self.state.engine.blend_parameter;
+        let pose = self
+            .state
+            .engine
+            .blend_space
+            .sample(parameter.x, parameter.y, self.state.engine.playback.time)
+            .apply_floor_constraint_with_radius(self.state.engine.radii.bone);
+        self.update_bone_uniforms(&pose.compute_bone_matrices());
+    }
+
+    /// Remap playback time through a piecewise-linear curve before sampling
+    /// `points` are flat `[input, output, ...]` pairs in milliseconds, starting at
+    /// input 0; the curve repeats past its last point. An empty array removes the warp.
+    pub fn set_time_warp(&mut self, points: Vec<f32>) -> Result<(), JsValue> {
+        let warp = match points.is_empty() {
+            true => None,
+            false => {
+                if !points.len().is_multiple_of(2) {
+                    return Err(JsValue::from_str("time warp needs [input, output] pairs"));
+                }
+                let pairs = points
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0] / 1000.0, pair[1] / 1000.0))
+                    .collect();
+                Some(TimeWarp::new(pairs).map_err(JsValue::from_str)?)
+            }
+        };
+        self.state.engine.playback.time_warp = warp;
         Ok(())
     }
 
+    /// Turn the animated character to face `yaw_degrees` about the vertical axis
+    /// Clips are authored facing +Z; 90 faces +X. Kept across exercise changes.
+    pub fn set_facing(&mut self, yaw_degrees: f32) {
+        self.state.engine.playback.facing = Quat::from_rotation_y(yaw_degrees.to_radians());
+    }
+
+    /// Show the nearest keyframe exactly instead of interpolating, e.g. while scrubbing
+    pub fn set_snap_to_keyframe(&mut self, enabled: bool) {
+        self.state.engine.playback.snap_to_keyframe = enabled;
+    }
+
     /// Advance simulation time (call each frame with delta time)
     pub fn advance_time(&mut self, delta_ms: f32) {
-        let delta_secs = delta_ms / 1000.0;
-        self.state.playback.time += delta_secs;
+        let from = self.state.engine.playback.sample_time();
+        self.state.engine.advance_time(delta_ms / 1000.0);
+        dispatch_events(&self.state.engine.crossed_events(from));
+    }
+
+    /// Call `callback(label)` whenever playback passes an event marker
+    /// Replaces any previous callback. Markers fire from `advance_time`,
+    /// `advance_time_fixed` and `tick`.
+    pub fn register_event_callback(&mut self, callback: js_sys::Function) {
+        EVENT_CALLBACK.set(Some(callback));
+    }
+
+    /// Stop calling the registered event callback
+    pub fn clear_event_callback(&mut self) {
+        EVENT_CALLBACK.set(None);
+    }
+
+    /// Mark clip time `time` (seconds) of `id` with `label` for the event callback
+    pub fn add_event_marker(&mut self, id: AnimationId, time: f32, label: &str) {
+        self.state.engine.event_markers.add(id, time, label);
+    }
+
+    /// Remove all event markers of a clip
+    pub fn clear_event_markers(&mut self, id: AnimationId) {
+        self.state.engine.event_markers.clear(id);
+    }
+
+    /// Advance simulation time by the `performance.now()` time since the last tick
+    /// Alternative to `advance_time` for hosts that don't track frame deltas.
+    /// Returns the milliseconds advanced (0 on the first call).
+    pub fn tick(&mut self) -> f32 {
+        let Some(clock) = crate::time::PerformanceTimeSource::new() else {
+            return 0.0;
+        };
+        let from = self.state.engine.playback.sample_time();
+        let advanced = self.state.engine.tick(&clock);
+        dispatch_events(&self.state.engine.crossed_events(from));
+        advanced * 1000.0
+    }
+
+    /// Update skeleton with the procedural idle at the current playback time
+    /// Call this every frame instead of `update_skeleton_from_playback` for the empty state.
+    pub fn update_skeleton_from_idle(&mut self, seed: u32) {
+        let pose = procedural_idle(self.state.engine.playback.time, seed)
+            .apply_floor_constraint_with_radius(self.state.engine.radii.bone);
+        self.update_bone_uniforms(&pose.compute_bone_matrices());
+    }
+
+    /// Ease the floor constraint's root lift over `time_constant_ms` (0 = instant)
+    /// Avoids popping when a foot dips below the floor between keyframes.
+    pub fn set_floor_smoothing(&mut self, time_constant_ms: f32) {
+        self.state.engine.floor_smoothing = FloorSmoothing::new(time_constant_ms.max(0.0) / 1000.0);
+    }
+
+    /// Enable spring-driven secondary motion (follow-through) during playback
+    /// `damping` of `2 * sqrt(stiffness)` is critically damped (no overshoot).
+    pub fn set_secondary_motion(&mut self, enabled: bool, stiffness: f32, damping: f32) {
+        self.state.engine.spring = enabled.then_some(SpringSettings { stiffness, damping });
+        self.state.engine.secondary_motion.reset();
+    }
+
+    /// Enable an ambient breeze swaying the head and arms during playback
+    /// (dx, dy, dz) is the direction the wind blows toward; strength 1 is light.
+    pub fn set_wind(&mut self, enabled: bool, strength: f32, dx: f32, dy: f32, dz: f32) {
+        self.state.engine.wind = enabled.then_some(WindSettings {
+            strength,
+            direction: Vec3::new(dx, dy, dz),
+        });
+    }
+
+    /// Advance simulation time in fixed `step_ms` increments
+    /// Leftover time is carried to the next call. Returns the number of steps taken.
+    pub fn advance_time_fixed(&mut self, delta_ms: f32, step_ms: f32) -> u32 {
+        let step_secs = step_ms / 1000.0;
+        let (timestep, steps) = self
+            .state
+            .engine
+            .timestep
+            .accumulate(delta_ms / 1000.0, step_secs);
+        self.state.engine.timestep = timestep;
+        for _ in 0..steps {
+            let from = self.state.engine.playback.sample_time();
+            self.state.engine.playback = self.state.engine.playback.clone().advance(step_secs);
+            dispatch_events(&self.state.engine.crossed_events(from));
+        }
+        steps
     }
 }
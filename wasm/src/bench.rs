@@ -1,4 +1,5 @@
 use crate::math::{Mat4, Mat4Extended};
+use crate::time::{PerformanceTimeSource, TimeSource};
 use wasm_bindgen::prelude::*;
 
 #[derive(serde::Serialize)]
@@ -19,10 +20,15 @@ pub struct BenchmarkResults {
 /// Run performance comparison between different Matrix Multiply implementations
 #[wasm_bindgen]
 pub fn run_benchmarks(iterations: i32) -> JsValue {
+    let clock = PerformanceTimeSource::new().expect("should have performance");
+    let result = run_benchmarks_with(&clock, iterations);
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// Run the benchmarks timed by `clock`
+pub fn run_benchmarks_with(clock: &dyn TimeSource, iterations: i32) -> BenchmarkResults {
     use std::hint::black_box;
 
-    let window = web_sys::window().expect("should have a window");
-    let perf = window.performance().expect("should have performance");
     let m1 = black_box(Mat4::IDENTITY);
     let m2 = black_box(Mat4::IDENTITY);
 
@@ -45,20 +51,20 @@ pub fn run_benchmarks(iterations: i32) -> JsValue {
     }
 
     // 1. Scalar (Handwritten)
-    let start = perf.now();
+    let start = clock.now_ms();
     for _ in 0..iterations {
         black_box(m1.multiply_scalar(&m2));
     }
-    let scalar_time = perf.now() - start;
+    let scalar_time = clock.now_ms() - start;
 
     // 2. Portable SIMD (Handwritten)
     #[cfg(feature = "portable_simd")]
     let simd_time = {
-        let start = perf.now();
+        let start = clock.now_ms();
         for _ in 0..iterations {
             black_box(m1.multiply_std_simd(&m2));
         }
-        Some(perf.now() - start)
+        Some(clock.now_ms() - start)
     };
     #[cfg(not(feature = "portable_simd"))]
     let simd_time: Option<f64> = None;
@@ -66,39 +72,39 @@ pub fn run_benchmarks(iterations: i32) -> JsValue {
     // 3. Relaxed SIMD (Handwritten)
     #[cfg(all(target_arch = "wasm32", target_feature = "relaxed-simd"))]
     let fma_result = {
-        let start = perf.now();
+        let start = clock.now_ms();
         for _ in 0..iterations {
             black_box(m1.multiply_relaxed_simd(&m2));
         }
-        Some(perf.now() - start)
+        Some(clock.now_ms() - start)
     };
     #[cfg(not(all(target_arch = "wasm32", target_feature = "relaxed-simd")))]
     let fma_result: Option<f64> = None;
 
     // 4. Glam (Library implementation)
-    let start = perf.now();
+    let start = clock.now_ms();
     for _ in 0..iterations {
         black_box(m1 * m2);
     }
-    let glam_time = perf.now() - start;
+    let glam_time = clock.now_ms() - start;
 
     // --- Transpose Benchmarks ---
 
     // 1. Transpose Scalar
-    let start = perf.now();
+    let start = clock.now_ms();
     for _ in 0..iterations {
         black_box(m1.transpose_scalar());
     }
-    let t_scalar_time = perf.now() - start;
+    let t_scalar_time = clock.now_ms() - start;
 
     // 2. Transpose std_simd
     #[cfg(feature = "portable_simd")]
     let t_simd_time = {
-        let start = perf.now();
+        let start = clock.now_ms();
         for _ in 0..iterations {
             black_box(m1.transpose_std_simd());
         }
-        Some(perf.now() - start)
+        Some(clock.now_ms() - start)
     };
     #[cfg(not(feature = "portable_simd"))]
     let t_simd_time: Option<f64> = None;
@@ -106,23 +112,23 @@ pub fn run_benchmarks(iterations: i32) -> JsValue {
     // 3. Transpose Relaxed SIMD
     #[cfg(all(target_arch = "wasm32", target_feature = "relaxed-simd"))]
     let t_relaxed_time = {
-        let start = perf.now();
+        let start = clock.now_ms();
         for _ in 0..iterations {
             black_box(m1.transpose_relaxed_simd());
         }
-        Some(perf.now() - start)
+        Some(clock.now_ms() - start)
     };
     #[cfg(not(all(target_arch = "wasm32", target_feature = "relaxed-simd")))]
     let t_relaxed_time: Option<f64> = None;
 
     // 4. Transpose Glam
-    let start = perf.now();
+    let start = clock.now_ms();
     for _ in 0..iterations {
         black_box(m1.transpose());
     }
-    let t_glam_time = perf.now() - start;
+    let t_glam_time = clock.now_ms() - start;
 
-    let result = BenchmarkResults {
+    BenchmarkResults {
         iterations,
         scalar_ms: scalar_time,
         simd_std_ms: simd_time,
@@ -133,7 +139,5 @@ pub fn run_benchmarks(iterations: i32) -> JsValue {
         transpose_std_ms: t_simd_time,
         transpose_relaxed_ms: t_relaxed_time,
         transpose_glam_ms: t_glam_time,
-    };
-
-    serde_wasm_bindgen::to_value(&result).unwrap()
+    }
 }
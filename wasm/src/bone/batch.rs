@@ -0,0 +1,50 @@
+//! Batch forward kinematics for throughput work (baking, blend-space sampling)
+//!
+//! `RotationPose` is built for interactive edits: lazy per-bone dirty tracking
+//! behind a `RefCell`. When thousands of poses are evaluated at once that
+//! bookkeeping dominates, so this path takes plain rotation arrays, hoists the
+//! rest-pose bone vectors out of the loop and writes positions straight out.
+
+use glam::{Quat, Vec3};
+
+use super::pose::stub_corrective;
+use super::{BoneId, BONE_HIERARCHY};
+
+/// Rest-length bone vectors in the parent's frame, and parent indices
+fn bone_offsets() -> ([Vec3; BoneId::COUNT], [Option<usize>; BoneId::COUNT]) {
+    let offsets = BONE_HIERARCHY.map(|def| def.direction.normalize() * def.length);
+    let parents = BONE_HIERARCHY.map(|def| def.parent.map(|p| p.index()));
+    (offsets, parents)
+}
+
+/// World joint positions for many poses at rest bone lengths
+///
+/// Pose `i` is `rotations[i]` (local, indexed by `BoneId`) rooted at `roots[i]`;
+/// its positions are written to `out_positions[i]`. Matches
+/// `RotationPose::get_position` for poses with `rest_bone_lengths`.
+///
+/// # Panics
+/// If the three slices differ in length.
+pub fn compute_fk_batch(
+    rotations: &[[Quat; BoneId::COUNT]],
+    roots: &[Vec3],
+    out_positions: &mut [[Vec3; BoneId::COUNT]],
+) {
+    assert_eq!(rotations.len(), roots.len(), "one root per pose");
+    assert_eq!(rotations.len(), out_positions.len(), "one output per pose");
+
+    let (offsets, parents) = bone_offsets();
+    let mut world_rotations = [Quat::IDENTITY; BoneId::COUNT];
+    for ((local, &root), positions) in rotations.iter().zip(roots).zip(out_positions) {
+        // Parents precede children, so one pass in index order suffices
+        for bone in 0..BoneId::COUNT {
+            let (parent_pos, parent_rot) = match parents[bone] {
+                Some(parent) => (positions[parent], world_rotations[parent]),
+                None => (root, Quat::IDENTITY),
+            };
+            world_rotations[bone] = parent_rot * local[bone];
+            let stub_tilt = stub_corrective(BoneId::ALL[bone], local[bone]);
+            positions[bone] = parent_pos + parent_rot * (stub_tilt * offsets[bone]);
+        }
+    }
+}
@@ -88,6 +88,12 @@ impl DirtyFlags {
     pub fn cleared() -> Self {
         Self(0)
     }
+
+    /// Raw bitset (bit i set = bone with index i is dirty)
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
 }
 
 /// Cache for forward kinematics results
@@ -1,6 +1,8 @@
+use super::constraint::constraint_chain;
 use super::id::BoneId;
+use super::mask::BoneMask;
 use super::pose::RotationPose;
-use glam::Quat;
+use glam::{Quat, Vec4};
 use half::f16; // Note: We use the 'half' crate because the native WASM target does not support f16
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +17,141 @@ fn q15_to_f32(bytes: [u8; 2]) -> f32 {
     val as f32 / 32767.0
 }
 
+/// Convert f32 in [-1.0, 1.0] to Q1.15 signed fixed-point
+fn f32_to_q15(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+/// Rotation as stored in binary clips: Q1.15 XYZ, W reconstructed as non-negative
+type QuantizedRotation = [i16; 3];
+
+/// Root position as stored in binary clips: f16 bits
+type QuantizedRoot = [u16; 3];
+
+/// One keyframe as stored in binary clips
+type QuantizedFrame = (QuantizedRoot, [QuantizedRotation; BoneId::COUNT]);
+
+fn quantize_rotation(q: Quat) -> QuantizedRotation {
+    // W is dropped, so store the hemisphere where it is non-negative
+    let q = if q.w < 0.0 { -q } else { q };
+    [f32_to_q15(q.x), f32_to_q15(q.y), f32_to_q15(q.z)]
+}
+
+fn dequantize_rotation([x, y, z]: QuantizedRotation) -> Quat {
+    let (x, y, z) = (x as f32 / 32767.0, y as f32 / 32767.0, z as f32 / 32767.0);
+    // Reconstruct W: w^2 + x^2 + y^2 + z^2 = 1.0
+    let sum_sq = x * x + y * y + z * z;
+    let w = (1.0 - sum_sq).max(0.0).sqrt();
+    Quat::from_xyzw(x, y, z, w).normalize()
+}
+
+fn quantize_root(root: glam::Vec3) -> QuantizedRoot {
+    root.to_array().map(|c| f16::from_f32(c).to_bits())
+}
+
+fn dequantize_root(bits: QuantizedRoot) -> glam::Vec3 {
+    glam::Vec3::from_array(bits.map(|b| f16::from_bits(b).to_f32()))
+}
+
+/// Leading bytes of a delta-encoded clip: "JVD" then the format version
+///
+/// Read as a full-format header this would be 22090 keyframes over a ~2e-5 s
+/// duration, so the two formats can be told apart from the first bytes.
+const DELTA_MAGIC: [u8; 4] = *b"JVD\x01";
+
+/// Bit of `dynamic_mask` marking the root position as animated
+const ROOT_DYNAMIC_BIT: u32 = 1 << BoneId::COUNT;
+
+/// Append a signed value as a zigzag LEB128 varint (small magnitudes take one byte)
+fn write_varint(out: &mut Vec<u8>, value: i32) {
+    let mut zigzag = ((value << 1) ^ (value >> 31)) as u32;
+    loop {
+        let byte = (zigzag & 0x7f) as u8;
+        zigzag >>= 7;
+        if zigzag == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Read a zigzag LEB128 varint written by `write_varint`
+fn read_varint(data: &[u8], offset: &mut usize) -> Result<i32, &'static str> {
+    let mut zigzag = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *data
+            .get(*offset)
+            .ok_or("Binary data truncated in delta values")?;
+        *offset += 1;
+        zigzag |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((zigzag >> 1) as i32 ^ -((zigzag & 1) as i32));
+        }
+    }
+    Err("Binary delta value too long")
+}
+
+/// Fixed part of both binary formats: counts, which channels animate, and the
+/// values of every channel at the first keyframe
+struct BinaryHeader {
+    keyframe_count: usize,
+    duration: f32,
+    dynamic_mask: u32,
+    base_root: QuantizedRoot,
+    base_rotations: [QuantizedRotation; BoneId::COUNT],
+}
+
+impl BinaryHeader {
+    /// Size in bytes: 8 byte header + 6 byte root + 22 * 6 byte rotations
+    const SIZE: usize = 8 + 6 + BoneId::COUNT * 6;
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.keyframe_count as u16).to_le_bytes());
+        out.extend_from_slice(&f16::from_f32(self.duration).to_le_bytes());
+        out.extend_from_slice(&self.dynamic_mask.to_le_bytes());
+        for bits in self.base_root {
+            out.extend_from_slice(&bits.to_le_bytes());
+        }
+        for component in self.base_rotations.iter().flatten() {
+            out.extend_from_slice(&component.to_le_bytes());
+        }
+    }
+
+    fn read(data: &[u8]) -> Result<Self, &'static str> {
+        if data.len() < 8 {
+            return Err("Binary data too short for header");
+        }
+        if data.len() < 8 + 6 {
+            return Err("Binary data too short for base root");
+        }
+        if data.len() < Self::SIZE {
+            return Err("Binary data too short for base rotations");
+        }
+        let u16_at = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let mut base_rotations = [[0i16; 3]; BoneId::COUNT];
+        for (i, rotation) in base_rotations.iter_mut().enumerate() {
+            *rotation = std::array::from_fn(|c| u16_at(14 + i * 6 + c * 2) as i16);
+        }
+        Ok(Self {
+            keyframe_count: u16_at(0) as usize,
+            duration: f16::from_bits(u16_at(2)).to_f32(),
+            dynamic_mask: u32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+            base_root: [u16_at(8), u16_at(10), u16_at(12)],
+            base_rotations,
+        })
+    }
+}
+
+/// Keyframes are evenly spaced over the duration in both binary formats
+fn binary_keyframe_time(index: usize, keyframe_count: usize, duration: f32) -> f32 {
+    if keyframe_count > 1 {
+        duration * (index as f32) / ((keyframe_count - 1) as f32)
+    } else {
+        0.0
+    }
+}
+
 // ============================================================================
 // Animation System
 // ============================================================================
@@ -33,12 +170,82 @@ pub struct EulerAngles {
 impl EulerAngles {
     /// Convert to quaternion (XYZ order)
     pub fn to_quat(&self) -> Quat {
-        Quat::from_euler(
-            glam::EulerRot::XYZ,
-            self.x.to_radians(),
-            self.y.to_radians(),
-            self.z.to_radians(),
-        )
+        self.to_quat_with_order(glam::EulerRot::XYZ)
+    }
+
+    /// Convert from quaternion (XYZ order, degrees)
+    pub fn from_quat(q: Quat) -> Self {
+        Self::from_quat_with_order(q, glam::EulerRot::XYZ)
+    }
+
+    /// Convert to quaternion, applying the angles in the given order
+    /// `x`, `y` and `z` stay the angles about those axes whatever the order.
+    /// Only the six Tait-Bryan orders (XYZ, ZYX, ...) are meaningful here.
+    pub fn to_quat_with_order(&self, order: glam::EulerRot) -> Quat {
+        let (a, b, c) = order_axes(order, [self.x, self.y, self.z]);
+        Quat::from_euler(order, a.to_radians(), b.to_radians(), c.to_radians())
+    }
+
+    /// Convert from quaternion to angles (degrees) for the given order
+    pub fn from_quat_with_order(q: Quat, order: glam::EulerRot) -> Self {
+        let (a, b, c) = q.to_euler(order);
+        let mut angles = [0.0; 3];
+        for (axis, angle) in order_axis_indices(order).into_iter().zip([a, b, c]) {
+            angles[axis] = angle.to_degrees();
+        }
+        let [x, y, z] = angles;
+        Self { x, y, z }
+    }
+}
+
+/// Axis indices (0 = X, 1 = Y, 2 = Z) in the order glam applies them for `order`
+fn order_axis_indices(order: glam::EulerRot) -> [usize; 3] {
+    use glam::EulerRot;
+    match order {
+        EulerRot::XZY => [0, 2, 1],
+        EulerRot::YXZ => [1, 0, 2],
+        EulerRot::YZX => [1, 2, 0],
+        EulerRot::ZXY => [2, 0, 1],
+        EulerRot::ZYX => [2, 1, 0],
+        _ => [0, 1, 2],
+    }
+}
+
+/// Per-axis angles rearranged into the argument order of `Quat::from_euler`
+fn order_axes(order: glam::EulerRot, xyz: [f32; 3]) -> (f32, f32, f32) {
+    let [a, b, c] = order_axis_indices(order);
+    (xyz[a], xyz[b], xyz[c])
+}
+
+/// Order a clip's Euler angles are applied in (JSON `"eo"`, e.g. `"zyx"`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EulerOrder {
+    #[default]
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl EulerOrder {
+    /// Matching glam rotation order
+    pub fn to_euler_rot(self) -> glam::EulerRot {
+        use glam::EulerRot;
+        match self {
+            EulerOrder::Xyz => EulerRot::XYZ,
+            EulerOrder::Xzy => EulerRot::XZY,
+            EulerOrder::Yxz => EulerRot::YXZ,
+            EulerOrder::Yzx => EulerRot::YZX,
+            EulerOrder::Zxy => EulerRot::ZXY,
+            EulerOrder::Zyx => EulerRot::ZYX,
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == EulerOrder::default()
     }
 }
 
@@ -74,8 +281,13 @@ pub enum BoneRotation {
 
 impl BoneRotation {
     pub fn to_quat(&self) -> Quat {
+        self.to_quat_with_order(glam::EulerRot::XYZ)
+    }
+
+    /// Convert to quaternion, reading Euler angles in the given order
+    pub fn to_quat_with_order(&self, order: glam::EulerRot) -> Quat {
         match self {
-            BoneRotation::Euler(e) => e.to_quat(),
+            BoneRotation::Euler(e) => e.to_quat_with_order(order),
             BoneRotation::Quaternion(q) => q.to_quat(),
         }
     }
@@ -135,8 +347,13 @@ pub struct RotationPoseJson {
 }
 
 impl RotationPoseJson {
-    /// Convert JSON pose to RotationPose
+    /// Convert JSON pose to RotationPose (Euler angles in XYZ order)
     pub fn to_rotation_pose(&self) -> RotationPose {
+        self.to_rotation_pose_with_order(glam::EulerRot::XYZ)
+    }
+
+    /// Convert JSON pose to RotationPose, reading Euler angles in the given order
+    pub fn to_rotation_pose_with_order(&self, order: glam::EulerRot) -> RotationPose {
         let mut pose = RotationPose::bind_pose();
 
         // Apply root position if specified
@@ -146,70 +363,70 @@ impl RotationPoseJson {
 
         // Apply rotations for each bone if specified
         if let Some(rot) = self.pelvis {
-            pose.local_rotations[BoneId::Pelvis.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Pelvis.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_hip {
-            pose.local_rotations[BoneId::LeftHip.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftHip.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_hip {
-            pose.local_rotations[BoneId::RightHip.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightHip.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.spine1 {
-            pose.local_rotations[BoneId::Spine1.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Spine1.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_knee {
-            pose.local_rotations[BoneId::LeftKnee.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftKnee.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_knee {
-            pose.local_rotations[BoneId::RightKnee.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightKnee.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.spine2 {
-            pose.local_rotations[BoneId::Spine2.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Spine2.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_ankle {
-            pose.local_rotations[BoneId::LeftAnkle.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftAnkle.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_ankle {
-            pose.local_rotations[BoneId::RightAnkle.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightAnkle.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.spine3 {
-            pose.local_rotations[BoneId::Spine3.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Spine3.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_foot {
-            pose.local_rotations[BoneId::LeftFoot.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftFoot.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_foot {
-            pose.local_rotations[BoneId::RightFoot.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightFoot.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.neck {
-            pose.local_rotations[BoneId::Neck.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Neck.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_collar {
-            pose.local_rotations[BoneId::LeftCollar.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftCollar.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_collar {
-            pose.local_rotations[BoneId::RightCollar.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightCollar.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.head {
-            pose.local_rotations[BoneId::Head.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::Head.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_shoulder {
-            pose.local_rotations[BoneId::LeftShoulder.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftShoulder.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_shoulder {
-            pose.local_rotations[BoneId::RightShoulder.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightShoulder.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_elbow {
-            pose.local_rotations[BoneId::LeftElbow.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftElbow.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_elbow {
-            pose.local_rotations[BoneId::RightElbow.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightElbow.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.l_wrist {
-            pose.local_rotations[BoneId::LeftWrist.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::LeftWrist.index()] = rot.to_quat_with_order(order);
         }
         if let Some(rot) = self.r_wrist {
-            pose.local_rotations[BoneId::RightWrist.index()] = rot.to_quat();
+            pose.local_rotations[BoneId::RightWrist.index()] = rot.to_quat_with_order(order);
         }
 
         pose
@@ -219,8 +436,9 @@ impl RotationPoseJson {
     pub fn from_pose(pose: &RotationPose) -> Self {
         let mut json = RotationPoseJson::default();
 
-        // Only include root position if non-zero
-        if pose.root_position.length_squared() > 1e-6 {
+        // Omit the root only when it is the bind root that import falls back to,
+        // so authored roots (including the origin) round-trip
+        if pose.root_position != RotationPose::bind_pose().root_position {
             json.root_position = Some(pose.root_position.to_array());
         }
 
@@ -262,11 +480,98 @@ impl RotationPoseJson {
     }
 }
 
+/// Kochanek-Bartels spline controls for a keyframe
+///
+/// All zero is a standard Catmull-Rom spline. Tension tightens the curve
+/// (1 = zero tangents), continuity sharpens the corner at the keyframe and
+/// bias shifts the curve toward the previous (< 0) or next (> 0) keyframe.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tcb {
+    pub tension: f32,
+    pub continuity: f32,
+    pub bias: f32,
+}
+
+impl Tcb {
+    /// Build from optional JSON fields; None if none are present
+    fn from_json(tension: Option<f32>, continuity: Option<f32>, bias: Option<f32>) -> Option<Self> {
+        if tension.is_none() && continuity.is_none() && bias.is_none() {
+            return None;
+        }
+        Some(Self {
+            tension: tension.unwrap_or(0.0),
+            continuity: continuity.unwrap_or(0.0),
+            bias: bias.unwrap_or(0.0),
+        })
+    }
+
+    /// Incoming and outgoing tangents at `p1` given its neighbors
+    fn tangents<T>(self, p0: T, p1: T, p2: T) -> (T, T)
+    where
+        T: Copy
+            + std::ops::Add<Output = T>
+            + std::ops::Sub<Output = T>
+            + std::ops::Mul<f32, Output = T>,
+    {
+        let Tcb {
+            tension: t,
+            continuity: c,
+            bias: b,
+        } = self;
+        let (before, after) = (p1 - p0, p2 - p1);
+        let incoming = before * ((1.0 - t) * (1.0 - c) * (1.0 + b) * 0.5)
+            + after * ((1.0 - t) * (1.0 + c) * (1.0 - b) * 0.5);
+        let outgoing = before * ((1.0 - t) * (1.0 + c) * (1.0 + b) * 0.5)
+            + after * ((1.0 - t) * (1.0 - c) * (1.0 - b) * 0.5);
+        (incoming, outgoing)
+    }
+}
+
+/// Cubic Hermite interpolation of the segment `p1 -> p2` using TCB tangents
+fn tcb_interpolate<T>(points: [T; 4], tcb1: Tcb, tcb2: Tcb, s: f32) -> T
+where
+    T: Copy
+        + std::ops::Add<Output = T>
+        + std::ops::Sub<Output = T>
+        + std::ops::Mul<f32, Output = T>,
+{
+    let [p0, p1, p2, p3] = points;
+    let (_, out1) = tcb1.tangents(p0, p1, p2);
+    let (in2, _) = tcb2.tangents(p1, p2, p3);
+
+    let (s2, s3) = (s * s, s * s * s);
+    p1 * (2.0 * s3 - 3.0 * s2 + 1.0)
+        + out1 * (s3 - 2.0 * s2 + s)
+        + p2 * (-2.0 * s3 + 3.0 * s2)
+        + in2 * (s3 - s2)
+}
+
 /// A keyframe in a rotation-based animation
 #[derive(Debug, Clone)]
 pub struct RotationKeyframe {
     pub time: f32,
     pub pose: RotationPose,
+    /// Spline controls; segments touching a keyframe with TCB use a
+    /// Kochanek-Bartels spline instead of slerp
+    pub tcb: Option<Tcb>,
+}
+
+impl RotationKeyframe {
+    /// Same pose and spline controls, within `tol` per quaternion and root component
+    /// Times are not compared; `q` and `-q` count as the same rotation.
+    pub fn approx_eq(&self, other: &RotationKeyframe, tol: f32) -> bool {
+        self.tcb == other.tcb
+            && self
+                .pose
+                .root_position
+                .abs_diff_eq(other.pose.root_position, tol)
+            && self
+                .pose
+                .local_rotations
+                .iter()
+                .zip(&other.pose.local_rotations)
+                .all(|(a, b)| a.abs_diff_eq(*b, tol) || a.abs_diff_eq(-*b, tol))
+    }
 }
 
 /// JSON format for keyframe
@@ -276,6 +581,12 @@ pub struct RotationKeyframeJson {
     pub time: f32,
     #[serde(rename = "p")]
     pub pose: RotationPoseJson,
+    #[serde(rename = "tn", default, skip_serializing_if = "Option::is_none")]
+    pub tension: Option<f32>,
+    #[serde(rename = "cn", default, skip_serializing_if = "Option::is_none")]
+    pub continuity: Option<f32>,
+    #[serde(rename = "bs", default, skip_serializing_if = "Option::is_none")]
+    pub bias: Option<f32>,
 }
 
 /// Rotation-based animation clip
@@ -297,91 +608,180 @@ pub struct RotationAnimationClipJson {
     pub name: String,
     #[serde(rename = "d")]
     pub duration: f32,
+    #[serde(rename = "eo", default, skip_serializing_if = "EulerOrder::is_default")]
+    pub euler_order: EulerOrder,
     #[serde(rename = "kf")]
     pub keyframes: Vec<RotationKeyframeJson>,
 }
 
-fn default_version() -> u32 {
+pub(super) fn default_version() -> u32 {
     2
 }
 
+/// glTF-style animation export (simplified: accessors are inlined as arrays)
+///
+/// Nodes are named after `BoneId` variants (`"Pelvis"`, `"LeftKnee"`, ...), so a
+/// three.js skeleton with matching bone names can bind the channels directly.
+#[derive(Debug, Clone, Serialize)]
+pub struct GltfAnimationJson {
+    pub name: String,
+    pub channels: Vec<GltfChannelJson>,
+    pub samplers: Vec<GltfSamplerJson>,
+}
+
+/// Binds a sampler to one property of a node
+#[derive(Debug, Clone, Serialize)]
+pub struct GltfChannelJson {
+    pub sampler: usize,
+    pub target: GltfTargetJson,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GltfTargetJson {
+    pub node: String,
+    /// `"rotation"` (XYZW quaternions) or `"translation"` (XYZ)
+    pub path: &'static str,
+}
+
+/// Keyframe times and flattened output values for one channel
+#[derive(Debug, Clone, Serialize)]
+pub struct GltfSamplerJson {
+    pub input: Vec<f32>,
+    pub interpolation: &'static str,
+    pub output: Vec<f32>,
+}
+
+/// World-space clip export: absolute bone rotations instead of local ones
+///
+/// For engines that expect global transforms. Rotations are keyed by bone
+/// name (`BoneId::name`) as XYZW quaternions.
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldRotationClipJson {
+    #[serde(rename = "n")]
+    pub name: String,
+    #[serde(rename = "d")]
+    pub duration: f32,
+    #[serde(rename = "kf")]
+    pub keyframes: Vec<WorldRotationKeyframeJson>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldRotationKeyframeJson {
+    pub t: f32,
+    /// Root position
+    pub rp: [f32; 3],
+    /// World rotation per bone
+    pub wr: std::collections::BTreeMap<&'static str, [f32; 4]>,
+}
+
+/// Length of the ramps blending an overlay in and out, in seconds
+const OVERLAY_BLEND: f32 = 0.1;
+
+/// Quantization step for hashing: values closer than this may hash equal
+const HASH_QUANTUM: f32 = 1e-4;
+
+/// Shortest duration `fit_duration` leaves, so `sample`'s `time % duration` stays defined
+const MIN_FIT_DURATION: f32 = 1e-3;
+
+/// FNV-1a 64-bit hasher: stable across runs and platforms, unlike `DefaultHasher`
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Hash a float quantized to `HASH_QUANTUM` so FP noise doesn't change the hash
+    fn write_quantized(&mut self, value: f32) {
+        let quantized = (value / HASH_QUANTUM).round() as i32;
+        self.write(&quantized.to_le_bytes());
+    }
+}
+
 impl RotationAnimationClip {
     /// Parse from JSON string
     pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         let clip_json: RotationAnimationClipJson = serde_json::from_str(json)?;
+        let order = clip_json.euler_order.to_euler_rot();
 
         let keyframes: Vec<RotationKeyframe> = clip_json
             .keyframes
             .into_iter()
             .map(|kf| RotationKeyframe {
                 time: kf.time,
-                pose: kf.pose.to_rotation_pose(),
+                pose: kf.pose.to_rotation_pose_with_order(order),
+                tcb: Tcb::from_json(kf.tension, kf.continuity, kf.bias),
             })
             .collect();
 
-        Ok(Self {
+        let mut clip = Self {
             name: clip_json.name,
             duration: clip_json.duration,
             keyframes,
-        })
+        };
+        if clip.sort_keyframes() {
+            log::warn!("Clip '{}' had out-of-order keyframe times", clip.name);
+        }
+        Ok(clip)
     }
 
-    /// Parse from binary format
+    /// Order keyframes by time, as `sample` requires, dropping repeated times
     ///
-    /// Binary format:
-    /// - Header: u16 keyframe_count, f16 duration
-    /// - Per keyframe: 22 bones * 4 Q1.15 values + 3 f16 root position (182 bytes)
-    pub fn from_binary(data: &[u8], name: String) -> Result<Self, &'static str> {
-        if data.len() < 8 {
-            return Err("Binary data too short for header");
+    /// The sort is stable, so of several keyframes at the same time the first
+    /// one in the original order is kept. Returns whether anything changed.
+    pub fn sort_keyframes(&mut self) -> bool {
+        let len = self.keyframes.len();
+        let sorted = self.keyframes.is_sorted_by(|a, b| a.time <= b.time);
+        if !sorted {
+            self.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
         }
+        self.keyframes
+            .dedup_by(|later, earlier| later.time == earlier.time);
+        !sorted || self.keyframes.len() != len
+    }
 
-        // 1. Read Header (8 bytes)
-        let keyframe_count = u16::from_le_bytes([data[0], data[1]]) as usize;
-        let duration = f16::from_le_bytes([data[2], data[3]]).to_f32();
-        let dynamic_mask = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-
-        let mut offset = 8;
-
-        // 2. Read Base Data (Header extension)
-        // Base Root (6 bytes)
-        if data.len() < offset + 6 {
-            return Err("Binary data too short for base root");
-        }
-        let base_rx = f16::from_le_bytes([data[offset], data[offset + 1]]).to_f32();
-        let base_ry = f16::from_le_bytes([data[offset + 2], data[offset + 3]]).to_f32();
-        let base_rz = f16::from_le_bytes([data[offset + 4], data[offset + 5]]).to_f32();
-        let base_root = glam::Vec3::new(base_rx, base_ry, base_rz);
-        offset += 6;
-
-        // Base Rotations (22 bones * 6 bytes = 132 bytes)
-        if data.len() < offset + 132 {
-            return Err("Binary data too short for base rotations");
-        }
-        let mut base_rotations = [Quat::IDENTITY; BoneId::COUNT];
-        for i in 0..BoneId::COUNT {
-            let x = q15_to_f32([data[offset], data[offset + 1]]);
-            let y = q15_to_f32([data[offset + 2], data[offset + 3]]);
-            let z = q15_to_f32([data[offset + 4], data[offset + 5]]);
-            offset += 6;
+    /// Drop keyframes that repeat the one before them (see `RotationKeyframe::approx_eq`)
+    /// Only consecutive runs collapse, to their first keyframe. Returns how many were removed.
+    pub fn dedup_keyframes(&mut self, tol: f32) -> usize {
+        let len = self.keyframes.len();
+        self.keyframes
+            .dedup_by(|later, earlier| later.approx_eq(earlier, tol));
+        len - self.keyframes.len()
+    }
 
-            // Reconstruct W: w^2 + x^2 + y^2 + z^2 = 1.0
-            let sum_sq = x * x + y * y + z * z;
-            let w = (1.0 - sum_sq).max(0.0).sqrt();
-            base_rotations[i] = Quat::from_xyzw(x, y, z, w).normalize();
-        }
+    /// Parse from binary format
+    ///
+    /// Binary format:
+    /// - Header: u16 keyframe_count, f16 duration, u32 dynamic_mask
+    ///   (bit per bone, bit 22 = root position)
+    /// - Base values: 3 f16 root position, 22 bones * 3 Q1.15 rotations (W >= 0)
+    /// - Per keyframe: 3 Q1.15 per dynamic bone, 3 f16 if the root is dynamic
+    pub fn from_binary(data: &[u8], name: String) -> Result<Self, &'static str> {
+        let header = BinaryHeader::read(data)?;
+        let mut offset = BinaryHeader::SIZE;
 
-        // 3. Read Dynamic Keyframe Data
-        let mut keyframes = Vec::with_capacity(keyframe_count);
+        let base_root = dequantize_root(header.base_root);
+        let base_rotations = header.base_rotations.map(dequantize_rotation);
 
-        for i in 0..keyframe_count {
+        let mut keyframes = Vec::with_capacity(header.keyframe_count);
+        for i in 0..header.keyframe_count {
             let mut pose = RotationPose::bind_pose();
             pose.root_position = base_root;
             pose.local_rotations = base_rotations;
 
             // Read dynamic rotations (3 components each)
             for bone_idx in 0..BoneId::COUNT {
-                if dynamic_mask & (1 << bone_idx) != 0 {
+                if header.dynamic_mask & (1 << bone_idx) != 0 {
                     if data.len() < offset + 6 {
                         return Err("Binary data truncated in dynamic rotations");
                     }
@@ -397,7 +797,7 @@ impl RotationAnimationClip {
             }
 
             // Read dynamic root position
-            if dynamic_mask & (1 << 22) != 0 {
+            if header.dynamic_mask & ROOT_DYNAMIC_BIT != 0 {
                 if data.len() < offset + 6 {
                     return Err("Binary data truncated in dynamic root position");
                 }
@@ -408,19 +808,171 @@ impl RotationAnimationClip {
                 offset += 6;
             }
 
-            // Keyframes are evenly spaced over duration
-            let time = if keyframe_count > 1 {
-                duration * (i as f32) / ((keyframe_count - 1) as f32)
-            } else {
-                0.0
-            };
+            keyframes.push(RotationKeyframe {
+                time: binary_keyframe_time(i, header.keyframe_count, header.duration),
+                pose,
+                tcb: None,
+            });
+        }
+
+        Ok(Self {
+            name,
+            duration: header.duration,
+            keyframes,
+        })
+    }
+
+    /// Parse either binary format, detected from the leading bytes
+    pub fn from_binary_any(data: &[u8], name: String) -> Result<Self, &'static str> {
+        if data.starts_with(&DELTA_MAGIC) {
+            Self::from_binary_delta(data, name)
+        } else {
+            Self::from_binary(data, name)
+        }
+    }
 
-            keyframes.push(RotationKeyframe { time, pose });
+    /// Quantized keyframes plus the header describing them
+    /// Keyframe times are not stored: binary clips are evenly spaced.
+    fn quantize_for_binary(&self) -> Result<(BinaryHeader, Vec<QuantizedFrame>), &'static str> {
+        if self.keyframes.len() > u16::MAX as usize {
+            return Err("Too many keyframes for binary format");
+        }
+        let frames: Vec<_> = self
+            .keyframes
+            .iter()
+            .map(|kf| {
+                (
+                    quantize_root(kf.pose.root_position),
+                    kf.pose.local_rotations.map(quantize_rotation),
+                )
+            })
+            .collect();
+        let (base_root, base_rotations) = frames.first().copied().unwrap_or_else(|| {
+            let bind = RotationPose::bind_pose();
+            (
+                quantize_root(bind.root_position),
+                bind.local_rotations.map(quantize_rotation),
+            )
+        });
+
+        // A channel is dynamic if any keyframe differs from the first
+        let mut dynamic_mask = 0;
+        for (root, rotations) in &frames {
+            for bone_idx in 0..BoneId::COUNT {
+                if rotations[bone_idx] != base_rotations[bone_idx] {
+                    dynamic_mask |= 1 << bone_idx;
+                }
+            }
+            if *root != base_root {
+                dynamic_mask |= ROOT_DYNAMIC_BIT;
+            }
+        }
+
+        let header = BinaryHeader {
+            keyframe_count: frames.len(),
+            duration: self.duration,
+            dynamic_mask,
+            base_root,
+            base_rotations,
+        };
+        Ok((header, frames))
+    }
+
+    /// Encode in the binary format read by `from_binary`
+    pub fn to_binary(&self) -> Result<Vec<u8>, &'static str> {
+        let (header, frames) = self.quantize_for_binary()?;
+        let mut out = Vec::new();
+        header.write(&mut out);
+        for (root, rotations) in &frames {
+            for (bone_idx, rotation) in rotations.iter().enumerate() {
+                if header.dynamic_mask & (1 << bone_idx) != 0 {
+                    rotation
+                        .iter()
+                        .for_each(|c| out.extend_from_slice(&c.to_le_bytes()));
+                }
+            }
+            if header.dynamic_mask & ROOT_DYNAMIC_BIT != 0 {
+                root.iter()
+                    .for_each(|c| out.extend_from_slice(&c.to_le_bytes()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Encode in the delta binary format read by `from_binary_delta`
+    ///
+    /// Layout: `DELTA_MAGIC`, then the `from_binary` header and base values (the
+    /// first keyframe). Each later keyframe stores, for the dynamic channels only,
+    /// the change from the previous keyframe as zigzag varints: Q1.15 steps for
+    /// rotations, f16 bit patterns for the root. Smooth motion mostly needs one
+    /// byte per component, and quantized values are reproduced exactly.
+    pub fn to_binary_delta(&self) -> Result<Vec<u8>, &'static str> {
+        let (header, frames) = self.quantize_for_binary()?;
+        let mut out = DELTA_MAGIC.to_vec();
+        header.write(&mut out);
+        for pair in frames.windows(2) {
+            let ((prev_root, prev_rotations), (root, rotations)) = (pair[0], pair[1]);
+            for bone_idx in 0..BoneId::COUNT {
+                if header.dynamic_mask & (1 << bone_idx) != 0 {
+                    for c in 0..3 {
+                        let delta =
+                            rotations[bone_idx][c] as i32 - prev_rotations[bone_idx][c] as i32;
+                        write_varint(&mut out, delta);
+                    }
+                }
+            }
+            if header.dynamic_mask & ROOT_DYNAMIC_BIT != 0 {
+                for c in 0..3 {
+                    write_varint(&mut out, root[c].wrapping_sub(prev_root[c]) as i16 as i32);
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Parse the delta binary format written by `to_binary_delta`
+    pub fn from_binary_delta(data: &[u8], name: String) -> Result<Self, &'static str> {
+        let data = data
+            .strip_prefix(&DELTA_MAGIC)
+            .ok_or("Binary data is not delta encoded")?;
+        let header = BinaryHeader::read(data)?;
+        let mut offset = BinaryHeader::SIZE;
+
+        let mut root = header.base_root;
+        let mut rotations = header.base_rotations;
+        let mut keyframes = Vec::with_capacity(header.keyframe_count);
+        for i in 0..header.keyframe_count {
+            if i > 0 {
+                for (bone_idx, rotation) in rotations.iter_mut().enumerate() {
+                    if header.dynamic_mask & (1 << bone_idx) != 0 {
+                        for component in rotation.iter_mut() {
+                            let delta = read_varint(data, &mut offset)?;
+                            *component = i16::try_from(*component as i32 + delta)
+                                .map_err(|_| "Binary delta rotation out of range")?;
+                        }
+                    }
+                }
+                if header.dynamic_mask & ROOT_DYNAMIC_BIT != 0 {
+                    for component in root.iter_mut() {
+                        let delta = read_varint(data, &mut offset)?;
+                        *component = component.wrapping_add(delta as i16 as u16);
+                    }
+                }
+            }
+
+            let mut pose = RotationPose::bind_pose();
+            pose.root_position = dequantize_root(root);
+            pose.local_rotations = rotations.map(dequantize_rotation);
+            keyframes.push(RotationKeyframe {
+                time: binary_keyframe_time(i, header.keyframe_count, header.duration),
+                pose,
+                tcb: None,
+            });
         }
 
         Ok(Self {
             name,
-            duration,
+            duration: header.duration,
             keyframes,
         })
     }
@@ -433,6 +985,9 @@ impl RotationAnimationClip {
             .map(|kf| RotationKeyframeJson {
                 time: kf.time,
                 pose: RotationPoseJson::from_pose(&kf.pose),
+                tension: kf.tcb.map(|tcb| tcb.tension),
+                continuity: kf.tcb.map(|tcb| tcb.continuity),
+                bias: kf.tcb.map(|tcb| tcb.bias),
             })
             .collect();
 
@@ -441,18 +996,528 @@ impl RotationAnimationClip {
             version: 2,
             name: self.name.clone(),
             duration: self.duration,
+            // Poses are written as quaternions, so the order never matters on export
+            euler_order: EulerOrder::default(),
             keyframes: keyframes_json,
         };
 
         serde_json::to_string_pretty(&json_struct)
     }
 
+    /// Convert to a glTF-style animation JSON string (see `GltfAnimationJson`)
+    ///
+    /// Emits one rotation sampler per bone that leaves identity in any keyframe
+    /// (the others stay at their rest pose) and a translation sampler for the
+    /// root, on the `Pelvis` node. Inputs are the keyframe times. Samplers are
+    /// `LINEAR`, so TCB splines are reduced to slerp between keyframes.
+    pub fn to_gltf_animation_json(&self) -> Result<String, serde_json::Error> {
+        let input: Vec<f32> = self.keyframes.iter().map(|kf| kf.time).collect();
+        let mut channels = Vec::new();
+        let mut samplers = Vec::new();
+        let mut push_channel = |node: BoneId, path, output| {
+            channels.push(GltfChannelJson {
+                sampler: samplers.len(),
+                target: GltfTargetJson {
+                    node: format!("{:?}", node),
+                    path,
+                },
+            });
+            samplers.push(GltfSamplerJson {
+                input: input.clone(),
+                interpolation: "LINEAR",
+                output,
+            });
+        };
+
+        for bone in BoneId::ALL {
+            let rotations: Vec<Quat> = self
+                .keyframes
+                .iter()
+                .map(|kf| kf.pose.local_rotations[bone.index()])
+                .collect();
+            if rotations
+                .iter()
+                .all(|q| q.abs_diff_eq(Quat::IDENTITY, 1e-6))
+            {
+                continue;
+            }
+
+            // Keep consecutive quaternions in one hemisphere for LINEAR slerp
+            let mut output = Vec::with_capacity(rotations.len() * 4);
+            let mut previous = Quat::IDENTITY;
+            for (i, &q) in rotations.iter().enumerate() {
+                let q = if i > 0 && previous.dot(q) < 0.0 {
+                    -q
+                } else {
+                    q
+                };
+                output.extend_from_slice(&q.to_array());
+                previous = q;
+            }
+            push_channel(bone, "rotation", output);
+        }
+
+        let translations = self
+            .keyframes
+            .iter()
+            .flat_map(|kf| kf.pose.root_position.to_array())
+            .collect();
+        push_channel(BoneId::Pelvis, "translation", translations);
+
+        serde_json::to_string_pretty(&GltfAnimationJson {
+            name: self.name.clone(),
+            channels,
+            samplers,
+        })
+    }
+
+    /// World positions of `bone` at `samples` evenly spaced times over one loop
+    ///
+    /// Times are `i * duration / samples`, so the end of the loop (which wraps to
+    /// the start) is not repeated; close the polyline to draw a looping path.
+    pub fn end_effector_trajectory(&self, bone: BoneId, samples: usize) -> Vec<glam::Vec3> {
+        (0..samples)
+            .map(|i| {
+                let time = self.duration * i as f32 / samples as f32;
+                self.sample(time).get_position(bone)
+            })
+            .collect()
+    }
+
+    /// Play the same rotations on a skeleton with different segment lengths
+    ///
+    /// Every keyframe pose gets `lengths` (see `RotationPose::with_bone_lengths`)
+    /// and its root height is shifted so the lowest joint stays at the height it
+    /// had on the original skeleton, keeping planted feet on the floor whether
+    /// the legs got longer or shorter. Lengths are not serialized by `to_json_string`.
+    pub fn retarget(&self, lengths: &[f32; BoneId::COUNT]) -> RotationAnimationClip {
+        let lowest = |pose: &RotationPose| {
+            BoneId::ALL
+                .iter()
+                .map(|&bone| pose.get_position(bone).y)
+                .fold(pose.root_position.y, f32::min)
+        };
+
+        let mut result = self.clone();
+        for keyframe in &mut result.keyframes {
+            let original = lowest(&keyframe.pose);
+            let pose = std::mem::take(&mut keyframe.pose).with_bone_lengths(*lengths);
+            let lift = original - lowest(&pose);
+            let root = pose.root_position + glam::Vec3::Y * lift;
+            keyframe.pose = pose.with_root_position(root);
+        }
+        result
+    }
+
+    /// Make each bone's quaternions hemisphere-consistent across the whole clip
+    ///
+    /// Walks keyframes in time order and negates a bone's quaternion when its dot
+    /// with the previous keyframe's is negative. `q` and `-q` are the same rotation,
+    /// so poses are unchanged, but cubic interpolation no longer spins around.
+    pub fn enforce_continuity(&mut self) {
+        for i in 1..self.keyframes.len() {
+            let prev = self.keyframes[i - 1].pose.local_rotations;
+            let pose = &mut self.keyframes[i].pose;
+            for (q, q_prev) in pose.local_rotations.iter_mut().zip(prev) {
+                if q.dot(q_prev) < 0.0 {
+                    *q = -*q;
+                }
+            }
+        }
+    }
+
+    /// Shrink or grow `duration` to end at the last keyframe
+    ///
+    /// Removes dead time after the last keyframe left by edits. A clip whose
+    /// keyframes all sit at 0 keeps `MIN_FIT_DURATION`; an empty clip is unchanged.
+    pub fn fit_duration(&mut self) {
+        if let Some(last_time) = self.keyframes.iter().map(|kf| kf.time).reduce(f32::max) {
+            self.duration = last_time.max(MIN_FIT_DURATION);
+        }
+    }
+
+    /// Return a copy with the duration and every keyframe time multiplied by `factor`
+    ///
+    /// Bakes a speed change into the clip (2.0 = half speed) while keeping the
+    /// relative keyframe spacing. `factor` must be positive and finite.
+    pub fn scale_time(&self, factor: f32) -> Result<RotationAnimationClip, &'static str> {
+        if !(factor > 0.0 && factor.is_finite()) {
+            return Err("Time scale factor must be positive");
+        }
+
+        Ok(Self {
+            name: self.name.clone(),
+            duration: self.duration * factor,
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|kf| RotationKeyframe {
+                    time: kf.time * factor,
+                    pose: kf.pose.clone(),
+                    tcb: kf.tcb,
+                })
+                .collect(),
+        })
+    }
+
+    /// Return a copy that holds its first and last keyframes for extra time
+    ///
+    /// Keyframes shift later by `lead_in` behind a copy of the first keyframe at
+    /// time 0, and a copy of the last keyframe is appended `lead_out` after it;
+    /// the duration grows by both. Meant for non-looping playback into and out of
+    /// UI transitions. Negative paddings count as 0.
+    pub fn pad(&self, lead_in: f32, lead_out: f32) -> RotationAnimationClip {
+        let (lead_in, lead_out) = (lead_in.max(0.0), lead_out.max(0.0));
+        let mut keyframes: Vec<RotationKeyframe> = self
+            .keyframes
+            .iter()
+            .map(|kf| RotationKeyframe {
+                time: kf.time + lead_in,
+                pose: kf.pose.clone(),
+                tcb: kf.tcb,
+            })
+            .collect();
+        if let (Some(first), true) = (self.keyframes.first(), lead_in > 0.0) {
+            keyframes.insert(
+                0,
+                RotationKeyframe {
+                    time: 0.0,
+                    pose: first.pose.clone(),
+                    tcb: first.tcb,
+                },
+            );
+        }
+        if let (Some(last), true) = (keyframes.last(), lead_out > 0.0) {
+            let hold = RotationKeyframe {
+                time: last.time + lead_out,
+                pose: last.pose.clone(),
+                tcb: last.tcb,
+            };
+            keyframes.push(hold);
+        }
+
+        Self {
+            name: self.name.clone(),
+            duration: self.duration + lead_in + lead_out,
+            keyframes,
+        }
+    }
+
+    /// Return a copy time-shifted so `phase_time` lands at time 0
+    ///
+    /// Lines up locomotion cycles before blending, e.g. with `phase_time` at
+    /// left-foot contact. The pose at `phase_time` is sampled into a new first
+    /// keyframe and the others rotate around the loop, so playback is unchanged
+    /// apart from the offset.
+    pub fn normalize_phase(&self, phase_time: f32) -> RotationAnimationClip {
+        if self.keyframes.is_empty() || self.duration <= 0.0 {
+            return self.clone();
+        }
+        let offset = phase_time.rem_euclid(self.duration);
+
+        let mut keyframes = vec![RotationKeyframe {
+            time: 0.0,
+            pose: self.sample(offset),
+            tcb: None,
+        }];
+        let mut shifted: Vec<RotationKeyframe> = self
+            .keyframes
+            .iter()
+            .map(|kf| RotationKeyframe {
+                time: (kf.time - offset).rem_euclid(self.duration),
+                pose: kf.pose.clone(),
+                tcb: kf.tcb,
+            })
+            // A keyframe exactly at the phase point is replaced by the sampled one
+            .filter(|kf| kf.time > crate::EPSILON)
+            .collect();
+        shifted.sort_by(|a, b| a.time.total_cmp(&b.time));
+        keyframes.extend(shifted);
+
+        Self {
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes,
+        }
+    }
+
+    /// Stable hash of the clip's animation data, for unsaved-change detection
+    ///
+    /// Covers duration, keyframe times, root positions and rotations, each
+    /// quantized to `HASH_QUANTUM`. The name is not included.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv1a::new();
+        hasher.write_quantized(self.duration);
+        hasher.write(&(self.keyframes.len() as u32).to_le_bytes());
+        for kf in &self.keyframes {
+            hasher.write_quantized(kf.time);
+            for value in kf.pose.root_position.to_array() {
+                hasher.write_quantized(value);
+            }
+            for rotation in &kf.pose.local_rotations {
+                for value in rotation.to_array() {
+                    hasher.write_quantized(value);
+                }
+            }
+        }
+        hasher.0
+    }
+
+    /// Convert to a world-rotation JSON string (see `WorldRotationClipJson`)
+    pub fn to_world_rotation_json(&self) -> Result<String, serde_json::Error> {
+        let keyframes = self
+            .keyframes
+            .iter()
+            .map(|kf| {
+                let world = kf.pose.world_rotations_array();
+                WorldRotationKeyframeJson {
+                    t: kf.time,
+                    rp: kf.pose.root_position.to_array(),
+                    wr: BoneId::ALL
+                        .iter()
+                        .map(|bone| (bone.name(), world[bone.index()].to_array()))
+                        .collect(),
+                }
+            })
+            .collect();
+        serde_json::to_string_pretty(&WorldRotationClipJson {
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes,
+        })
+    }
+
+    /// Replace the masked bones' rotations with `source`'s within `[start, end]`
+    ///
+    /// The masked bones ramp from this clip's rotations to the source's over
+    /// `OVERLAY_BLEND` seconds before `start`, and back after `end`. Keyframes are
+    /// added at the source's keyframe times and the ramp ends so both clips'
+    /// slerped motion is kept between keyframes. The root and unmasked bones
+    /// are unchanged. Both clips are sampled at the same (looping) time.
+    pub fn overlay_range(
+        &self,
+        source: &RotationAnimationClip,
+        mask: BoneMask,
+        start: f32,
+        end: f32,
+    ) -> RotationAnimationClip {
+        let (start, end) = (start.max(0.0), end.min(self.duration));
+        if start > end || self.keyframes.is_empty() {
+            return self.clone();
+        }
+        let weight = |time: f32| {
+            if time < start {
+                1.0 - (start - time) / OVERLAY_BLEND
+            } else if time > end {
+                1.0 - (time - end) / OVERLAY_BLEND
+            } else {
+                1.0
+            }
+            .clamp(0.0, 1.0)
+        };
+
+        // Existing keyframes, ramp ends, and source keyframes inside the range
+        let mut times: Vec<f32> = self.keyframes.iter().map(|kf| kf.time).collect();
+        times.extend([start - OVERLAY_BLEND, start, end, end + OVERLAY_BLEND]);
+        if source.duration > 0.0 {
+            let first_loop = (start / source.duration).floor() as i32;
+            let last_loop = (end / source.duration).ceil() as i32;
+            for k in first_loop..=last_loop {
+                let offset = k as f32 * source.duration;
+                times.extend(source.keyframes.iter().map(|kf| kf.time + offset));
+            }
+        }
+        times.retain(|&t| (0.0..=self.duration).contains(&t));
+        times.sort_by(f32::total_cmp);
+        times.dedup_by(|a, b| (*a - *b).abs() < 1e-5);
+
+        let keyframes = times
+            .into_iter()
+            .map(|time| {
+                let existing = self
+                    .keyframes
+                    .iter()
+                    .find(|kf| (kf.time - time).abs() < 1e-5);
+                let mut pose = existing.map_or_else(|| self.sample(time), |kf| kf.pose.clone());
+                let w = weight(time);
+                if w > 0.0 {
+                    let overlay = source.sample(time);
+                    for bone in BoneId::ALL.into_iter().filter(|&bone| mask.contains(bone)) {
+                        let rotation = pose.local_rotations[bone.index()]
+                            .slerp(overlay.local_rotations[bone.index()], w);
+                        pose = pose.with_rotation(bone, rotation);
+                    }
+                }
+                RotationKeyframe {
+                    time,
+                    pose,
+                    tcb: existing.and_then(|kf| kf.tcb),
+                }
+            })
+            .collect();
+
+        RotationAnimationClip {
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes,
+        }
+    }
+
+    /// Return a copy with every keyframe mirrored across the YZ plane
+    ///
+    /// Swaps left and right (see `RotationPose::mirror_x`), e.g. to derive the
+    /// left-handed version of a one-sided exercise.
+    pub fn mirror(&self) -> RotationAnimationClip {
+        Self {
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|kf| RotationKeyframe {
+                    time: kf.time,
+                    pose: kf.pose.mirror_x(),
+                    tcb: kf.tcb,
+                })
+                .collect(),
+        }
+    }
+
+    /// Return a copy authored for an A-pose rest, converted to this T-pose skeleton
+    ///
+    /// A-pose data leaves the upper arms identity-rotated when they hang
+    /// `arm_down_angle` radians below horizontal. Each shoulder rotation is
+    /// composed with that drop (about Z, in the collar's frame), so identity
+    /// arms land in the A-pose and authored swings are applied on top of it.
+    pub fn rebind_to_a_pose(&self, arm_down_angle: f32) -> RotationAnimationClip {
+        // Left arm points along +X, right along -X: both drop toward -Y
+        let drops = [
+            (BoneId::LeftShoulder, Quat::from_rotation_z(-arm_down_angle)),
+            (BoneId::RightShoulder, Quat::from_rotation_z(arm_down_angle)),
+        ];
+
+        let mut result = self.clone();
+        for keyframe in &mut result.keyframes {
+            for (bone, drop) in drops {
+                let rotation = &mut keyframe.pose.local_rotations[bone.index()];
+                *rotation = (*rotation * drop).normalize();
+            }
+        }
+        result
+    }
+
+    /// Split horizontal root translation out of the clip
+    ///
+    /// Returns an in-place copy with every keyframe's root at the origin in XZ
+    /// (Y is kept, grounding is left to the floor constraint at playback) and a
+    /// `(time, displacement)` track of the XZ root offset from the first keyframe.
+    pub fn extract_root_motion(&self) -> (RotationAnimationClip, Vec<(f32, glam::Vec3)>) {
+        let start = self
+            .keyframes
+            .first()
+            .map_or(glam::Vec3::ZERO, |kf| kf.pose.root_position);
+
+        let mut track = Vec::with_capacity(self.keyframes.len());
+        let keyframes = self
+            .keyframes
+            .iter()
+            .map(|kf| {
+                let root = kf.pose.root_position;
+                track.push((
+                    kf.time,
+                    glam::Vec3::new(root.x - start.x, 0.0, root.z - start.z),
+                ));
+                RotationKeyframe {
+                    time: kf.time,
+                    pose: kf
+                        .pose
+                        .clone()
+                        .with_root_position(glam::Vec3::new(0.0, root.y, 0.0)),
+                    tcb: kf.tcb,
+                }
+            })
+            .collect();
+
+        let in_place = Self {
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes,
+        };
+        (in_place, track)
+    }
+
     /// Sample the animation at a given time, using slerp interpolation
     pub fn sample(&self, time: f32) -> RotationPose {
         if self.keyframes.is_empty() {
             return RotationPose::bind_pose();
         }
+        match self.segment_at(time) {
+            Some((a, b, t)) => self.interpolate_segment(a, b, t),
+            // Before first keyframe
+            None => self.keyframes[0].pose.clone(),
+        }
+    }
+
+    /// `sample` with the last `blend_time` seconds of each loop crossfading
+    /// into the pose at time 0, hiding a pop where the loop wraps
+    ///
+    /// The blend weight rises linearly to 1 at the clip end, so the result
+    /// meets the next loop's start exactly. Times before the blend window
+    /// sample as usual; `blend_time` is capped at the duration.
+    pub fn sample_loop_blended(&self, time: f32, blend_time: f32) -> RotationPose {
+        let pose = self.sample(time);
+        let blend_time = blend_time.min(self.duration);
+        if blend_time <= 0.0 || self.keyframes.is_empty() {
+            return pose;
+        }
+        let blend_start = self.duration - blend_time;
+        let looped_time = time % self.duration;
+        if looped_time <= blend_start {
+            return pose;
+        }
+        let weight = (looped_time - blend_start) / blend_time;
+        RotationPose::lerp(&pose, &self.sample(0.0), weight)
+    }
+
+    /// `sample`, then IK each `locked` effector onto the interpolated track of
+    /// its world position between the two keyframes
+    ///
+    /// Blending rotations moves a planted foot or hand along an arc; this keeps
+    /// it on the straight line between its keyframed positions instead, so it
+    /// doesn't slide mid-segment. Chains are those of `constraint_chain`.
+    pub fn sample_ik_corrected(&self, time: f32, locked: &[BoneId]) -> RotationPose {
+        if self.keyframes.is_empty() {
+            return RotationPose::bind_pose();
+        }
+        let Some((a, b, t)) = self.segment_at(time) else {
+            return self.keyframes[0].pose.clone();
+        };
+        let (pose_a, pose_b) = (&self.keyframes[a].pose, &self.keyframes[b].pose);
+        let mut pose = self.interpolate_segment(a, b, t);
+        for &effector in locked {
+            let target = pose_a
+                .get_position(effector)
+                .lerp(pose_b.get_position(effector), t);
+            pose = pose.apply_ik(&constraint_chain(effector), target);
+        }
+        pose
+    }
+
+    /// Index of the keyframe closest in time to `time` (looped)
+    ///
+    /// Distance follows looped playback, so past the last keyframe the first
+    /// one can be nearest. Returns 0 for a clip without keyframes.
+    pub fn nearest_keyframe_index(&self, time: f32) -> usize {
+        match self.segment_at(time) {
+            Some((_, b, t)) if t > 0.5 => b,
+            Some((a, _, _)) => a,
+            None => 0,
+        }
+    }
 
+    /// Keyframes around `time` (looped) and the blend factor between them
+    /// None before the first keyframe, where the clip holds its first pose.
+    fn segment_at(&self, time: f32) -> Option<(usize, usize, f32)> {
         // Loop time within duration
         let looped_time = time % self.duration;
 
@@ -460,8 +1525,7 @@ impl RotationAnimationClip {
         let next_idx = self.keyframes.partition_point(|kf| kf.time <= looped_time);
 
         if next_idx == 0 {
-            // Before first keyframe
-            return self.keyframes[0].pose.clone();
+            return None;
         }
 
         if next_idx >= self.keyframes.len() {
@@ -475,7 +1539,7 @@ impl RotationAnimationClip {
             } else {
                 0.0
             };
-            return RotationPose::lerp(&prev.pose, &next.pose, t);
+            return Some((self.keyframes.len() - 1, 0, t));
         }
 
         // Between two keyframes
@@ -490,6 +1554,58 @@ impl RotationAnimationClip {
             0.0
         };
 
-        RotationPose::lerp(&prev.pose, &next.pose, t)
+        Some((next_idx - 1, next_idx, t))
+    }
+
+    /// Interpolate between keyframes `a` and `b` (adjacent, possibly wrapping)
+    ///
+    /// Slerp unless either keyframe has TCB controls, in which case a
+    /// Kochanek-Bartels spline through the neighboring keyframes is used.
+    fn interpolate_segment(&self, a: usize, b: usize, t: f32) -> RotationPose {
+        let (kf_a, kf_b) = (&self.keyframes[a], &self.keyframes[b]);
+        if kf_a.tcb.is_none() && kf_b.tcb.is_none() {
+            return RotationPose::lerp(&kf_a.pose, &kf_b.pose, t);
+        }
+
+        // Neighbors wrap around, matching looped playback
+        let n = self.keyframes.len();
+        let before = &self.keyframes[(a + n - 1) % n].pose;
+        let after = &self.keyframes[(b + 1) % n].pose;
+        let (tcb_a, tcb_b) = (kf_a.tcb.unwrap_or_default(), kf_b.tcb.unwrap_or_default());
+
+        let mut result = RotationPose::bind_pose();
+        result.bone_lengths = kf_a.pose.bone_lengths;
+        result.root_position = tcb_interpolate(
+            [
+                before.root_position,
+                kf_a.pose.root_position,
+                kf_b.pose.root_position,
+                after.root_position,
+            ],
+            tcb_a,
+            tcb_b,
+            t,
+        );
+
+        for i in 0..BoneId::COUNT {
+            // Put all four quaternions in the same hemisphere as their predecessor
+            let q1 = Vec4::from(kf_a.pose.local_rotations[i]);
+            let align = |q: Quat, reference: Vec4| {
+                let q = Vec4::from(q);
+                if q.dot(reference) < 0.0 {
+                    -q
+                } else {
+                    q
+                }
+            };
+            let q0 = align(before.local_rotations[i], q1);
+            let q2 = align(kf_b.pose.local_rotations[i], q1);
+            let q3 = align(after.local_rotations[i], q2);
+
+            let q = tcb_interpolate([q0, q1, q2, q3], tcb_a, tcb_b, t);
+            result.local_rotations[i] = Quat::from_vec4(q).normalize();
+        }
+
+        result.with_all_dirty()
     }
 }
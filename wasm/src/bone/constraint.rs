@@ -0,0 +1,187 @@
+//! Constraint clips: keyframes store end-effector targets, poses are solved with IK
+//!
+//! A keyframe is just a handful of world positions (hands, feet, pelvis), so
+//! these clips are tiny and easy to author. The cost moves to playback: every
+//! `sample` interpolates the targets and runs IK on the bind pose.
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{BoneId, RotationPose, BONE_HIERARCHY};
+
+/// A keyframe of world-space joint targets
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstraintKeyframe {
+    pub time: f32,
+    /// A `Pelvis` target places the root; the others are reached with IK
+    pub targets: Vec<(BoneId, Vec3)>,
+}
+
+/// Clip whose poses are solved from interpolated targets at sample time
+#[derive(Debug, Clone)]
+pub struct ConstraintClip {
+    pub name: String,
+    pub duration: f32,
+    pub keyframes: Vec<ConstraintKeyframe>,
+}
+
+/// JSON format for a constraint keyframe; targets are keyed by `BoneId` name
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConstraintKeyframeJson {
+    #[serde(rename = "t")]
+    pub time: f32,
+    #[serde(rename = "tg")]
+    pub targets: BTreeMap<String, [f32; 3]>,
+}
+
+/// JSON format for a constraint clip (same header as v2, `ck` instead of `kf`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ConstraintClipJson {
+    #[serde(default = "super::clip::default_version", rename = "v")]
+    pub version: u32,
+    #[serde(rename = "n")]
+    pub name: String,
+    #[serde(rename = "d")]
+    pub duration: f32,
+    #[serde(rename = "ck")]
+    pub keyframes: Vec<ConstraintKeyframeJson>,
+}
+
+fn is_torso(bone: BoneId) -> bool {
+    matches!(
+        bone,
+        BoneId::Pelvis | BoneId::Spine1 | BoneId::Spine2 | BoneId::Spine3
+    )
+}
+
+/// Bones IK rotates to move `effector`: the effector and its ancestors, stopping
+/// before the bone that hangs off the torso so the body itself doesn't swing
+pub fn constraint_chain(effector: BoneId) -> Vec<BoneId> {
+    let parent_of = |bone: BoneId| BONE_HIERARCHY[bone.index()].parent;
+    let mut chain = vec![effector];
+    let mut current = effector;
+    while let Some(parent) = parent_of(current) {
+        if is_torso(parent) || parent_of(parent).is_none_or(is_torso) {
+            break;
+        }
+        chain.push(parent);
+        current = parent;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Solve a pose reaching `targets`, starting from the bind pose
+/// Targets are applied in hierarchy order, so parents settle before children.
+pub fn solve_targets(targets: &[(BoneId, Vec3)]) -> RotationPose {
+    let mut sorted = targets.to_vec();
+    sorted.sort_by_key(|&(bone, _)| bone);
+
+    let mut pose = RotationPose::bind_pose();
+    for (bone, target) in sorted {
+        if bone == BoneId::Pelvis {
+            let def = &BONE_HIERARCHY[bone.index()];
+            let offset = def.direction.normalize() * pose.bone_lengths[bone.index()];
+            pose = pose.with_root_position(target - offset);
+        } else {
+            pose = pose.apply_ik(&constraint_chain(bone), target);
+        }
+    }
+    pose
+}
+
+/// Interpolate two target sets; a bone targeted by only one side keeps that target
+fn lerp_targets(a: &[(BoneId, Vec3)], b: &[(BoneId, Vec3)], t: f32) -> Vec<(BoneId, Vec3)> {
+    let mut merged: BTreeMap<BoneId, Vec3> = a.iter().copied().collect();
+    for &(bone, target_b) in b {
+        let target = match merged.get(&bone) {
+            Some(&target_a) => target_a.lerp(target_b, t),
+            None => target_b,
+        };
+        merged.insert(bone, target);
+    }
+    merged.into_iter().collect()
+}
+
+impl ConstraintClip {
+    /// Parse from JSON string
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        use serde::de::Error;
+
+        let clip_json: ConstraintClipJson = serde_json::from_str(json)?;
+        let mut keyframes = Vec::with_capacity(clip_json.keyframes.len());
+        for kf in clip_json.keyframes {
+            let mut targets = Vec::with_capacity(kf.targets.len());
+            for (name, position) in kf.targets {
+                let bone = BoneId::ALL
+                    .into_iter()
+                    .find(|bone| format!("{:?}", bone) == name)
+                    .ok_or_else(|| serde_json::Error::custom(format!("unknown bone `{}`", name)))?;
+                targets.push((bone, Vec3::from_array(position)));
+            }
+            keyframes.push(ConstraintKeyframe {
+                time: kf.time,
+                targets,
+            });
+        }
+        Ok(Self {
+            name: clip_json.name,
+            duration: clip_json.duration,
+            keyframes,
+        })
+    }
+
+    /// Convert to JSON string
+    pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+        let clip_json = ConstraintClipJson {
+            version: 2,
+            name: self.name.clone(),
+            duration: self.duration,
+            keyframes: self
+                .keyframes
+                .iter()
+                .map(|kf| ConstraintKeyframeJson {
+                    time: kf.time,
+                    targets: kf
+                        .targets
+                        .iter()
+                        .map(|(bone, target)| (format!("{:?}", bone), target.to_array()))
+                        .collect(),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&clip_json)
+    }
+
+    /// Targets at `time`, interpolated like `RotationAnimationClip::sample` (looping)
+    pub fn targets_at(&self, time: f32) -> Vec<(BoneId, Vec3)> {
+        let (Some(first), Some(last)) = (self.keyframes.first(), self.keyframes.last()) else {
+            return Vec::new();
+        };
+        let looped_time = time % self.duration;
+        let next_idx = self.keyframes.partition_point(|kf| kf.time <= looped_time);
+
+        if next_idx == 0 {
+            return first.targets.clone();
+        }
+        let (prev, next, segment_duration) = if next_idx >= self.keyframes.len() {
+            // After last keyframe - interpolate to first for looping
+            (last, first, self.duration - last.time + first.time)
+        } else {
+            let (prev, next) = (&self.keyframes[next_idx - 1], &self.keyframes[next_idx]);
+            (prev, next, next.time - prev.time)
+        };
+        let t = if segment_duration > 0.0 {
+            (looped_time - prev.time) / segment_duration
+        } else {
+            0.0
+        };
+        lerp_targets(&prev.targets, &next.targets, t)
+    }
+
+    /// Sample the clip at a given time by solving IK for the interpolated targets
+    pub fn sample(&self, time: f32) -> RotationPose {
+        solve_targets(&self.targets_at(time))
+    }
+}
@@ -0,0 +1,75 @@
+//! Skeleton definition export for external tools
+//!
+//! Clip JSON only carries per-bone rotations; a tool that wants to rebuild the
+//! skeleton also needs the hierarchy it applies to. This serializes
+//! `BONE_HIERARCHY` with the bone names and the bind pose root.
+
+use serde::Serialize;
+
+use super::{BoneId, BONE_HIERARCHY};
+use crate::skeleton_constants::DEFAULT_PELVIS;
+
+/// One bone of the exported definition
+#[derive(Debug, Clone, Serialize)]
+pub struct BoneDefinitionJson {
+    pub index: usize,
+    /// snake_case name, as in the pose fields of clip JSON
+    pub name: &'static str,
+    /// Index of the parent bone, null for the root
+    pub parent: Option<usize>,
+    pub length: f32,
+    /// Unit rest direction in the parent's frame
+    pub direction: [f32; 3],
+}
+
+/// Bone hierarchy and rest layout, as written by `export_skeleton_definition_json`
+#[derive(Debug, Clone, Serialize)]
+pub struct SkeletonDefinitionJson {
+    /// Bind pose position of the root (pelvis)
+    pub root_position: [f32; 3],
+    /// Rest offsets of the left and right hip joints from the pelvis
+    pub left_hip_offset: [f32; 3],
+    pub right_hip_offset: [f32; 3],
+    /// In `BoneId` index order, parents before children
+    pub bones: Vec<BoneDefinitionJson>,
+}
+
+impl SkeletonDefinitionJson {
+    /// Build the definition from `BONE_HIERARCHY`
+    pub fn new() -> Self {
+        let rest_offset = |bone: BoneId| {
+            let def = &BONE_HIERARCHY[bone.index()];
+            (def.direction.normalize() * def.length).to_array()
+        };
+        Self {
+            root_position: DEFAULT_PELVIS.to_array(),
+            left_hip_offset: rest_offset(BoneId::LeftHip),
+            right_hip_offset: rest_offset(BoneId::RightHip),
+            bones: BoneId::ALL
+                .iter()
+                .map(|&bone| {
+                    let def = &BONE_HIERARCHY[bone.index()];
+                    BoneDefinitionJson {
+                        index: bone.index(),
+                        name: bone.name(),
+                        parent: def.parent.map(|p| p.index()),
+                        length: def.length,
+                        direction: def.direction.normalize().to_array(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Default for SkeletonDefinitionJson {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The skeleton definition as pretty-printed JSON
+pub fn export_skeleton_definition_json() -> String {
+    serde_json::to_string_pretty(&SkeletonDefinitionJson::new())
+        .expect("skeleton definition serializes")
+}
@@ -65,6 +65,39 @@ impl BoneId {
             None
         }
     }
+
+    /// snake_case name, as used for the pose fields in the JSON schema
+    pub const fn name(self) -> &'static str {
+        match self {
+            BoneId::Pelvis => "pelvis",
+            BoneId::LeftHip => "l_hip",
+            BoneId::RightHip => "r_hip",
+            BoneId::Spine1 => "spine1",
+            BoneId::LeftKnee => "l_knee",
+            BoneId::RightKnee => "r_knee",
+            BoneId::Spine2 => "spine2",
+            BoneId::LeftAnkle => "l_ankle",
+            BoneId::RightAnkle => "r_ankle",
+            BoneId::Spine3 => "spine3",
+            BoneId::LeftFoot => "l_foot",
+            BoneId::RightFoot => "r_foot",
+            BoneId::Neck => "neck",
+            BoneId::LeftCollar => "l_collar",
+            BoneId::RightCollar => "r_collar",
+            BoneId::Head => "head",
+            BoneId::LeftShoulder => "l_shoulder",
+            BoneId::RightShoulder => "r_shoulder",
+            BoneId::LeftElbow => "l_elbow",
+            BoneId::RightElbow => "r_elbow",
+            BoneId::LeftWrist => "l_wrist",
+            BoneId::RightWrist => "r_wrist",
+        }
+    }
+
+    /// Look up a bone by its `name`
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|bone| bone.name() == name)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
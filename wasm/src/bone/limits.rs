@@ -0,0 +1,72 @@
+use super::clip::EulerAngles;
+use super::id::BoneId;
+
+/// Per-axis Euler limits for a joint's local rotation, in degrees
+///
+/// `None` leaves an axis free. Used when authoring poses so hinge joints
+/// can't be bent backwards; playback of existing clips is not clamped.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JointLimits {
+    pub x: Option<(f32, f32)>,
+    pub y: Option<(f32, f32)>,
+    pub z: Option<(f32, f32)>,
+}
+
+impl JointLimits {
+    /// No limits on any axis
+    pub const FREE: JointLimits = JointLimits {
+        x: None,
+        y: None,
+        z: None,
+    };
+
+    /// Clamp Euler angles (degrees, XYZ order) into the allowed ranges
+    pub fn clamp(&self, euler: EulerAngles) -> EulerAngles {
+        let clamp_axis = |value: f32, limit: Option<(f32, f32)>| match limit {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        };
+        EulerAngles {
+            x: clamp_axis(euler.x, self.x),
+            y: clamp_axis(euler.y, self.y),
+            z: clamp_axis(euler.z, self.z),
+        }
+    }
+
+    /// Limits for one axis (0 = X, 1 = Y, 2 = Z)
+    pub fn axis_mut(&mut self, axis: usize) -> Option<&mut Option<(f32, f32)>> {
+        match axis {
+            0 => Some(&mut self.x),
+            1 => Some(&mut self.y),
+            2 => Some(&mut self.z),
+            _ => None,
+        }
+    }
+}
+
+/// Knee flexion (positive X swings the shin backwards)
+const KNEE: JointLimits = JointLimits {
+    x: Some((0.0, 150.0)),
+    y: None,
+    z: None,
+};
+
+/// Default limits per bone: the hinge joints (knees and elbows) only bend one way
+///
+/// A bone's local rotation orients its child segment, so the knee limits sit on
+/// `LeftKnee`/`RightKnee` and the elbow limits on `LeftElbow`/`RightElbow`.
+/// Elbows flex about Y, in opposite directions on each side.
+pub fn default_joint_limits() -> [JointLimits; BoneId::COUNT] {
+    let mut limits = [JointLimits::FREE; BoneId::COUNT];
+    limits[BoneId::LeftKnee.index()] = KNEE;
+    limits[BoneId::RightKnee.index()] = KNEE;
+    limits[BoneId::LeftElbow.index()] = JointLimits {
+        y: Some((-150.0, 0.0)),
+        ..JointLimits::FREE
+    };
+    limits[BoneId::RightElbow.index()] = JointLimits {
+        y: Some((0.0, 150.0)),
+        ..JointLimits::FREE
+    };
+    limits
+}
@@ -0,0 +1,68 @@
+use super::id::BoneId;
+
+/// A set of bones, e.g. to restrict an edit to the legs
+/// Bit i corresponds to the BoneId with index i.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoneMask(u32);
+
+impl BoneMask {
+    /// No bones
+    pub const NONE: BoneMask = BoneMask(0);
+    /// Every bone
+    pub const ALL: BoneMask = BoneMask((1 << BoneId::COUNT) - 1);
+
+    /// Mask from raw bits (e.g. from JavaScript); bits past the last bone are dropped
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+
+    /// Mask holding exactly these bones
+    pub fn from_bones(bones: &[BoneId]) -> Self {
+        bones.iter().fold(Self::NONE, |mask, &bone| mask.with(bone))
+    }
+
+    /// Hips, knees, ankles and feet of both legs
+    pub fn legs() -> Self {
+        Self::from_bones(&[
+            BoneId::LeftHip,
+            BoneId::RightHip,
+            BoneId::LeftKnee,
+            BoneId::RightKnee,
+            BoneId::LeftAnkle,
+            BoneId::RightAnkle,
+            BoneId::LeftFoot,
+            BoneId::RightFoot,
+        ])
+    }
+
+    /// Collars, shoulders, elbows and wrists of both arms
+    pub fn arms() -> Self {
+        Self::from_bones(&[
+            BoneId::LeftCollar,
+            BoneId::RightCollar,
+            BoneId::LeftShoulder,
+            BoneId::RightShoulder,
+            BoneId::LeftElbow,
+            BoneId::RightElbow,
+            BoneId::LeftWrist,
+            BoneId::RightWrist,
+        ])
+    }
+
+    /// Return a new mask that also holds `bone`
+    pub fn with(self, bone: BoneId) -> Self {
+        Self(self.0 | (1 << bone.index()))
+    }
+
+    /// Check if the mask holds `bone`
+    #[inline]
+    pub fn contains(self, bone: BoneId) -> bool {
+        self.0 & (1 << bone.index()) != 0
+    }
+
+    /// Raw bitset
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+}
@@ -1,13 +1,23 @@
 pub mod anim_ids;
+pub mod batch;
 pub mod cache;
 pub mod clip;
+pub mod constraint;
+pub mod definition;
 pub mod id;
+pub mod limits;
+pub mod mask;
 pub mod pose;
 
 pub use anim_ids::*;
+pub use batch::*;
 pub use cache::*;
 pub use clip::*;
+pub use constraint::*;
+pub use definition::*;
 pub use id::*;
+pub use limits::*;
+pub use mask::*;
 pub use pose::*;
 
 #[cfg(test)]
@@ -129,10 +139,12 @@ mod tests {
         let kf_a = RotationKeyframe {
             time: 0.0,
             pose: pose_a,
+            tcb: None,
         };
         let kf_b = RotationKeyframe {
             time: 1.0,
             pose: pose_b,
+            tcb: None,
         };
 
         let clip = RotationAnimationClip {
@@ -223,6 +235,42 @@ mod tests {
         );
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_euler_order() {
+        use glam::EulerRot;
+        let euler = EulerAngles {
+            x: 30.0,
+            y: -50.0,
+            z: 70.0,
+        };
+        let xyz = euler.to_quat_with_order(EulerRot::XYZ);
+        let zyx = euler.to_quat_with_order(EulerRot::ZYX);
+        assert!(xyz.dot(zyx).abs() < 0.99, "Orders should differ");
+        assert_eq!(xyz, euler.to_quat());
+
+        for order in [EulerRot::XYZ, EulerRot::ZYX, EulerRot::YXZ] {
+            let q = euler.to_quat_with_order(order);
+            let back = EulerAngles::from_quat_with_order(q, order);
+            assert!((back.x - euler.x).abs() < 1e-3, "{:?}: {:?}", order, back);
+            assert!((back.y - euler.y).abs() < 1e-3, "{:?}: {:?}", order, back);
+            assert!((back.z - euler.z).abs() < 1e-3, "{:?}: {:?}", order, back);
+        }
+
+        // Clip JSON selects the order with "eo"
+        let clip = |order: &str| {
+            let json = format!(
+                r#"{{ "n": "o", "d": 1.0, {} "kf": [{{ "t": 0.0, "p": {{ "h": {{ "x": 30.0, "y": -50.0, "z": 70.0 }} }} }}] }}"#,
+                order
+            );
+            RotationAnimationClip::from_json(&json).unwrap().keyframes[0]
+                .pose
+                .local_rotations[BoneId::Head.index()]
+        };
+        assert_eq!(clip(""), xyz);
+        assert_eq!(clip(r#""eo": "zyx","#), zyx);
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_animation_parsing() {
@@ -279,4 +327,1504 @@ mod tests {
         assert!((clip.duration - 5.0).abs() < 0.1);
         assert!((clip.keyframes[0].pose.root_position.y - 1.0).abs() < 0.01);
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bone_names_round_trip() {
+        for bone in BoneId::ALL {
+            assert_eq!(BoneId::from_name(bone.name()), Some(bone));
+        }
+        assert_eq!(BoneId::from_name("LeftHip"), None);
+
+        // Same names as the pose fields of the v2 schema
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../../../schemas/animation.v2.schema.json"))
+                .unwrap();
+        let mut fields: Vec<&str> = schema["properties"]["keyframes"]["items"]["properties"]
+            ["pose"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .filter(|&key| key != "root_position")
+            .collect();
+        let mut names: Vec<&str> = BoneId::ALL.iter().map(|bone| bone.name()).collect();
+        fields.sort_unstable();
+        names.sort_unstable();
+        assert_eq!(fields, names);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_binary_delta_round_trip() {
+        // Smooth motion: elbow swing and a root bob; everything else static
+        let count = 60;
+        let keyframes = (0..count)
+            .map(|i| {
+                let phase = i as f32 / (count - 1) as f32 * std::f32::consts::TAU;
+                let pose = RotationPose::bind_pose()
+                    .with_rotation(BoneId::LeftElbow, Quat::from_rotation_z(0.8 * phase.sin()))
+                    .with_rotation(BoneId::RightKnee, Quat::from_rotation_x(-0.5 * phase.cos()));
+                let root = pose.root_position + Vec3::Y * 0.05 * phase.sin();
+                RotationKeyframe {
+                    time: 2.0 * i as f32 / (count - 1) as f32,
+                    pose: pose.with_root_position(root),
+                    tcb: None,
+                }
+            })
+            .collect();
+        let clip = RotationAnimationClip {
+            name: "smooth".to_string(),
+            duration: 2.0,
+            keyframes,
+        };
+
+        let full = clip.to_binary().unwrap();
+        let delta = clip.to_binary_delta().unwrap();
+        assert!(
+            delta.len() < full.len(),
+            "delta {} vs full {}",
+            delta.len(),
+            full.len()
+        );
+
+        let decoded = RotationAnimationClip::from_binary_any(&delta, "smooth".to_string()).unwrap();
+        let reference =
+            RotationAnimationClip::from_binary_any(&full, "smooth".to_string()).unwrap();
+        assert_eq!(decoded.keyframes.len(), clip.keyframes.len());
+        for ((original, decoded), reference) in clip
+            .keyframes
+            .iter()
+            .zip(&decoded.keyframes)
+            .zip(&reference.keyframes)
+        {
+            assert!((original.time - decoded.time).abs() < 1e-3);
+            // Within quantization tolerance of the source, identical to the full format
+            assert!(original
+                .pose
+                .root_position
+                .abs_diff_eq(decoded.pose.root_position, 1e-3));
+            assert_eq!(decoded.pose.root_position, reference.pose.root_position);
+            for bone in BoneId::ALL {
+                let (a, b) = (
+                    original.pose.local_rotations[bone.index()],
+                    decoded.pose.local_rotations[bone.index()],
+                );
+                assert!(
+                    a.dot(b).abs() > 1.0 - 1e-6,
+                    "{:?}: {:?} vs {:?}",
+                    bone,
+                    a,
+                    b
+                );
+                assert_eq!(b, reference.pose.local_rotations[bone.index()]);
+            }
+        }
+
+        // Truncated data is rejected rather than misread
+        let truncated = &delta[..delta.len() - 1];
+        assert!(RotationAnimationClip::from_binary_any(truncated, "x".to_string()).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_overlay_range_takes_masked_bones_from_source() {
+        let clip = |name: &str, keys: &[(f32, f32, f32)]| RotationAnimationClip {
+            name: name.to_string(),
+            duration: 1.0,
+            keyframes: keys
+                .iter()
+                .map(|&(time, elbow, knee)| RotationKeyframe {
+                    time,
+                    pose: RotationPose::bind_pose()
+                        .with_rotation(BoneId::LeftElbow, Quat::from_rotation_z(elbow))
+                        .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(knee)),
+                    tcb: None,
+                })
+                .collect(),
+        };
+        // A moves the arm, B moves the leg (and holds the arm elsewhere)
+        let a = clip("a", &[(0.0, 0.0, 0.0), (0.5, 1.2, 0.0)]);
+        let b = clip(
+            "b",
+            &[(0.0, -0.4, 0.0), (0.25, -0.4, 1.0), (0.75, -0.4, 0.3)],
+        );
+
+        let merged = a.overlay_range(&b, BoneMask::legs(), 0.0, a.duration);
+        let close = |x: Quat, y: Quat| x.dot(y).abs() > 1.0 - 1e-6;
+        for time in [0.1, 0.3, 0.6, 0.9] {
+            let pose = merged.sample(time);
+            let (from_a, from_b) = (a.sample(time), b.sample(time));
+            let elbow = BoneId::LeftElbow.index();
+            let knee = BoneId::LeftKnee.index();
+            assert!(close(
+                pose.local_rotations[elbow],
+                from_a.local_rotations[elbow]
+            ));
+            assert!(close(
+                pose.local_rotations[knee],
+                from_b.local_rotations[knee]
+            ));
+        }
+
+        // Outside a partial range (and its blend ramp) A is untouched
+        let partial = a.overlay_range(&b, BoneMask::legs(), 0.4, 0.6);
+        let knee = BoneId::LeftKnee.index();
+        assert_eq!(partial.sample(0.2).local_rotations[knee], Quat::IDENTITY);
+        assert!(close(
+            partial.sample(0.5).local_rotations[knee],
+            b.sample(0.5).local_rotations[knee]
+        ));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_enforce_continuity_removes_sign_flips() {
+        let q = Quat::from_rotation_z(0.3);
+        let keyframes = [q, -q, q, -q]
+            .into_iter()
+            .enumerate()
+            .map(|(i, rot)| RotationKeyframe {
+                time: i as f32 * 0.25,
+                pose: RotationPose::bind_pose().with_rotation(BoneId::LeftElbow, rot),
+                tcb: None,
+            })
+            .collect();
+
+        let mut clip = RotationAnimationClip {
+            name: "flip".to_string(),
+            duration: 1.0,
+            keyframes,
+        };
+        clip.enforce_continuity();
+
+        for pair in clip.keyframes.windows(2) {
+            for i in 0..BoneId::COUNT {
+                let dot = pair[0].pose.local_rotations[i].dot(pair[1].pose.local_rotations[i]);
+                assert!(dot >= 0.0, "Bone {} flips sign between keyframes", i);
+            }
+        }
+        // Same rotation, only the sign is changed
+        let elbow = clip.keyframes[1].pose.local_rotations[BoneId::LeftElbow.index()];
+        assert!(elbow.angle_between(q) < 1e-4);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_scale_time() {
+        let keyframes = (0..4)
+            .map(|i| RotationKeyframe {
+                time: i as f32 * 0.25,
+                pose: RotationPose::bind_pose()
+                    .with_rotation(BoneId::Spine1, Quat::from_rotation_x(i as f32 * 0.4)),
+                tcb: None,
+            })
+            .collect();
+        let clip = RotationAnimationClip {
+            name: "scale".to_string(),
+            duration: 1.0,
+            keyframes,
+        };
+
+        let scaled = clip.scale_time(2.0).unwrap();
+        assert_eq!(scaled.duration, 2.0);
+        for (a, b) in clip.keyframes.iter().zip(&scaled.keyframes) {
+            assert_eq!(b.time, a.time * 2.0);
+        }
+
+        for t in [0.1, 0.3, 0.6, 0.9] {
+            let original = clip.sample(t).local_rotations[BoneId::Spine1.index()];
+            let stretched = scaled.sample(2.0 * t).local_rotations[BoneId::Spine1.index()];
+            assert!(
+                original.abs_diff_eq(stretched, 1e-5),
+                "Mismatch at t = {}",
+                t
+            );
+        }
+
+        assert!(clip.scale_time(0.0).is_err());
+        assert!(clip.scale_time(-1.0).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_similarity() {
+        let target = RotationPose::bind_pose()
+            .with_rotation(BoneId::Spine2, Quat::from_rotation_x(0.3))
+            .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(0.8));
+        assert_eq!(target.similarity(&target.clone()), 1.0);
+
+        // Left arm flipped around at the shoulder
+        let flipped = target.clone().with_rotation(
+            BoneId::LeftShoulder,
+            Quat::from_rotation_y(std::f32::consts::PI),
+        );
+        let score = flipped.similarity(&target);
+        assert!(score < 0.6, "score {}", score);
+        let worst = flipped.worst_bone(&target);
+        assert!(
+            matches!(
+                worst,
+                BoneId::LeftShoulder | BoneId::LeftElbow | BoneId::LeftWrist
+            ),
+            "{:?}",
+            worst
+        );
+
+        // Ignoring the arm restores a perfect score
+        let mut weights = [1.0; BoneId::COUNT];
+        for bone in [BoneId::LeftShoulder, BoneId::LeftElbow, BoneId::LeftWrist] {
+            weights[bone.index()] = 0.0;
+        }
+        assert!(flipped.similarity_weighted(&target, &weights) > 0.999);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_floor_penetration() {
+        let grounded = RotationPose::bind_pose().apply_floor_constraint();
+        assert!(grounded.floor_penetration() < 1e-5);
+
+        let root = grounded.root_position - Vec3::Y * 0.2;
+        let sunk = grounded.clone().with_root_position(root);
+        assert!((sunk.floor_penetration() - 0.2).abs() < 1e-4);
+        // Detection doesn't move anything
+        assert_eq!(sunk.root_position, root);
+        assert!(sunk.apply_floor_constraint().floor_penetration() < 1e-5);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_shuffled_keyframes_are_sorted_on_load() {
+        let json = r#"{
+            "n": "shuffled", "d": 1.0,
+            "kf": [
+                { "t": 0.75, "p": { "s1": { "x": 30.0, "y": 0.0, "z": 0.0 } } },
+                { "t": 0.0, "p": {} },
+                { "t": 0.75, "p": { "s1": { "x": -30.0, "y": 0.0, "z": 0.0 } } },
+                { "t": 0.25, "p": { "s1": { "x": 10.0, "y": 0.0, "z": 0.0 } } }
+            ]
+        }"#;
+        let mut clip = RotationAnimationClip::from_json(json).unwrap();
+        let times: Vec<f32> = clip.keyframes.iter().map(|kf| kf.time).collect();
+        assert_eq!(times, [0.0, 0.25, 0.75]);
+        // The first keyframe at a repeated time wins
+        let spine = |pose: &RotationPose| pose.local_rotations[BoneId::Spine1.index()];
+        let kept = Quat::from_rotation_x(30f32.to_radians());
+        assert!(spine(&clip.keyframes[2].pose).abs_diff_eq(kept, 1e-5));
+
+        // Spine bend grows monotonically up to the last keyframe
+        let angles: Vec<f32> = (0..=15)
+            .map(|i| spine(&clip.sample(i as f32 * 0.05)).angle_between(Quat::IDENTITY))
+            .collect();
+        assert!(
+            angles.windows(2).all(|pair| pair[1] >= pair[0] - 1e-5),
+            "{:?}",
+            angles
+        );
+
+        assert!(!clip.sort_keyframes());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_locked_foot_follows_interpolated_track() {
+        // A leg swing: the ankle arcs under plain slerp. Knees stay bent, as
+        // IK can't fold a straight leg to shorten it.
+        let planted =
+            RotationPose::bind_pose().with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(0.3));
+        let swung = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftHip, Quat::from_rotation_x(-0.9))
+            .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(0.6))
+            .with_root_position(Vec3::new(0.0, 0.9, 0.1));
+        let clip = RotationAnimationClip {
+            name: "swing".to_string(),
+            duration: 2.0,
+            keyframes: [(0.0, planted), (1.0, swung)]
+                .into_iter()
+                .map(|(time, pose)| RotationKeyframe {
+                    time,
+                    pose,
+                    tcb: None,
+                })
+                .collect(),
+        };
+        let ankle = BoneId::LeftAnkle;
+        let start = clip.keyframes[0].pose.get_position(ankle);
+        let end = clip.keyframes[1].pose.get_position(ankle);
+
+        let mut max_drift: f32 = 0.0;
+        for i in 1..10 {
+            let t = i as f32 * 0.1;
+            let track = start.lerp(end, t);
+            let corrected = clip.sample_ik_corrected(t, &[ankle]).get_position(ankle);
+            assert!(
+                corrected.distance(track) < 2.0 * RotationPose::IK_TOLERANCE,
+                "t={}: {} vs {}",
+                t,
+                corrected,
+                track
+            );
+            max_drift = max_drift.max(clip.sample(t).get_position(ankle).distance(track));
+        }
+        assert!(max_drift > 0.02, "plain slerp drift {}", max_drift);
+
+        // Unlocked bones and keyframe times sample as before
+        let plain = clip.sample(0.5);
+        let corrected = clip.sample_ik_corrected(0.5, &[ankle]);
+        let right = BoneId::RightAnkle;
+        assert!(corrected
+            .get_position(right)
+            .abs_diff_eq(plain.get_position(right), 1e-6));
+        assert!(clip
+            .sample_ik_corrected(0.0, &[ankle])
+            .get_position(ankle)
+            .abs_diff_eq(start, 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pad_holds_first_and_last_frames() {
+        let first = Quat::from_rotation_x(0.5);
+        let last = Quat::from_rotation_x(-0.8);
+        let clip = RotationAnimationClip {
+            name: "pad".to_string(),
+            duration: 1.0,
+            keyframes: [(0.0, first), (1.0, last)]
+                .into_iter()
+                .map(|(time, rotation)| RotationKeyframe {
+                    time,
+                    pose: RotationPose::bind_pose().with_rotation(BoneId::Spine1, rotation),
+                    tcb: None,
+                })
+                .collect(),
+        };
+
+        let padded = clip.pad(0.5, 0.25);
+        assert_eq!(padded.duration, 1.75);
+        let times: Vec<f32> = padded.keyframes.iter().map(|kf| kf.time).collect();
+        assert_eq!(times, [0.0, 0.5, 1.5, 1.75]);
+
+        let spine = |time: f32| padded.sample(time).local_rotations[BoneId::Spine1.index()];
+        for t in [0.0, 0.2, 0.45] {
+            assert!(spine(t).abs_diff_eq(first, 1e-5), "lead-in at {}", t);
+        }
+        assert!(spine(1.6).abs_diff_eq(last, 1e-5));
+
+        // Zero padding adds no keyframes
+        assert_eq!(clip.pad(0.0, 0.0).keyframes.len(), 2);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_diff() {
+        let pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::Spine2, Quat::from_rotation_y(0.2))
+            .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(-0.7));
+
+        let same = pose.diff(&pose.clone());
+        assert!(same.bone_angles.iter().all(|a| a.abs() < 1e-2));
+        assert_eq!(same.root_delta, Vec3::ZERO);
+
+        // Rotating one bone only shows up on that bone, not its children
+        let edited = pose
+            .clone()
+            .with_rotation(BoneId::LeftElbow, Quat::from_rotation_z(30f32.to_radians()));
+        let diff = pose.diff(&edited);
+        for bone in BoneId::ALL {
+            let angle = diff.bone_angles[bone.index()];
+            if bone == BoneId::LeftElbow {
+                assert!((angle - 30.0).abs() < 0.1, "Elbow diff {}", angle);
+            } else {
+                assert!(angle.abs() < 1e-2, "{:?} diff {}", bone, angle);
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_extract_root_motion() {
+        let start = RotationPose::bind_pose().root_position;
+        let keyframes = (0..=4)
+            .map(|i| {
+                let t = i as f32 * 0.25;
+                RotationKeyframe {
+                    time: t,
+                    pose: RotationPose::bind_pose()
+                        .with_root_position(start + Vec3::new(0.0, 0.0, t)),
+                    tcb: None,
+                }
+            })
+            .collect();
+        let clip = RotationAnimationClip {
+            name: "lunge".to_string(),
+            duration: 1.0,
+            keyframes,
+        };
+
+        let (in_place, track) = clip.extract_root_motion();
+
+        for kf in &in_place.keyframes {
+            assert_eq!(kf.pose.root_position.x, 0.0);
+            assert_eq!(kf.pose.root_position.z, 0.0);
+            assert_eq!(kf.pose.root_position.y, start.y);
+        }
+        assert_eq!(track.len(), 5);
+        assert_eq!(track[0], (0.0, Vec3::ZERO));
+        let (end_time, end) = track[4];
+        assert_eq!(end_time, 1.0);
+        assert!((end - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-6);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_mirror_clip_swaps_raised_arm() {
+        // Raise the left arm by rotating the collar about +Z
+        let raised = Quat::from_rotation_z(1.2);
+        let keyframes = vec![
+            RotationKeyframe {
+                time: 0.0,
+                pose: RotationPose::bind_pose(),
+                tcb: None,
+            },
+            RotationKeyframe {
+                time: 0.6,
+                pose: RotationPose::bind_pose().with_rotation(BoneId::LeftCollar, raised),
+                tcb: None,
+            },
+        ];
+        let clip = RotationAnimationClip {
+            name: "left_arm".to_string(),
+            duration: 1.0,
+            keyframes,
+        };
+
+        let mirrored = clip.mirror();
+        assert_eq!(mirrored.duration, clip.duration);
+
+        let original = &clip.keyframes[1].pose;
+        let flipped = &mirrored.keyframes[1].pose;
+        assert_eq!(mirrored.keyframes[1].time, 0.6);
+        assert_eq!(
+            flipped.local_rotations[BoneId::LeftCollar.index()],
+            Quat::IDENTITY
+        );
+
+        let left_wrist = original.get_position(BoneId::LeftWrist);
+        let right_wrist = flipped.get_position(BoneId::RightWrist);
+        let bind_wrist = RotationPose::bind_pose().get_position(BoneId::RightWrist);
+        assert!(
+            right_wrist.y > bind_wrist.y + 0.2,
+            "Right arm should be raised"
+        );
+        let expected = Vec3::new(-left_wrist.x, left_wrist.y, left_wrist.z);
+        assert!(
+            right_wrist.distance(expected) < 0.05,
+            "Mirrored wrist {:?}, expected {:?}",
+            right_wrist,
+            expected
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ik_chain_lengths_exact() {
+        // The collar owns the first segment, the shoulder and elbow the rest
+        let chain = [BoneId::LeftShoulder, BoneId::LeftElbow, BoneId::LeftWrist];
+        let rest = RotationPose::bind_pose();
+
+        // Reachable, overhead (large shoulder corrective) and out of reach
+        for target in [
+            Vec3::new(0.5, 0.0, 0.3),
+            Vec3::new(0.2, 0.9, 0.0),
+            Vec3::new(2.0, 0.5, -1.0),
+        ] {
+            let pose = rest.clone().apply_ik(&chain, target);
+            // Only the arm moves, not the torso
+            assert_eq!(
+                pose.get_position(BoneId::Spine3),
+                rest.get_position(BoneId::Spine3)
+            );
+
+            let mut start = pose.get_position(BoneId::LeftCollar);
+            for bone in chain {
+                let end = pose.get_position(bone);
+                let error = (start.distance(end) - BONE_HIERARCHY[bone.index()].length).abs();
+                assert!(error < 1e-6, "{:?} length error {}", bone, error);
+                start = end;
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_content_hash() {
+        let make_clip = || RotationAnimationClip {
+            name: "hash".to_string(),
+            duration: 1.0,
+            keyframes: vec![
+                RotationKeyframe {
+                    time: 0.0,
+                    pose: RotationPose::bind_pose(),
+                    tcb: None,
+                },
+                RotationKeyframe {
+                    time: 0.5,
+                    pose: RotationPose::bind_pose()
+                        .with_rotation(BoneId::Neck, Quat::from_rotation_y(0.4)),
+                    tcb: None,
+                },
+            ],
+        };
+        let clip = make_clip();
+        assert_eq!(clip.content_hash(), make_clip().content_hash());
+
+        // FP noise below the quantization step is ignored
+        let mut noisy = make_clip();
+        noisy.keyframes[1].time += 1e-7;
+        assert_eq!(clip.content_hash(), noisy.content_hash());
+
+        let mut edited = make_clip();
+        edited.keyframes[1].pose = edited.keyframes[1]
+            .pose
+            .clone()
+            .with_rotation(BoneId::Neck, Quat::from_rotation_y(0.5));
+        assert_ne!(clip.content_hash(), edited.content_hash());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_distribute_twist_splits_roll() {
+        let chain = [BoneId::LeftElbow, BoneId::LeftWrist];
+        let pose = RotationPose::bind_pose().distribute_twist_weighted(
+            &chain,
+            &[0.5, 0.5],
+            std::f32::consts::FRAC_PI_2,
+        );
+
+        for bone in chain {
+            let (axis, angle) = pose.local_rotations[bone.index()].to_axis_angle();
+            let bone_axis = BONE_HIERARCHY[bone.index()].direction.normalize();
+            assert!(
+                (angle.to_degrees() - 45.0).abs() < 1e-3,
+                "{:?} rolled {} deg",
+                bone,
+                angle.to_degrees()
+            );
+            assert!(
+                axis.abs_diff_eq(bone_axis, 1e-4),
+                "{:?} axis {:?}",
+                bone,
+                axis
+            );
+        }
+
+        // Untouched bones keep their rotation
+        assert_eq!(
+            pose.local_rotations[BoneId::LeftShoulder.index()],
+            Quat::IDENTITY
+        );
+        let even = RotationPose::bind_pose().distribute_twist(&chain, std::f32::consts::FRAC_PI_2);
+        assert_eq!(even.local_rotations, pose.local_rotations);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_tcb_tension_flattens_spline() {
+        // Spine accelerates 0 -> 0.5 -> 1 -> 2 rad; sample the middle of segment 1 -> 2
+        let make_clip = |tension: f32| RotationAnimationClip {
+            name: "tcb".to_string(),
+            duration: 1.0,
+            keyframes: [0.0, 0.5, 1.0, 2.0]
+                .into_iter()
+                .enumerate()
+                .map(|(i, angle)| RotationKeyframe {
+                    time: i as f32 * 0.25,
+                    pose: RotationPose::bind_pose()
+                        .with_rotation(BoneId::Spine1, Quat::from_rotation_x(angle)),
+                    tcb: Some(Tcb {
+                        tension,
+                        ..Tcb::default()
+                    }),
+                })
+                .collect(),
+        };
+        let deviation = |clip: &RotationAnimationClip| {
+            let linear = RotationPose::lerp(&clip.keyframes[1].pose, &clip.keyframes[2].pose, 0.5);
+            let spline = clip.sample(0.375);
+            let bone = BoneId::Spine1.index();
+            spline.local_rotations[bone].angle_between(linear.local_rotations[bone])
+        };
+
+        let catmull_rom = deviation(&make_clip(0.0));
+        let tense = deviation(&make_clip(0.8));
+        assert!(
+            catmull_rom > 0.01,
+            "Catmull-Rom should bend away from slerp"
+        );
+        assert!(
+            tense < catmull_rom,
+            "Tension should flatten: {} vs {}",
+            tense,
+            catmull_rom
+        );
+
+        // Endpoints are interpolated exactly
+        let clip = make_clip(0.0);
+        let at_key = clip.sample(0.25).local_rotations[BoneId::Spine1.index()];
+        assert!(at_key.abs_diff_eq(Quat::from_rotation_x(0.5), 1e-5));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_tcb_json_roundtrip() {
+        let json = r#"{
+            "n": "tcb", "d": 1.0,
+            "kf": [
+                { "t": 0.0, "p": {}, "tn": 0.5 },
+                { "t": 0.5, "p": {} }
+            ]
+        }"#;
+        let clip = RotationAnimationClip::from_json(json).unwrap();
+        assert_eq!(
+            clip.keyframes[0].tcb,
+            Some(Tcb {
+                tension: 0.5,
+                continuity: 0.0,
+                bias: 0.0
+            })
+        );
+        assert_eq!(clip.keyframes[1].tcb, None);
+
+        let reparsed = RotationAnimationClip::from_json(&clip.to_json_string().unwrap()).unwrap();
+        assert_eq!(reparsed.keyframes[0].tcb, clip.keyframes[0].tcb);
+        assert_eq!(reparsed.keyframes[1].tcb, None);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_origin_root_survives_json_roundtrip() {
+        let keyframe = |time: f32, pose: RotationPose| RotationKeyframe {
+            time,
+            pose,
+            tcb: None,
+        };
+        let mut clip =
+            RotationAnimationClip::from_json(r#"{"n": "ground", "d": 1.0, "kf": []}"#).unwrap();
+        clip.keyframes = vec![
+            keyframe(
+                0.0,
+                RotationPose::bind_pose().with_root_position(Vec3::ZERO),
+            ),
+            keyframe(0.5, RotationPose::bind_pose()),
+        ];
+
+        let json = clip.to_json_string().unwrap();
+        let reparsed = RotationAnimationClip::from_json(&json).unwrap();
+        assert_eq!(reparsed.keyframes[0].pose.root_position, Vec3::ZERO);
+        assert_eq!(
+            reparsed.keyframes[1].pose.root_position,
+            RotationPose::bind_pose().root_position
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_gltf_animation_export() {
+        let json = r#"{
+            "n": "wave", "d": 1.0,
+            "kf": [
+                { "t": 0.0, "p": { "rp": [0.0, 1.0, 0.0] } },
+                { "t": 0.25, "p": { "le": { "x": 0.0, "y": 0.0, "z": 90.0 } } },
+                { "t": 0.75, "p": { "le": { "x": 0.0, "y": 0.0, "z": 45.0 }, "h": { "x": 10.0, "y": 0.0, "z": 0.0 } } }
+            ]
+        }"#;
+        let clip = RotationAnimationClip::from_json(json).unwrap();
+        let exported = clip.to_gltf_animation_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&exported).unwrap();
+
+        let channels = value["channels"].as_array().unwrap();
+        let samplers = value["samplers"].as_array().unwrap();
+        assert_eq!(channels.len(), samplers.len());
+
+        let nodes_with = |path: &str| -> Vec<&str> {
+            channels
+                .iter()
+                .filter(|c| c["target"]["path"] == path)
+                .map(|c| c["target"]["node"].as_str().unwrap())
+                .collect()
+        };
+        assert_eq!(nodes_with("rotation"), vec!["Head", "LeftElbow"]);
+        assert_eq!(nodes_with("translation"), vec!["Pelvis"]);
+
+        for (channel, sampler) in channels.iter().zip(samplers) {
+            let index = channel["sampler"].as_u64().unwrap() as usize;
+            assert_eq!(&samplers[index], sampler);
+            let input: Vec<f64> = sampler["input"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|t| t.as_f64().unwrap())
+                .collect();
+            assert_eq!(input, vec![0.0, 0.25, 0.75]);
+
+            let width = if channel["target"]["path"] == "rotation" {
+                4
+            } else {
+                3
+            };
+            assert_eq!(
+                sampler["output"].as_array().unwrap().len(),
+                input.len() * width
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_apply_wind() {
+        let pose =
+            RotationPose::bind_pose().with_rotation(BoneId::Spine2, Quat::from_rotation_x(0.2));
+        let direction = Vec3::new(1.0, 0.0, 0.5);
+
+        let calm = pose.clone().apply_wind(1.3, 0.0, direction);
+        assert_eq!(calm.local_rotations, pose.local_rotations);
+        assert_eq!(calm.root_position, pose.root_position);
+
+        // Deterministic, and extremities move more than the hips
+        for time in [0.0, 0.7, 2.5] {
+            let windy = pose.clone().apply_wind(time, 1.0, direction);
+            let again = pose.clone().apply_wind(time, 1.0, direction);
+            assert_eq!(windy.local_rotations, again.local_rotations);
+
+            let moved = |bone: BoneId| windy.get_position(bone).distance(pose.get_position(bone));
+            for (hand, hip) in [
+                (BoneId::LeftWrist, BoneId::LeftHip),
+                (BoneId::RightWrist, BoneId::RightHip),
+            ] {
+                assert!(
+                    moved(hand) > 0.01,
+                    "{:?} barely moved at t = {}",
+                    hand,
+                    time
+                );
+                assert!(moved(hand) > moved(hip));
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_reach_extent_left_arm() {
+        let pose = RotationPose::bind_pose();
+
+        // Dragging the wrist swings the upper arm and forearm about the shoulder
+        let (center, radius) = pose.reach_extent(BoneId::LeftWrist);
+        let upper_arm = BONE_HIERARCHY[BoneId::LeftElbow.index()].length;
+        let forearm = BONE_HIERARCHY[BoneId::LeftWrist.index()].length;
+        assert!((radius - (upper_arm + forearm)).abs() < 1e-6);
+        assert!(center.abs_diff_eq(pose.get_position(BoneId::LeftShoulder), 1e-6));
+
+        // The rest-pose wrist is at full reach, and IK cannot take it further
+        let wrist = pose.get_position(BoneId::LeftWrist);
+        assert!((wrist.distance(center) - radius).abs() < 1e-4);
+        let stretched = pose.clone().apply_ik(
+            &[BoneId::LeftElbow, BoneId::LeftWrist],
+            center + Vec3::Y * 2.0,
+        );
+        // Measured from the stretched pose's shoulder: the corrective shifts it
+        // toward the raised arm
+        let (stretched_center, _) = stretched.reach_extent(BoneId::LeftWrist);
+        assert!(
+            stretched
+                .get_position(BoneId::LeftWrist)
+                .distance(stretched_center)
+                <= radius + 1e-4
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bind_pose_bone_matrices_are_identity() {
+        // The mesh is built at the bind pose joints, so nothing moves
+        let pose = RotationPose::bind_pose();
+        let matrices = pose.to_bone_matrices();
+        assert_eq!(matrices, pose.compute_bone_matrices());
+        for (i, matrix) in matrices.iter().enumerate() {
+            assert!(
+                matrix.abs_diff_eq(glam::Mat4::IDENTITY, 1e-4),
+                "Render bone {} is not identity: {:?}",
+                i,
+                matrix
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_prepared_pose_interleaved_queries() {
+        let pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::Spine2, Quat::from_rotation_x(0.3))
+            .with_rotation(BoneId::LeftElbow, Quat::from_rotation_y(-1.0));
+        let prepared = pose.prepared();
+
+        // Nested queries hold no borrows, so they cannot conflict
+        let farthest = BoneId::ALL
+            .iter()
+            .map(|&a| {
+                BoneId::ALL
+                    .iter()
+                    .map(|&b| prepared.position(a).distance(prepared.position(b)))
+                    .fold(0.0, f32::max)
+            })
+            .fold(0.0, f32::max);
+        assert!(farthest > 1.0);
+
+        for bone in BoneId::ALL {
+            assert_eq!(prepared.position(bone), pose.get_position(bone));
+        }
+        assert_eq!(
+            prepared.world_rotation(BoneId::LeftWrist),
+            Quat::from_rotation_x(0.3) * Quat::from_rotation_y(-1.0)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_end_effector_trajectory_straight_line() {
+        // Root slides along +X and back, carrying the right wrist in a straight line
+        let json = r#"{
+            "n": "slide", "d": 1.0,
+            "kf": [
+                { "t": 0.0, "p": { "rp": [0.0, 1.0, 0.0] } },
+                { "t": 0.5, "p": { "rp": [0.6, 1.0, 0.0] } }
+            ]
+        }"#;
+        let clip = RotationAnimationClip::from_json(json).unwrap();
+        let points = clip.end_effector_trajectory(BoneId::RightWrist, 16);
+        assert_eq!(points.len(), 16);
+
+        let start = points[0];
+        let far = points[8];
+        assert!(start.distance(far) > 0.5);
+        let direction = (far - start).normalize();
+        for point in &points {
+            let offset = *point - start;
+            let off_line = (offset - direction * offset.dot(direction)).length();
+            assert!(
+                off_line < 1e-4,
+                "Point {} is {} off the line",
+                point,
+                off_line
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_floor_constraint_follows_bone_radius() {
+        // Sink the pose so the floor constraint has to lift it
+        let pose = RotationPose::bind_pose()
+            .with_root_position(Vec3::new(0.0, 0.2, 0.0))
+            .apply_floor_constraint_with_radius(0.03);
+        let thick = pose.clone().apply_floor_constraint_with_radius(0.08);
+
+        let lowest = |pose: &RotationPose| {
+            BoneId::ALL
+                .iter()
+                .map(|&bone| pose.get_position(bone).y)
+                .fold(f32::MAX, f32::min)
+        };
+        assert!((lowest(&pose) - 0.03).abs() < 1e-5);
+        assert!((lowest(&thick) - 0.08).abs() < 1e-5);
+        assert!((thick.root_position.y - pose.root_position.y - 0.05).abs() < 1e-5);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rebind_to_a_pose_lowers_rest_arms() {
+        let arm_down = 40f32.to_radians();
+        let swing = Quat::from_rotation_y(0.6);
+        let clip = RotationAnimationClip {
+            name: "a-pose".to_string(),
+            duration: 1.0,
+            keyframes: vec![
+                RotationKeyframe {
+                    time: 0.0,
+                    pose: RotationPose::bind_pose(),
+                    tcb: None,
+                },
+                RotationKeyframe {
+                    time: 0.5,
+                    pose: RotationPose::bind_pose().with_rotation(BoneId::LeftShoulder, swing),
+                    tcb: None,
+                },
+            ],
+        };
+        let rebound = clip.rebind_to_a_pose(arm_down);
+
+        // Identity arms now hang at the A-pose angle, straight and on both sides
+        let rest = rebound.sample(0.0);
+        let arm = |shoulder: BoneId, wrist: BoneId| {
+            rest.get_position(wrist) - rest.get_position(shoulder)
+        };
+        let left = arm(BoneId::LeftShoulder, BoneId::LeftWrist);
+        let expected = glam::Vec3::new(arm_down.cos(), -arm_down.sin(), 0.0);
+        assert!(left.normalize().abs_diff_eq(expected, 1e-4), "{}", left);
+        let reach = [BoneId::LeftElbow, BoneId::LeftWrist]
+            .map(|bone| rest.bone_lengths[bone.index()])
+            .iter()
+            .sum::<f32>();
+        assert!((left.length() - reach).abs() < 1e-4);
+        let right = arm(BoneId::RightShoulder, BoneId::RightWrist);
+        let right_drop = (-right.y / right.length()).asin();
+        assert!((right_drop - arm_down).abs() < 0.1, "{}", right_drop);
+
+        // Authored rotations apply on top of the A-pose; other bones are untouched
+        let swung = rebound.sample(0.5);
+        let shoulder = swung.get_position(BoneId::LeftShoulder);
+        let swung_arm = swung.get_position(BoneId::LeftWrist) - shoulder;
+        assert!(swung_arm.abs_diff_eq(swing * left, 1e-4));
+        for bone in BoneId::ALL {
+            if !matches!(bone, BoneId::LeftShoulder | BoneId::RightShoulder) {
+                assert_eq!(
+                    rest.local_rotations[bone.index()],
+                    clip.sample(0.0).local_rotations[bone.index()]
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_retarget_longer_legs() {
+        let json = r#"{
+            "n": "lunge", "d": 1.0,
+            "kf": [
+                { "t": 0.0, "p": { "lk": { "x": 30.0, "y": 0.0, "z": 0.0 }, "le": { "x": 0.0, "y": -45.0, "z": 0.0 } } },
+                { "t": 0.5, "p": { "rk": { "x": 40.0, "y": 0.0, "z": 0.0 }, "re": { "x": 0.0, "y": 60.0, "z": 0.0 } } }
+            ]
+        }"#;
+        let clip = RotationAnimationClip::from_json(json).unwrap();
+        let mut lengths = rest_bone_lengths();
+        for bone in [
+            BoneId::LeftKnee,
+            BoneId::RightKnee,
+            BoneId::LeftAnkle,
+            BoneId::RightAnkle,
+        ] {
+            lengths[bone.index()] *= 1.25;
+        }
+        let long_legs = clip.retarget(&lengths);
+
+        for t in [0.0, 0.25, 0.5, 0.8] {
+            let original = clip.sample(t);
+            let retargeted = long_legs.sample(t);
+            assert_eq!(retargeted.bone_lengths, lengths);
+            for bone in [BoneId::LeftShoulder, BoneId::LeftElbow, BoneId::RightElbow] {
+                assert_eq!(
+                    retargeted.local_rotations[bone.index()],
+                    original.local_rotations[bone.index()]
+                );
+            }
+
+            // Feet move with the longer legs, and the root is raised to compensate
+            let foot_shift = retargeted
+                .get_position(BoneId::LeftFoot)
+                .distance(original.get_position(BoneId::LeftFoot));
+            assert!(foot_shift > 0.01, "Foot did not move at t = {}", t);
+            assert!(retargeted.root_position.y > original.root_position.y);
+        }
+
+        // Keyframes keep their lowest joint at the original height (grounded)
+        let lowest = |pose: &RotationPose| {
+            BoneId::ALL
+                .iter()
+                .map(|&bone| pose.get_position(bone).y)
+                .fold(f32::MAX, f32::min)
+        };
+        for (original, retargeted) in clip.keyframes.iter().zip(&long_legs.keyframes) {
+            assert!((lowest(&retargeted.pose) - lowest(&original.pose)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_constraint_clip_reaches_interpolated_targets() {
+        let effectors = [
+            BoneId::Pelvis,
+            BoneId::LeftWrist,
+            BoneId::RightWrist,
+            BoneId::LeftFoot,
+            BoneId::RightFoot,
+        ];
+        let targets_of = |pose: &RotationPose| -> Vec<(BoneId, Vec3)> {
+            effectors
+                .iter()
+                .map(|&bone| (bone, pose.get_position(bone)))
+                .collect()
+        };
+        let start = RotationPose::bind_pose();
+        let end = RotationPose::bind_pose()
+            .with_root_position(Vec3::new(0.1, -0.2, 0.0))
+            .with_rotation(BoneId::LeftShoulder, Quat::from_rotation_z(-1.0))
+            .with_rotation(BoneId::RightElbow, Quat::from_rotation_y(-1.2))
+            .with_rotation(BoneId::LeftHip, Quat::from_rotation_x(0.6))
+            .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(-0.9));
+        let clip = ConstraintClip {
+            name: "squat".to_string(),
+            duration: 2.0,
+            keyframes: vec![
+                ConstraintKeyframe {
+                    time: 0.0,
+                    targets: targets_of(&start),
+                },
+                ConstraintKeyframe {
+                    time: 1.0,
+                    targets: targets_of(&end),
+                },
+            ],
+        };
+
+        for time in [0.0, 0.25, 0.5, 1.0] {
+            let pose = clip.sample(time);
+            for &bone in &effectors {
+                let expected = start.get_position(bone).lerp(end.get_position(bone), time);
+                let error = pose.get_position(bone).distance(expected);
+                assert!(error < 0.01, "{:?} at t={}: off by {}", bone, time, error);
+            }
+        }
+
+        // JSON round trip keeps the targets; unknown bone names are rejected
+        let reparsed = ConstraintClip::from_json(&clip.to_json_string().unwrap()).unwrap();
+        assert_eq!(reparsed.keyframes.len(), 2);
+        let mut sorted = clip.keyframes[1].targets.clone();
+        sorted.sort_by_key(|&(bone, _)| bone.index());
+        let mut reparsed_targets = reparsed.keyframes[1].targets.clone();
+        reparsed_targets.sort_by_key(|&(bone, _)| bone.index());
+        assert_eq!(reparsed_targets, sorted);
+        let bad = r#"{ "n": "x", "d": 1.0, "ck": [{ "t": 0.0, "tg": { "Tail": [0, 0, 0] } }] }"#;
+        assert!(ConstraintClip::from_json(bad).is_err());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_sanitize_replaces_non_finite_values() {
+        let mut pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftElbow, Quat::from_xyzw(f32::NAN, 0.0, 0.0, 1.0))
+            .with_root_position(Vec3::new(0.5, f32::INFINITY, 0.25));
+        assert!(!pose.is_finite());
+
+        pose.sanitize();
+        assert!(pose.is_finite());
+        assert_eq!(
+            pose.local_rotations[BoneId::LeftElbow.index()],
+            Quat::IDENTITY
+        );
+        assert_eq!(pose.root_position, Vec3::new(0.5, 0.0, 0.25));
+        // FK is recomputed from the repaired values
+        assert!(pose.get_position(BoneId::LeftWrist).is_finite());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_shoulder_corrective_for_raised_arm() {
+        let rest = RotationPose::bind_pose();
+        assert_eq!(rest.pose_correctives(), rest.local_rotations);
+
+        // Left arm points along +X: -Z lowers it to the side, +Z raises it overhead
+        let hanging = rest.clone().with_rotation(
+            BoneId::LeftShoulder,
+            Quat::from_rotation_z(-90f32.to_radians()),
+        );
+        assert_eq!(hanging.pose_correctives(), hanging.local_rotations);
+
+        let raised = rest.with_rotation(
+            BoneId::LeftShoulder,
+            Quat::from_rotation_z(150f32.to_radians()),
+        );
+        let corrected = raised.pose_correctives();
+        let collar = corrected[BoneId::LeftCollar.index()];
+        assert!(collar.angle_between(Quat::IDENTITY) > 0.01);
+        // The other arm is at rest and untouched
+        assert_eq!(corrected[BoneId::RightCollar.index()], Quat::IDENTITY);
+
+        // FK moves the shoulder joint with the tilted stub
+        let prepared = raised.prepared();
+        let def = &BONE_HIERARCHY[BoneId::LeftShoulder.index()];
+        let uncorrected = prepared.position(BoneId::LeftCollar)
+            + prepared.world_rotation(BoneId::LeftCollar)
+                * (def.direction.normalize() * def.length);
+        assert!(
+            prepared
+                .position(BoneId::LeftShoulder)
+                .distance(uncorrected)
+                > 1e-3
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_world_rotations_accumulate_down_the_chain() {
+        let bind = RotationPose::bind_pose();
+        assert!(bind
+            .world_rotations_array()
+            .iter()
+            .all(|&q| q == Quat::IDENTITY));
+
+        let bend = Quat::from_rotation_x(0.4);
+        let pose = bind.with_rotation(BoneId::Spine2, bend);
+        let world = pose.world_rotations_array();
+        assert!(world[BoneId::Head.index()].abs_diff_eq(bend, 1e-6));
+        assert_eq!(world[BoneId::Spine1.index()], Quat::IDENTITY);
+        assert_eq!(world[BoneId::LeftKnee.index()], Quat::IDENTITY);
+
+        let clip = RotationAnimationClip {
+            name: "bend".to_string(),
+            duration: 1.0,
+            keyframes: vec![RotationKeyframe {
+                time: 0.0,
+                pose,
+                tcb: None,
+            }],
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&clip.to_world_rotation_json().unwrap()).unwrap();
+        let head = &json["kf"][0]["wr"]["head"];
+        for (i, value) in bend.to_array().into_iter().enumerate() {
+            assert!((head[i].as_f64().unwrap() as f32 - value).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ik_reaches_target() {
+        let chain = [BoneId::LeftElbow, BoneId::LeftWrist];
+        let pose = RotationPose::bind_pose();
+        let target = pose.get_position(BoneId::LeftWrist) + Vec3::new(-0.1, 0.15, 0.05);
+
+        let pose = pose.apply_ik(&chain, target);
+        let reached = pose.get_position(BoneId::LeftWrist);
+        assert!(
+            reached.distance(target) < 2.0 * RotationPose::IK_TOLERANCE,
+            "Wrist at {:?}, target {:?}",
+            reached,
+            target
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_floor_constraint_planted() {
+        use crate::skeleton::BONE_RADIUS;
+
+        // Crouched on the left leg, right knee raised, torso folded so the
+        // hanging right arm reaches below the planted foot
+        let pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftHip, Quat::from_rotation_x(0.8))
+            .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(-1.5))
+            .with_rotation(BoneId::RightHip, Quat::from_rotation_x(-1.5))
+            .with_rotation(BoneId::Spine1, Quat::from_rotation_x(-2.5))
+            .with_rotation(BoneId::RightCollar, Quat::from_rotation_z(-1.5));
+        let foot_before = pose.get_position(BoneId::LeftFoot);
+        assert!(pose.get_position(BoneId::RightWrist).y < foot_before.y);
+
+        let pose = pose.apply_floor_constraint_planted(BoneId::LeftFoot);
+        let foot = pose.get_position(BoneId::LeftFoot);
+        let hand = pose.get_position(BoneId::RightWrist);
+        assert!(
+            (foot.y - BONE_RADIUS).abs() < 0.005,
+            "Planted foot should stay on the floor, got {}",
+            foot.y
+        );
+        assert!(
+            (foot.x - foot_before.x).abs() < 0.005 && (foot.z - foot_before.z).abs() < 0.005,
+            "Planted foot should not slide"
+        );
+        assert!(
+            hand.y >= BONE_RADIUS - 1e-4,
+            "Hand should be lifted above the floor, got {}",
+            hand.y
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_dirty_mask() {
+        let pose = RotationPose::bind_pose();
+        pose.compute_all();
+        assert_eq!(pose.dirty_mask(), 0);
+
+        let pose = pose.with_rotation(BoneId::Spine1, Quat::from_rotation_x(0.5));
+        let mask = pose.dirty_mask();
+        let bit = |bone: BoneId| 1u32 << bone.index();
+
+        assert_ne!(mask & bit(BoneId::Spine1), 0);
+        assert_ne!(mask & bit(BoneId::Head), 0);
+        for leg in [BoneId::LeftHip, BoneId::RightKnee, BoneId::LeftFoot] {
+            assert_eq!(mask & bit(leg), 0, "{:?} should stay clean", leg);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_normalize_phase() {
+        let pose_at = |angle: f32| {
+            RotationPose::bind_pose()
+                .with_rotation(BoneId::LeftHip, Quat::from_rotation_x(angle))
+                .with_rotation(BoneId::RightHip, Quat::from_rotation_x(-angle))
+        };
+        let clip = RotationAnimationClip {
+            name: "walk".to_string(),
+            duration: 1.0,
+            keyframes: [(0.0, 0.0), (0.25, 0.6), (0.5, 0.0), (0.75, -0.6)]
+                .into_iter()
+                .map(|(time, angle)| RotationKeyframe {
+                    time,
+                    pose: pose_at(angle),
+                    tcb: None,
+                })
+                .collect(),
+        };
+
+        let foot_down = 0.4;
+        let aligned = clip.normalize_phase(foot_down);
+        assert_eq!(aligned.duration, clip.duration);
+        assert_eq!(aligned.keyframes[0].time, 0.0);
+        assert!(aligned.keyframes.windows(2).all(|w| w[0].time < w[1].time));
+
+        // Phase 0 is the old foot-down pose, and the rest of the cycle follows it
+        for t in [0.0, 0.1, 0.35, 0.7, 0.95] {
+            let expected = clip.sample(t + foot_down);
+            let actual = aligned.sample(t);
+            for bone in [BoneId::LeftHip, BoneId::RightHip] {
+                assert!(
+                    actual.local_rotations[bone.index()]
+                        .abs_diff_eq(expected.local_rotations[bone.index()], 1e-4),
+                    "Mismatch at t = {}",
+                    t
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_limits_clamp() {
+        let limits = default_joint_limits();
+
+        // Knee hyperextension clamps to the flexion limit
+        let knee = limits[BoneId::LeftKnee.index()].clamp(EulerAngles {
+            x: -30.0,
+            y: 5.0,
+            z: 0.0,
+        });
+        assert_eq!((knee.x, knee.y), (0.0, 5.0));
+        let knee = limits[BoneId::RightKnee.index()].clamp(EulerAngles {
+            x: 170.0,
+            ..Default::default()
+        });
+        assert_eq!(knee.x, 150.0);
+
+        // Unconstrained bones pass through
+        let euler = EulerAngles {
+            x: -120.0,
+            y: 80.0,
+            z: 45.0,
+        };
+        let spine = limits[BoneId::Spine1.index()].clamp(euler);
+        assert_eq!((spine.x, spine.y, spine.z), (euler.x, euler.y, euler.z));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_ik_neck_chain_degenerate_targets() {
+        let pose = RotationPose::bind_pose();
+        let chain = [BoneId::Neck, BoneId::Head];
+        let spine3 = pose.get_position(BoneId::Spine3);
+        let neck = pose.get_position(BoneId::Neck);
+
+        // Targets at the chain base and at an inner joint collapse segments to zero
+        for target in [spine3, neck, pose.get_position(BoneId::Head)] {
+            let solved = pose.clone().apply_ik(&chain, target);
+            assert!(solved
+                .local_rotations
+                .iter()
+                .all(|q| q.is_finite() && q.is_normalized()));
+            let matrices = solved.compute_bone_matrices();
+            assert!(matrices.iter().all(|m| m.is_finite()));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_loop_blend_eases_into_first_pose() {
+        let spine = |degrees: f32| {
+            RotationPose::bind_pose()
+                .with_rotation(BoneId::Spine1, Quat::from_rotation_x(degrees.to_radians()))
+        };
+        let clip = RotationAnimationClip {
+            name: "loop".to_string(),
+            duration: 1.0,
+            keyframes: [(0.0, 0.0), (0.5, 20.0), (1.0, 40.0)]
+                .into_iter()
+                .map(|(time, degrees)| RotationKeyframe {
+                    time,
+                    pose: spine(degrees),
+                    tcb: None,
+                })
+                .collect(),
+        };
+        let angle = |pose: &RotationPose| {
+            pose.local_rotations[BoneId::Spine1.index()]
+                .angle_between(Quat::IDENTITY)
+                .to_degrees()
+        };
+
+        // Just before the end the raw sample is almost the last pose (40 deg)
+        let raw = angle(&clip.sample(0.99));
+        let blended = angle(&clip.sample_loop_blended(0.99, 0.25));
+        assert!((raw - 39.6).abs() < 0.01, "{}", raw);
+        assert!(blended > 0.0 && blended < 5.0, "{}", blended);
+        // Halfway through the window it is half of each
+        let half = angle(&clip.sample_loop_blended(0.875, 0.25));
+        assert!(
+            (half - 0.5 * angle(&clip.sample(0.875))).abs() < 0.01,
+            "{}",
+            half
+        );
+
+        // Outside the window, and with no blend, sampling is unchanged
+        for (time, blend_time) in [(0.7, 0.25), (0.99, 0.0)] {
+            let pose = clip.sample_loop_blended(time, blend_time);
+            assert_eq!(angle(&pose), angle(&clip.sample(time)));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_raycast_hits_nearest_bone() {
+        use crate::skeleton::HEAD_RADIUS;
+
+        let pose = RotationPose::bind_pose().with_root_position(Vec3::new(1.0, 1.0, 0.0));
+        let head = pose.get_position(BoneId::Head);
+        let (bone, distance) = pose
+            .raycast(head + Vec3::Z * 5.0, Vec3::NEG_Z * 3.0)
+            .unwrap();
+        assert_eq!(bone, BoneId::Head);
+        assert!(
+            (distance - (5.0 - HEAD_RADIUS)).abs() < 1e-4,
+            "{}",
+            distance
+        );
+
+        // Mid-forearm, from the side of the arm (the bind pose is a T-pose)
+        let forearm = pose
+            .get_position(BoneId::LeftElbow)
+            .lerp(pose.get_position(BoneId::LeftWrist), 0.5);
+        let hit = pose.raycast(forearm + Vec3::Y * 2.0, Vec3::NEG_Y);
+        assert_eq!(hit.map(|(bone, _)| bone), Some(BoneId::LeftWrist));
+
+        // Past the skeleton, pointing away from it, and with no direction
+        assert_eq!(pose.raycast(head + Vec3::X * 3.0, Vec3::Z), None);
+        assert_eq!(pose.raycast(head + Vec3::Z * 5.0, Vec3::Z), None);
+        assert_eq!(pose.raycast(head, Vec3::ZERO), None);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bounding_capsule() {
+        let contains = |(a, b, radius): (Vec3, Vec3, f32), point: Vec3| {
+            let ab = b - a;
+            let t = ((point - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0);
+            point.distance(a + ab * t) <= radius + 1e-5
+        };
+
+        let bind = RotationPose::bind_pose();
+        let capsule = bind.bounding_capsule();
+        for bone in BoneId::ALL {
+            assert!(contains(capsule, bind.get_position(bone)), "{:?}", bone);
+        }
+        assert!(contains(capsule, bind.get_position(BoneId::Head)));
+        assert!(contains(capsule, bind.root_position));
+
+        // The bind pose is a T-pose; compare a spread-eagle against arms at the sides
+        let arms_down = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftShoulder, Quat::from_rotation_z(-1.4))
+            .with_rotation(BoneId::RightShoulder, Quat::from_rotation_z(1.4));
+        let spread = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftHip, Quat::from_rotation_z(0.5))
+            .with_rotation(BoneId::RightHip, Quat::from_rotation_z(-0.5));
+        let spread_capsule = spread.bounding_capsule();
+        assert!(spread_capsule.2 > arms_down.bounding_capsule().2 + 0.1);
+        for bone in BoneId::ALL {
+            assert!(contains(spread_capsule, spread.get_position(bone)));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fk_batch_matches_pose_positions() {
+        let poses: Vec<RotationPose> = (0..5)
+            .map(|i| {
+                let angle = i as f32 * 0.4;
+                RotationPose::bind_pose()
+                    .with_rotation(BoneId::Pelvis, Quat::from_rotation_y(angle))
+                    .with_rotation(BoneId::Spine2, Quat::from_rotation_x(-angle))
+                    .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(angle * 1.5))
+                    .with_rotation(BoneId::RightElbow, Quat::from_rotation_z(angle))
+                    .with_root_position(Vec3::new(angle, 1.0, -angle))
+            })
+            .collect();
+        let rotations: Vec<_> = poses.iter().map(|pose| pose.local_rotations).collect();
+        let roots: Vec<_> = poses.iter().map(|pose| pose.root_position).collect();
+        let mut positions = vec![[Vec3::ZERO; BoneId::COUNT]; poses.len()];
+
+        compute_fk_batch(&rotations, &roots, &mut positions);
+
+        for (pose, batch) in poses.iter().zip(&positions) {
+            for bone in BoneId::ALL {
+                assert!(
+                    batch[bone.index()].abs_diff_eq(pose.get_position(bone), 1e-5),
+                    "{:?}",
+                    bone
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_skeleton_definition_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&export_skeleton_definition_json()).expect("valid JSON");
+        let bones = json["bones"].as_array().expect("bones array");
+        assert_eq!(bones.len(), BoneId::COUNT);
+
+        for bone in BoneId::ALL {
+            let entry = &bones[bone.index()];
+            assert_eq!(entry["name"], bone.name());
+            let parent = BONE_HIERARCHY[bone.index()].parent;
+            match parent {
+                Some(p) => assert_eq!(entry["parent"], p.index()),
+                None => assert!(entry["parent"].is_null()),
+            }
+        }
+        assert_eq!(
+            bones[BoneId::LeftKnee.index()]["parent"],
+            BoneId::LeftHip.index()
+        );
+        assert_eq!(bones[BoneId::Head.index()]["parent"], BoneId::Neck.index());
+
+        // Root and hip offsets reproduce the bind pose hip joints
+        let pose = RotationPose::bind_pose();
+        let vec = |value: &serde_json::Value| {
+            let v: Vec<f32> = serde_json::from_value(value.clone()).expect("vec3");
+            Vec3::from_slice(&v)
+        };
+        let root = vec(&json["root_position"]);
+        assert!(root.abs_diff_eq(pose.get_position(BoneId::Pelvis), 1e-5));
+        let left_hip = root + vec(&json["left_hip_offset"]);
+        assert!(left_hip.abs_diff_eq(pose.get_position(BoneId::LeftHip), 1e-5));
+    }
 }
@@ -5,6 +5,172 @@ use crate::EPSILON;
 use glam::{Quat, Vec3, Vec3A};
 use std::cell::RefCell;
 
+/// How strongly wind sways each bone, relative to the wrists (unlisted bones stay still)
+/// Rotations cascade down the chain, so extremities also inherit their parents' sway.
+const WIND_SWAY: [(BoneId, f32); 10] = [
+    (BoneId::Spine2, 0.05),
+    (BoneId::Spine3, 0.1),
+    (BoneId::Neck, 0.3),
+    (BoneId::Head, 0.5),
+    (BoneId::LeftShoulder, 0.4),
+    (BoneId::RightShoulder, 0.4),
+    (BoneId::LeftElbow, 0.7),
+    (BoneId::RightElbow, 0.7),
+    (BoneId::LeftWrist, 1.0),
+    (BoneId::RightWrist, 1.0),
+];
+/// Peak wind rotation of a fully swaying bone at strength 1, in degrees
+const WIND_MAX_ANGLE: f32 = 8.0;
+/// Base frequency of wind gusts, in Hz
+const WIND_GUST_HZ: f32 = 0.4;
+
+/// Pose-space correctives: (stub owner, shoulder, elbow) per arm
+/// The collar owns the rigid stub ending at the shoulder joint; tilting it toward
+/// a raised upper arm keeps the stub from folding into the arm cylinder. Hip
+/// stubs hang off the pelvis, shared with the spine, so they get no corrective.
+const SHOULDER_CORRECTIVES: [(BoneId, BoneId, BoneId); 2] = [
+    (BoneId::LeftCollar, BoneId::LeftShoulder, BoneId::LeftElbow),
+    (
+        BoneId::RightCollar,
+        BoneId::RightShoulder,
+        BoneId::RightElbow,
+    ),
+];
+/// Upper arm elevation (degrees above horizontal in the torso frame) where the
+/// corrective starts, and where it peaks (straight up)
+const CORRECTIVE_START: f32 = 15.0;
+const CORRECTIVE_FULL: f32 = 90.0;
+/// Fraction of the way the stub turns toward the upper arm at full raise
+const CORRECTIVE_MAX_TILT: f32 = 0.3;
+
+/// Corrective tilt of the stub ending at `bone`'s joint (identity for other bones)
+///
+/// Only depends on the bone's own local rotation: as the upper arm rises above
+/// horizontal, the stub turns part of the way toward it. FK applies this to the
+/// stub vector, so every joint query sees the corrected shoulder.
+pub(crate) fn stub_corrective(bone: BoneId, local_rotation: Quat) -> Quat {
+    let Some(&(_, _, elbow)) = SHOULDER_CORRECTIVES
+        .iter()
+        .find(|(_, shoulder, _)| *shoulder == bone)
+    else {
+        return Quat::IDENTITY;
+    };
+    // Stub and upper arm directions in the collar's frame
+    let stub = BONE_HIERARCHY[bone.index()].direction.normalize();
+    let arm = local_rotation * BONE_HIERARCHY[elbow.index()].direction.normalize();
+
+    let elevation = arm.angle_between(Vec3::NEG_Y).to_degrees() - 90.0;
+    let t = ((elevation - CORRECTIVE_START) / (CORRECTIVE_FULL - CORRECTIVE_START)).clamp(0.0, 1.0);
+    if t == 0.0 {
+        return Quat::IDENTITY;
+    }
+    let weight = CORRECTIVE_MAX_TILT * t * t * (3.0 - 2.0 * t);
+    Quat::IDENTITY.slerp(Quat::from_rotation_arc(stub, arm), weight)
+}
+
+/// Smooth deterministic noise in [-1, 1]: a sum of incommensurate sines
+fn wind_noise(t: f32) -> f32 {
+    let t = t * std::f32::consts::TAU;
+    (t.sin() + 0.5 * (t * 2.31 + 1.7).sin() + 0.25 * (t * 5.17 + 4.1).sin()) / 1.75
+}
+
+/// Left/right counterpart of a bone (center bones map to themselves)
+pub fn mirrored_bone(bone: BoneId) -> BoneId {
+    match bone {
+        BoneId::LeftHip => BoneId::RightHip,
+        BoneId::RightHip => BoneId::LeftHip,
+        BoneId::LeftKnee => BoneId::RightKnee,
+        BoneId::RightKnee => BoneId::LeftKnee,
+        BoneId::LeftAnkle => BoneId::RightAnkle,
+        BoneId::RightAnkle => BoneId::LeftAnkle,
+        BoneId::LeftFoot => BoneId::RightFoot,
+        BoneId::RightFoot => BoneId::LeftFoot,
+        BoneId::LeftCollar => BoneId::RightCollar,
+        BoneId::RightCollar => BoneId::LeftCollar,
+        BoneId::LeftShoulder => BoneId::RightShoulder,
+        BoneId::RightShoulder => BoneId::LeftShoulder,
+        BoneId::LeftElbow => BoneId::RightElbow,
+        BoneId::RightElbow => BoneId::LeftElbow,
+        BoneId::LeftWrist => BoneId::RightWrist,
+        BoneId::RightWrist => BoneId::LeftWrist,
+        center => center,
+    }
+}
+
+/// IK chain ending at `planted`, stopping below the bones attached to the root
+/// so re-solving it never moves the pelvis
+fn planted_chain(planted: BoneId) -> Vec<BoneId> {
+    let mut chain = Vec::new();
+    let mut bone = planted;
+    while let Some(parent) = BONE_HIERARCHY[bone.index()].parent {
+        if BONE_HIERARCHY[parent.index()].parent.is_none() {
+            break;
+        }
+        chain.push(bone);
+        bone = parent;
+    }
+    chain.reverse();
+    chain
+}
+
+/// Nearest distance along a unit ray to a sphere, None if it misses
+/// A ray starting inside hits where it leaves; hits behind the origin don't count.
+fn ray_sphere(origin: Vec3, dir: Vec3, center: Vec3, radius: f32) -> Option<f32> {
+    let oc = origin - center;
+    let b = oc.dot(dir);
+    let h = b * b - (oc.length_squared() - radius * radius);
+    if h < 0.0 {
+        return None;
+    }
+    let h = h.sqrt();
+    [-b - h, -b + h].into_iter().find(|&t| t >= 0.0)
+}
+
+/// Nearest distance along a unit ray to the capsule around segment `a`-`b`
+/// The body is an infinite cylinder cut to the segment, capped by spheres at the ends.
+fn ray_capsule(origin: Vec3, dir: Vec3, a: Vec3, b: Vec3, radius: f32) -> Option<f32> {
+    let caps = [a, b]
+        .into_iter()
+        .filter_map(|center| ray_sphere(origin, dir, center, radius));
+
+    let axis = b - a;
+    let axis_len2 = axis.length_squared();
+    let oa = origin - a;
+    let (axis_dir, axis_oa) = (axis.dot(dir), axis.dot(oa));
+    // Quadratic in t for the distance to the axis line, scaled by |axis|^2
+    let qa = axis_len2 - axis_dir * axis_dir;
+    let qb = axis_len2 * oa.dot(dir) - axis_oa * axis_dir;
+    let qc = axis_len2 * oa.length_squared() - axis_oa * axis_oa - radius * radius * axis_len2;
+    let h = qb * qb - qa * qc;
+    let body = (qa > EPSILON && h >= 0.0)
+        .then(|| {
+            let h = h.sqrt();
+            [(-qb - h) / qa, (-qb + h) / qa]
+                .into_iter()
+                .filter(|&t| t >= 0.0)
+                .find(|&t| (0.0..=axis_len2).contains(&(axis_oa + t * axis_dir)))
+        })
+        .flatten();
+
+    body.into_iter().chain(caps).min_by(f32::total_cmp)
+}
+
+/// Per-bone difference between two poses (see `RotationPose::diff`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoseDiff {
+    /// Angle between local rotations per bone, in degrees
+    pub bone_angles: [f32; BoneId::COUNT],
+    /// Root position change (other - self)
+    pub root_delta: Vec3,
+}
+
+impl PoseDiff {
+    /// Largest per-bone angle change, in degrees
+    pub fn max_angle(&self) -> f32 {
+        self.bone_angles.iter().copied().fold(0.0, f32::max)
+    }
+}
+
 /// Rotation-based pose for animation.
 ///
 /// Each bone stores a local rotation (relative to parent).
@@ -20,10 +186,43 @@ pub struct RotationPose {
     /// Local rotation for each bone (relative to parent)
     pub local_rotations: [Quat; BoneId::COUNT],
 
+    /// Length of the segment ending at each bone (`BONE_HIERARCHY` lengths by default)
+    pub bone_lengths: [f32; BoneId::COUNT],
+
     /// Cache for derived world transforms
     pub cache: RefCell<PoseCache>,
 }
 
+/// Rest segment lengths from `BONE_HIERARCHY`
+pub fn rest_bone_lengths() -> [f32; BoneId::COUNT] {
+    BONE_HIERARCHY.map(|def| def.length)
+}
+
+/// A pose with forward kinematics fully computed
+///
+/// Plain data with no interior mutability: the getters are infallible `&self`
+/// reads that can be freely interleaved, unlike `RotationPose::get_position`,
+/// which fills the `RefCell` cache on demand.
+#[derive(Debug, Clone)]
+pub struct PreparedPose {
+    pub root_position: Vec3,
+    pub local_rotations: [Quat; BoneId::COUNT],
+    world_positions: [Vec3A; BoneId::COUNT],
+    world_rotations: [Quat; BoneId::COUNT],
+}
+
+impl PreparedPose {
+    /// World position of a bone's end joint
+    pub fn position(&self, bone: BoneId) -> Vec3 {
+        Vec3::from(self.world_positions[bone.index()])
+    }
+
+    /// World rotation of a bone
+    pub fn world_rotation(&self, bone: BoneId) -> Quat {
+        self.world_rotations[bone.index()]
+    }
+}
+
 impl Default for RotationPose {
     fn default() -> Self {
         Self::bind_pose()
@@ -38,6 +237,7 @@ impl RotationPose {
         Self {
             root_position,
             local_rotations: [Quat::IDENTITY; BoneId::COUNT],
+            bone_lengths: rest_bone_lengths(),
             cache: RefCell::new(PoseCache::default()),
         }
     }
@@ -64,6 +264,25 @@ impl RotationPose {
         new_pose
     }
 
+    /// Return a new pose turned by `facing` about the root (Functional Set)
+    /// The pelvis is the root bone and has no length, so pre-multiplying its
+    /// rotation turns the whole body in place; bone lengths and heights are kept.
+    pub fn with_facing(self, facing: Quat) -> Self {
+        let pelvis = self.local_rotations[BoneId::Pelvis.index()];
+        self.with_rotation(BoneId::Pelvis, (facing * pelvis).normalize())
+    }
+
+    /// Return a new pose with overridden segment lengths (Functional Set)
+    /// Rotations are kept, so the same motion plays on different proportions.
+    pub fn with_bone_lengths(self, lengths: [f32; BoneId::COUNT]) -> Self {
+        let mut new_pose = self;
+        if new_pose.bone_lengths != lengths {
+            new_pose.bone_lengths = lengths;
+            new_pose.cache.borrow_mut().dirty = DirtyFlags::all_dirty();
+        }
+        new_pose
+    }
+
     /// Mark all bones as needing recomputation
     pub fn with_all_dirty(self) -> Self {
         let new_pose = self;
@@ -71,6 +290,36 @@ impl RotationPose {
         new_pose
     }
 
+    /// True if the root and all rotations are finite (no NaN/Inf)
+    pub fn is_finite(&self) -> bool {
+        self.root_position.is_finite() && self.local_rotations.iter().all(|q| q.is_finite())
+    }
+
+    /// Replace non-finite rotations with identity and non-finite root coordinates with 0
+    ///
+    /// Degenerate input (a zero-length drag direction, an unreachable IK target)
+    /// can produce NaNs that would otherwise spread through FK into the bone
+    /// matrix buffer.
+    pub fn sanitize(&mut self) {
+        let mut dirty = self.cache.borrow().dirty;
+        for bone in BoneId::ALL {
+            let rotation = &mut self.local_rotations[bone.index()];
+            if !rotation.is_finite() {
+                *rotation = Quat::IDENTITY;
+                dirty = dirty.with_marked_dirty(bone);
+            }
+        }
+        if !self.root_position.is_finite() {
+            let root = self
+                .root_position
+                .to_array()
+                .map(|c| if c.is_finite() { c } else { 0.0 });
+            self.root_position = Vec3::from_array(root);
+            dirty = DirtyFlags::all_dirty();
+        }
+        self.cache.borrow_mut().dirty = dirty;
+    }
+
     /// Get world position of a bone's end joint (computes FK if needed)
     pub fn get_position(&self, bone: BoneId) -> Vec3 {
         self.ensure_computed(bone);
@@ -115,8 +364,10 @@ impl RotationPose {
 
         // World rotation = parent rotation * local rotation
         let world_rot = parent_rot * local_rot;
-        // World position = parent position + rotated bone vector
-        let bone_vector = parent_rot * (def.direction.normalize() * def.length);
+        // World position = parent position + rotated bone vector (stub correctives included)
+        let bone_vector = parent_rot
+            * (stub_corrective(bone, local_rot)
+                * (def.direction.normalize() * self.bone_lengths[bone.index()]));
         let world_pos = parent_pos + bone_vector;
 
         cache.world_rotations[bone.index()] = world_rot;
@@ -124,6 +375,28 @@ impl RotationPose {
         cache.dirty = cache.dirty.with_cleared(bone);
     }
 
+    /// World rotation of every bone, in bone index order (computes FK if needed)
+    pub fn world_rotations_array(&self) -> [Quat; BoneId::COUNT] {
+        self.prepared().world_rotations
+    }
+
+    /// Compute all FK once and take a snapshot with plain `&self` getters
+    pub fn prepared(&self) -> PreparedPose {
+        self.compute_all();
+        let cache = self.cache.borrow();
+        PreparedPose {
+            root_position: self.root_position,
+            local_rotations: self.local_rotations,
+            world_positions: cache.world_positions,
+            world_rotations: cache.world_rotations,
+        }
+    }
+
+    /// Bitset of bones whose world transforms are stale (bit i = bone index i)
+    pub fn dirty_mask(&self) -> u32 {
+        self.cache.borrow().dirty.bits()
+    }
+
     /// Force recomputation of all bones (useful after bulk updates)
     pub fn compute_all(&self) {
         for bone in BoneId::ALL {
@@ -132,170 +405,237 @@ impl RotationPose {
         self.cache.borrow_mut().dirty = DirtyFlags::cleared();
     }
 
+    /// Floor constraint for the default `BONE_RADIUS`
     pub fn apply_floor_constraint(self) -> Self {
+        self.apply_floor_constraint_with_radius(crate::skeleton::BONE_RADIUS)
+    }
+
+    /// Lift the pose so no joint is closer than `bone_radius` to the floor
+    /// Use the configured `RadiiConfig::bone` so thicker limbs don't clip.
+    pub fn apply_floor_constraint_with_radius(self, bone_radius: f32) -> Self {
+        let min_y = self.lowest_joint_y();
+        let mut new_pose = self;
+        if min_y < bone_radius {
+            new_pose.root_position.y += bone_radius - min_y;
+            new_pose = new_pose.with_all_dirty();
+            // Ensure consistency immediately
+            new_pose.compute_all();
+        }
+        new_pose.sanitize();
+        new_pose
+    }
+
+    /// Height of the lowest joint (or the root, if lower)
+    fn lowest_joint_y(&self) -> f32 {
         // Need to compute to check positions
         if self.cache.borrow().dirty.is_any_dirty() {
             self.compute_all();
         }
+
+        let cache = self.cache.borrow();
+        cache
+            .world_positions
+            .iter()
+            .fold(self.root_position.y, |min_y, position| {
+                min_y.min(position.y)
+            })
+    }
+
+    /// Floor penetration for the default `BONE_RADIUS`
+    pub fn floor_penetration(&self) -> f32 {
+        self.floor_penetration_with_radius(crate::skeleton::BONE_RADIUS)
+    }
+
+    /// How far the floor constraint would lift the pose (0 if it is clear)
+    /// Detection only: the lowest joint's depth below `bone_radius`.
+    pub fn floor_penetration_with_radius(&self, bone_radius: f32) -> f32 {
+        (bone_radius - self.lowest_joint_y()).max(0.0)
+    }
+
+    /// Ground the pose around a planted joint instead of lifting it bodily
+    ///
+    /// The pose is moved so `planted` rests on the floor. If any other joint still
+    /// dips below the floor, the root is raised by the difference and the planted
+    /// leg is re-solved with IK so the joint stays where it was; a leg too short
+    /// for the lift ends as close to its plant as it can reach.
+    pub fn apply_floor_constraint_planted(self, planted: BoneId) -> Self {
         use crate::skeleton::BONE_RADIUS;
 
-        let mut min_y = self.root_position.y;
-        {
-            let cache = self.cache.borrow();
-            for i in 0..BoneId::COUNT {
-                min_y = min_y.min(cache.world_positions[i].y);
-            }
+        // Rest the planted joint on the floor
+        let drop = BONE_RADIUS - self.get_position(planted).y;
+        let root = self.root_position + Vec3::Y * drop;
+        let pose = self.with_root_position(root);
+        let anchor = pose.get_position(planted);
+
+        // Lowest joint outside the planted chain (IK re-solves the chain)
+        let chain = planted_chain(planted);
+        let min_y = BoneId::ALL
+            .iter()
+            .filter(|bone| !chain.contains(bone))
+            .map(|&bone| pose.get_position(bone).y)
+            .fold(root.y, f32::min);
+        if min_y >= BONE_RADIUS {
+            return pose;
         }
 
-        let mut new_pose = self;
-        if min_y < BONE_RADIUS {
-            new_pose.root_position.y += BONE_RADIUS - min_y;
-            new_pose = new_pose.with_all_dirty();
-            // Ensure consistency immediately
-            new_pose.compute_all();
+        let lifted = pose.with_root_position(root + Vec3::Y * (BONE_RADIUS - min_y));
+        lifted.apply_ik(&chain, anchor)
+    }
+
+    /// Local rotations with the corrective shoulder rotations folded in
+    ///
+    /// FK applies `stub_corrective` itself; this is for FK that doesn't (the GPU
+    /// skinning pass). The collar tilts by the corrective and the shoulder is
+    /// counter-rotated, so the upper arm keeps its world direction. At rest these
+    /// are the pose's own rotations.
+    pub fn pose_correctives(&self) -> [Quat; BoneId::COUNT] {
+        let mut rotations = self.local_rotations;
+        for (collar, shoulder, _) in SHOULDER_CORRECTIVES {
+            let corrective = stub_corrective(shoulder, self.local_rotations[shoulder.index()]);
+            if corrective == Quat::IDENTITY {
+                continue;
+            }
+            rotations[collar.index()] = (rotations[collar.index()] * corrective).normalize();
+            rotations[shoulder.index()] =
+                (corrective.inverse() * rotations[shoulder.index()]).normalize();
         }
-        new_pose
+        rotations
+    }
+
+    /// Bone matrices for this pose, computed from scratch
+    ///
+    /// Same result as `compute_bone_matrices`, without the shared `bind_data`
+    /// cache: a pure function of the pose for native tests and external renderers.
+    pub fn to_bone_matrices(&self) -> [glam::Mat4; crate::skeleton::RENDER_BONE_COUNT] {
+        crate::skeleton::BindData::compute().bone_matrices(&self.prepared().world_positions)
     }
 
     /// Compute all bone matrices for skinning
     /// Returns [Mat4; RENDER_BONE_COUNT]
     pub fn compute_bone_matrices(&self) -> [glam::Mat4; crate::skeleton::RENDER_BONE_COUNT] {
-        self.compute_all();
-        let cache = self.cache.borrow();
+        debug_assert!(self.is_finite(), "non-finite pose reached the GPU path");
+        let prepared = self.prepared();
+        // Cylinders then the head sphere, in generate_bind_pose_mesh order
+        crate::skeleton::bind_data().bone_matrices(&prepared.world_positions)
+    }
 
-        use crate::skeleton::{compute_aligned_matrix, compute_offset_matrix, RENDER_BONE_COUNT};
-        use crate::skeleton_constants::*;
+    /// Mirror the pose across the YZ plane (x -> -x)
+    ///
+    /// Left and right bones swap rotations, and each rotation is reflected:
+    /// for a mirror across YZ the quaternion (x, y, z, w) becomes (x, -y, -z, w).
+    pub fn mirror_x(&self) -> RotationPose {
+        let mut local_rotations = [Quat::IDENTITY; BoneId::COUNT];
+        for bone in BoneId::ALL {
+            let q = self.local_rotations[mirrored_bone(bone).index()];
+            local_rotations[bone.index()] = Quat::from_xyzw(q.x, -q.y, -q.z, q.w);
+        }
 
-        let mut matrices = [glam::Mat4::IDENTITY; RENDER_BONE_COUNT];
+        let root = self.root_position;
+        Self {
+            root_position: Vec3::new(-root.x, root.y, root.z),
+            local_rotations,
+            bone_lengths: BoneId::ALL.map(|bone| self.bone_lengths[mirrored_bone(bone).index()]),
+            cache: RefCell::new(PoseCache::default()),
+        }
+    }
 
-        // Helper macro to get pos
-        macro_rules! pos {
-            ($id:expr) => {
-                cache.world_positions[$id.index()]
-            };
+    /// Spread a roll evenly along a chain, each bone twisting about its own axis
+    /// `total_roll` is in radians. See `distribute_twist_weighted`.
+    pub fn distribute_twist(self, chain: &[BoneId], total_roll: f32) -> Self {
+        let weights = vec![1.0 / chain.len() as f32; chain.len()];
+        self.distribute_twist_weighted(chain, &weights, total_roll)
+    }
+
+    /// Split a roll across a chain, e.g. 30% upper arm and 70% forearm
+    ///
+    /// Bone `i` gets `weights[i] * total_roll` (radians) about its rest direction,
+    /// applied on top of its current local rotation. Weights are used as given,
+    /// so they should normally sum to 1. Extra bones or weights are ignored.
+    pub fn distribute_twist_weighted(
+        self,
+        chain: &[BoneId],
+        weights: &[f32],
+        total_roll: f32,
+    ) -> Self {
+        let mut new_pose = self;
+        for (&bone, &weight) in chain.iter().zip(weights) {
+            let axis = BONE_HIERARCHY[bone.index()].direction.normalize();
+            let roll = Quat::from_axis_angle(axis, weight * total_roll);
+            let rotation = (new_pose.local_rotations[bone.index()] * roll).normalize();
+            new_pose = new_pose.with_rotation(bone, rotation);
         }
+        new_pose
+    }
 
-        // Current positions
-        let pelvis = pos!(BoneId::Pelvis); // Root, should be close to root_position
-        let spine1 = pos!(BoneId::Spine1);
-        let spine2 = pos!(BoneId::Spine2);
-        let spine3 = pos!(BoneId::Spine3);
-        let neck = pos!(BoneId::Neck);
-        let head = pos!(BoneId::Head);
-
-        let l_collar = pos!(BoneId::LeftCollar);
-        let l_shoulder = pos!(BoneId::LeftShoulder);
-        let l_elbow = pos!(BoneId::LeftElbow);
-        let l_wrist = pos!(BoneId::LeftWrist);
-
-        let r_collar = pos!(BoneId::RightCollar);
-        let r_shoulder = pos!(BoneId::RightShoulder);
-        let r_elbow = pos!(BoneId::RightElbow);
-        let r_wrist = pos!(BoneId::RightWrist);
-
-        let l_hip = pos!(BoneId::LeftHip);
-        let l_knee = pos!(BoneId::LeftKnee);
-        let l_ankle = pos!(BoneId::LeftAnkle);
-        let l_foot = pos!(BoneId::LeftFoot);
-
-        let r_hip = pos!(BoneId::RightHip);
-        let r_knee = pos!(BoneId::RightKnee);
-        let r_ankle = pos!(BoneId::RightAnkle);
-        let r_foot = pos!(BoneId::RightFoot);
-
-        // Cylinders - Must match generate_bind_pose_mesh order in skeleton.rs
-        let mut idx = 0;
-
-        // Spine Chain
-        matrices[idx] = compute_aligned_matrix(DEFAULT_PELVIS, DEFAULT_SPINE1, pelvis, spine1);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(DEFAULT_SPINE1, DEFAULT_SPINE2, spine1, spine2);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(DEFAULT_SPINE2, DEFAULT_SPINE3, spine2, spine3);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(DEFAULT_SPINE3, DEFAULT_NECK, spine3, neck);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(DEFAULT_NECK, DEFAULT_HEAD, neck, head);
-        idx += 1;
-
-        // Left Arm Chain
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_SPINE3, DEFAULT_LEFT_COLLAR, spine3, l_collar);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(
-            DEFAULT_LEFT_COLLAR,
-            DEFAULT_LEFT_SHOULDER,
-            l_collar,
-            l_shoulder,
-        );
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(
-            DEFAULT_LEFT_SHOULDER,
-            DEFAULT_LEFT_ELBOW,
-            l_shoulder,
-            l_elbow,
-        );
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_LEFT_ELBOW, DEFAULT_LEFT_WRIST, l_elbow, l_wrist);
-        idx += 1;
-
-        // Right Arm Chain
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_SPINE3, DEFAULT_RIGHT_COLLAR, spine3, r_collar);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(
-            DEFAULT_RIGHT_COLLAR,
-            DEFAULT_RIGHT_SHOULDER,
-            r_collar,
-            r_shoulder,
-        );
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(
-            DEFAULT_RIGHT_SHOULDER,
-            DEFAULT_RIGHT_ELBOW,
-            r_shoulder,
-            r_elbow,
-        );
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_RIGHT_ELBOW, DEFAULT_RIGHT_WRIST, r_elbow, r_wrist);
-        idx += 1;
-
-        // Left Leg Chain
-        matrices[idx] = compute_aligned_matrix(DEFAULT_PELVIS, DEFAULT_LEFT_HIP, pelvis, l_hip);
-        idx += 1;
-        matrices[idx] = compute_aligned_matrix(DEFAULT_LEFT_HIP, DEFAULT_LEFT_KNEE, l_hip, l_knee);
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_LEFT_KNEE, DEFAULT_LEFT_ANKLE, l_knee, l_ankle);
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_LEFT_ANKLE, DEFAULT_LEFT_FOOT, l_ankle, l_foot);
-        idx += 1;
-
-        // Right Leg Chain
-        matrices[idx] = compute_aligned_matrix(DEFAULT_PELVIS, DEFAULT_RIGHT_HIP, pelvis, r_hip);
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_RIGHT_HIP, DEFAULT_RIGHT_KNEE, r_hip, r_knee);
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_RIGHT_KNEE, DEFAULT_RIGHT_ANKLE, r_knee, r_ankle);
-        idx += 1;
-        matrices[idx] =
-            compute_aligned_matrix(DEFAULT_RIGHT_ANKLE, DEFAULT_RIGHT_FOOT, r_ankle, r_foot);
-        idx += 1;
-
-        // Head Sphere
-        matrices[idx] = compute_offset_matrix(DEFAULT_HEAD, head);
-
-        matrices
+    /// Compare local rotations bone by bone
+    ///
+    /// Angles are sign-independent (`q` and `-q` compare as equal) and only
+    /// reflect each bone's own rotation, not changes inherited from parents.
+    pub fn diff(&self, other: &RotationPose) -> PoseDiff {
+        let mut bone_angles = [0.0; BoneId::COUNT];
+        for (angle, (a, b)) in bone_angles
+            .iter_mut()
+            .zip(self.local_rotations.iter().zip(&other.local_rotations))
+        {
+            *angle = a.angle_between(*b).to_degrees();
+        }
+
+        PoseDiff {
+            bone_angles,
+            root_delta: other.root_position - self.root_position,
+        }
+    }
+
+    /// Angle (degrees) between each bone's world orientation here and in `target`
+    /// World rather than local, so a wrong shoulder also counts against the arm below it.
+    pub fn world_angle_errors(&self, target: &RotationPose) -> [f32; BoneId::COUNT] {
+        let (ours, theirs) = (self.world_rotations_array(), target.world_rotations_array());
+        std::array::from_fn(|i| ours[i].angle_between(theirs[i]).to_degrees())
+    }
+
+    /// Form score against `target`: 1 for a match, falling linearly to 0 as the
+    /// mean world bone angle error reaches `SIMILARITY_TOLERANCE`
+    pub fn similarity(&self, target: &RotationPose) -> f32 {
+        self.similarity_weighted(target, &[1.0; BoneId::COUNT])
+    }
+
+    /// `similarity` with per-bone importance weights (need not sum to 1)
+    /// All-zero weights score 1, since nothing is being compared.
+    pub fn similarity_weighted(
+        &self,
+        target: &RotationPose,
+        weights: &[f32; BoneId::COUNT],
+    ) -> f32 {
+        let total: f32 = weights.iter().sum();
+        if total <= EPSILON {
+            return 1.0;
+        }
+        let errors = self.world_angle_errors(target);
+        let mean: f32 = errors.iter().zip(weights).map(|(e, w)| e * w).sum::<f32>() / total;
+        (1.0 - mean / Self::SIMILARITY_TOLERANCE).clamp(0.0, 1.0)
+    }
+
+    /// Bone whose world orientation deviates most from `target` (for UI hints)
+    /// Ties, including identical poses, go to the lowest bone index.
+    pub fn worst_bone(&self, target: &RotationPose) -> BoneId {
+        let errors = self.world_angle_errors(target);
+        BoneId::ALL
+            .into_iter()
+            .zip(errors)
+            .fold((BoneId::Pelvis, 0.0), |worst, (bone, error)| {
+                if error > worst.1 {
+                    (bone, error)
+                } else {
+                    worst
+                }
+            })
+            .0
     }
 
     /// Interpolate between two poses using spherical linear interpolation (slerp)
     pub fn lerp(a: &RotationPose, b: &RotationPose, t: f32) -> RotationPose {
         let mut result = RotationPose::bind_pose();
+        result.bone_lengths = a.bone_lengths;
 
         // Lerp root position
         result.root_position = a.root_position.lerp(b.root_position, t);
@@ -319,6 +659,151 @@ impl RotationPose {
         result
     }
 
+    /// Sway the spine, head and arms in a light breeze
+    ///
+    /// A pure function of `time` (seconds): each bone bends downwind about the
+    /// horizontal axis perpendicular to `direction`, by an angle that gusts with
+    /// smooth noise. `strength` 1 gives up to `WIND_MAX_ANGLE` on the wrists;
+    /// 0 (or a vertical/zero direction) leaves the pose unchanged.
+    pub fn apply_wind(self, time: f32, strength: f32, direction: Vec3) -> Self {
+        let Some(axis) = Vec3::Y.cross(direction).try_normalize() else {
+            return self;
+        };
+        if strength <= 0.0 {
+            return self;
+        }
+
+        // Bend in the original pose's frames so earlier bones don't skew later axes
+        self.compute_all();
+        let world_rotations = self.cache.borrow().world_rotations;
+        let mut pose = self;
+        for (i, (bone, sway)) in WIND_SWAY.into_iter().enumerate() {
+            // Mostly downwind, fluttering with a per-bone phase
+            let gust = 0.6 + 0.4 * wind_noise(time * WIND_GUST_HZ + i as f32 * 0.37);
+            let angle = (strength * sway * gust * WIND_MAX_ANGLE).to_radians();
+            let world = world_rotations[bone.index()];
+            let delta = world.inverse() * Quat::from_axis_angle(axis, angle) * world;
+            let rotation = (pose.local_rotations[bone.index()] * delta).normalize();
+            pose = pose.with_rotation(bone, rotation);
+        }
+        pose
+    }
+
+    /// Capsule enclosing the skeleton: (axis start, axis end, radius)
+    ///
+    /// The axis runs along the spine (pelvis to head) and is stretched to cover
+    /// every joint's projection; the radius is the farthest joint from that line
+    /// plus `HEAD_RADIUS`, so rendered geometry fits too. Cheaper overlap tests
+    /// than the joint AABB for broad-phase collision.
+    pub fn bounding_capsule(&self) -> (Vec3, Vec3, f32) {
+        let prepared = self.prepared();
+        let origin = prepared.position(BoneId::Pelvis);
+        let axis = (prepared.position(BoneId::Head) - origin)
+            .try_normalize()
+            .unwrap_or(Vec3::Y);
+
+        let (mut min_t, mut max_t, mut radius) = (0.0f32, 0.0f32, 0.0f32);
+        for bone in BoneId::ALL {
+            let offset = prepared.position(bone) - origin;
+            let t = offset.dot(axis);
+            min_t = min_t.min(t);
+            max_t = max_t.max(t);
+            radius = radius.max((offset - axis * t).length());
+        }
+        (
+            origin + axis * min_t,
+            origin + axis * max_t,
+            radius + crate::skeleton::HEAD_RADIUS,
+        )
+    }
+
+    /// Nearest bone hit by a ray from `origin` along `dir`: (bone, distance)
+    ///
+    /// Each rendered segment is a capsule of `BONE_RADIUS` owned by the bone it
+    /// ends at, and the head adds a sphere of `HEAD_RADIUS`, matching the mesh
+    /// at its default radii. `dir` need not be normalized; the distance is in
+    /// world units. None if the ray misses or `dir` is zero.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<(BoneId, f32)> {
+        use crate::skeleton::{BONE_RADIUS, HEAD_RADIUS, RENDER_SEGMENTS};
+
+        let dir = dir.try_normalize()?;
+        let prepared = self.prepared();
+        let head = ray_sphere(origin, dir, prepared.position(BoneId::Head), HEAD_RADIUS)
+            .map(|t| (BoneId::Head, t));
+        RENDER_SEGMENTS
+            .iter()
+            .filter_map(|&(start, end)| {
+                let (a, b) = (prepared.position(start), prepared.position(end));
+                ray_capsule(origin, dir, a, b, BONE_RADIUS).map(|t| (end, t))
+            })
+            .chain(head)
+            .min_by(|x, y| x.1.total_cmp(&y.1))
+    }
+
+    /// Sphere enclosing `bounding_capsule`, for frustum culling: (center, radius)
+    pub fn bounding_sphere(&self) -> (Vec3, f32) {
+        let (start, end, radius) = self.bounding_capsule();
+        ((start + end) * 0.5, start.distance(end) * 0.5 + radius)
+    }
+
+    /// Sphere an end effector can reach by dragging: (chain base, total length)
+    ///
+    /// Uses the editor's drag chain (`crate::editor::drag_chain`); the base is the
+    /// joint the chain pivots on, exactly as in `apply_ik`. A shoulder pivot shifts
+    /// a little with the stub corrective as the arm rises.
+    pub fn reach_extent(&self, end_effector: BoneId) -> (Vec3, f32) {
+        let chain = crate::editor::drag_chain(end_effector);
+        let center = match BONE_HIERARCHY[chain[0].index()].parent {
+            Some(parent) => self.get_position(parent),
+            None => self.root_position,
+        };
+        let radius = chain
+            .iter()
+            .map(|bone| self.bone_lengths[bone.index()])
+            .sum();
+        (center, radius)
+    }
+
+    /// Weighted average of several poses (weights need not sum to 1)
+    ///
+    /// Rotations are blended as a normalized weighted quaternion sum, each aligned
+    /// to the first pose's hemisphere: exact for a single non-zero weight and close
+    /// to slerp for nearby poses. Returns the bind pose if the weights sum to zero.
+    pub fn weighted_average(poses: &[(&RotationPose, f32)]) -> RotationPose {
+        let total: f32 = poses.iter().map(|(_, weight)| weight).sum();
+        let mut result = RotationPose::bind_pose();
+        let Some((first, _)) = poses.first() else {
+            return result;
+        };
+        if total <= EPSILON {
+            return result;
+        }
+
+        result.root_position = Vec3::ZERO;
+        result.bone_lengths = first.bone_lengths;
+        let mut sums = [glam::Vec4::ZERO; BoneId::COUNT];
+        for (pose, weight) in poses {
+            let weight = weight / total;
+            result.root_position += pose.root_position * weight;
+            for (i, sum) in sums.iter_mut().enumerate() {
+                let mut q = pose.local_rotations[i];
+                if q.dot(first.local_rotations[i]) < 0.0 {
+                    q = -q;
+                }
+                *sum += glam::Vec4::from(q) * weight;
+            }
+        }
+        for (rotation, sum) in result.local_rotations.iter_mut().zip(sums) {
+            *rotation = Quat::from_vec4(sum).normalize();
+        }
+
+        result.cache.borrow_mut().dirty = DirtyFlags::all_dirty();
+        result
+    }
+
+    /// Mean world bone angle error (degrees) at which `similarity` reaches 0
+    pub const SIMILARITY_TOLERANCE: f32 = 45.0;
+
     pub const IK_ITERATIONS: usize = 10;
     pub const IK_TOLERANCE: f32 = 0.001;
 
@@ -329,6 +814,19 @@ impl RotationPose {
     /// * `chain` - List of bone IDs in the chain (parent to child/end-effector)
     /// * `target` - Target world position for the end effector
     pub fn apply_ik(self, chain: &[BoneId], target: Vec3) -> Self {
+        self.apply_ik_weighted(chain, &[], 0.0, target)
+    }
+
+    /// `apply_ik` where `stiffness[i]` (0..=1) makes the segment ending at
+    /// `chain[i]` resist turning and `damping` (0..1) slows each iteration;
+    /// see `crate::ik::solve_fabrik_weighted`
+    pub fn apply_ik_weighted(
+        self,
+        chain: &[BoneId],
+        stiffness: &[f32],
+        damping: f32,
+        target: Vec3,
+    ) -> Self {
         if chain.is_empty() {
             return self;
         }
@@ -349,49 +847,59 @@ impl RotationPose {
 
         for &bone in chain {
             joints.push(self.get_position(bone));
-            lengths.push(BONE_HIERARCHY[bone.index()].length);
+            lengths.push(self.bone_lengths[bone.index()]);
         }
 
         // 2. Solve IK (FABRIK)
-        let solved_joints = crate::ik::solve_fabrik(
+        let solved_joints = crate::ik::solve_fabrik_weighted(
             joints,
             &lengths,
+            stiffness,
+            damping,
             target,
             Self::IK_ITERATIONS,
             Self::IK_TOLERANCE,
         );
 
         // 3. Update local rotations
-        let mut current_parent_rot = if let Some(parent) = BONE_HIERARCHY[start_bone.index()].parent
-        {
-            self.ensure_computed(parent);
-            self.cache.borrow().world_rotations[parent.index()]
-        } else {
-            Quat::IDENTITY
-        };
-
+        // A segment ending at `bone` is oriented by its parent's world rotation,
+        // so each segment is aimed by rotating the bone that owns it.
         let mut new_pose = self;
         for (i, &bone) in chain.iter().enumerate() {
             let def = &BONE_HIERARCHY[bone.index()];
+            let Some(owner) = def.parent else {
+                continue; // Root segment has no length
+            };
 
-            let start_pos = solved_joints[i];
-            let end_pos = solved_joints[i + 1];
-            let target_vec = end_pos - start_pos;
-
+            // Zero-length (solved or rest) segments have no direction to aim
+            let target_vec = solved_joints[i + 1] - solved_joints[i];
             if target_vec.length_squared() < EPSILON {
                 continue;
             }
+            let (Some(target_dir), Some(rest_dir)) =
+                (target_vec.try_normalize(), def.direction.try_normalize())
+            else {
+                continue;
+            };
 
-            let target_dir_local = current_parent_rot.inverse() * target_vec.normalize();
-            let default_dir = def.direction.normalize();
+            new_pose.ensure_computed(owner);
+            let (owner_world, owner_parent_world) = {
+                let cache = new_pose.cache.borrow();
+                let parent_world = BONE_HIERARCHY[owner.index()]
+                    .parent
+                    .map_or(Quat::IDENTITY, |p| cache.world_rotations[p.index()]);
+                (cache.world_rotations[owner.index()], parent_world)
+            };
 
-            let delta_rot = Quat::from_rotation_arc(default_dir, target_dir_local);
+            // Smallest world-space rotation that aims the segment, keeping its twist
+            let current_dir = owner_world * rest_dir;
+            let delta_rot = Quat::from_rotation_arc(current_dir, target_dir);
+            let local_rot = owner_parent_world.inverse() * (delta_rot * owner_world);
 
-            new_pose = new_pose.with_rotation(bone, delta_rot.normalize());
-            new_pose.compute_bone(bone);
-            current_parent_rot = new_pose.cache.borrow().world_rotations[bone.index()];
+            new_pose = new_pose.with_rotation(owner, local_rot.normalize());
         }
 
+        new_pose.sanitize();
         new_pose
     }
 }
@@ -1,4 +1,4 @@
-use glam::{Mat4, Quat, Vec3};
+use glam::{Mat4, Quat, Vec3, Vec4};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
@@ -11,6 +11,13 @@ const MAX_UP_DOT: f32 = 0.98; // Don't allow looking straight down
 const MIN_DISTANCE: f32 = 1.5; // Minimum zoom (closest)
 const MAX_DISTANCE: f32 = 10.0; // Maximum zoom (farthest)
 
+/// Vertical field of view of the perspective projection
+pub const FOV_Y: f32 = std::f32::consts::FRAC_PI_4; // 45 degrees
+
+/// Default clip plane distances
+pub const DEFAULT_NEAR: f32 = 0.1;
+pub const DEFAULT_FAR: f32 = 100.0;
+
 /// Target point for orbit camera (center of stickman)
 pub const CAMERA_TARGET: Vec3 = Vec3::new(0.0, 0.5, 0.0);
 
@@ -22,6 +29,8 @@ pub struct Camera {
     pub orientation: Quat,
     /// Distance from target point
     pub distance: f32,
+    /// Auto-orbit rate around world up in radians per second (0 = off)
+    pub turntable_speed: f32,
 }
 
 impl Default for Camera {
@@ -36,6 +45,7 @@ impl Default for Camera {
         Self {
             orientation: (yaw_quat * pitch_quat).normalize(),
             distance: 4.0,
+            turntable_speed: 0.0,
         }
     }
 }
@@ -46,6 +56,7 @@ impl Camera {
         Self {
             orientation,
             distance,
+            turntable_speed: 0.0,
         }
     }
 
@@ -101,6 +112,32 @@ impl Camera {
         }
     }
 
+    /// Advance the turntable orbit by `delta` seconds
+    ///
+    /// Rotates about world up, so the elevation (and its clamp) is unchanged.
+    pub fn with_turntable(self, delta: f32) -> Camera {
+        if self.turntable_speed == 0.0 {
+            return self;
+        }
+        self.with_rotation(Vec3::Y, self.turntable_speed * delta)
+    }
+
+    /// Camera back at the default orientation and distance
+    ///
+    /// Keeps the turntable setting, which is a mode rather than part of the view.
+    pub fn with_default_view(self) -> Camera {
+        Camera {
+            turntable_speed: self.turntable_speed,
+            ..Camera::default()
+        }
+    }
+
+    /// Horizontal angle of the eye around the target, in radians
+    pub fn azimuth(&self) -> f32 {
+        let dir = self.orientation * Vec3::Z;
+        dir.x.atan2(dir.z)
+    }
+
     /// Compute camera eye position
     pub fn eye_position(&self) -> Vec3 {
         let offset = self.orientation * Vec3::new(0.0, 0.0, self.distance);
@@ -132,6 +169,147 @@ impl Camera {
     }
 }
 
+/// Oscillation rate of camera shake, in Hz
+const SHAKE_FREQUENCY: f32 = 18.0;
+
+/// Short, decaying camera wobble (e.g. "rep complete" feedback)
+///
+/// The offset is a deterministic function of elapsed time, so a shake replays
+/// identically for the same inputs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CameraShake {
+    /// Peak angular offset in radians
+    pub amplitude: f32,
+    /// Oscillation rate in Hz
+    pub frequency: f32,
+    /// Time until the shake has fully decayed, in seconds
+    pub duration: f32,
+    /// Time since the shake started, in seconds
+    pub elapsed: f32,
+}
+
+impl CameraShake {
+    /// Start a shake with the given peak angle (radians) and length (seconds)
+    pub fn new(amplitude: f32, duration: f32) -> Self {
+        Self {
+            amplitude,
+            frequency: SHAKE_FREQUENCY,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advance the shake by `delta` seconds
+    pub fn advance(self, delta: f32) -> Self {
+        Self {
+            elapsed: self.elapsed + delta.max(0.0),
+            ..self
+        }
+    }
+
+    /// Check if the shake still moves the camera
+    pub fn is_active(&self) -> bool {
+        self.elapsed < self.duration && self.amplitude != 0.0
+    }
+
+    /// Current (yaw, pitch) offset in radians
+    ///
+    /// Two sums of incommensurate sines stand in for noise; a quadratic envelope
+    /// decays them to exactly zero at `duration`.
+    pub fn offset(&self) -> (f32, f32) {
+        if !self.is_active() {
+            return (0.0, 0.0);
+        }
+        let decay = (1.0 - self.elapsed / self.duration).powi(2);
+        let phase = std::f32::consts::TAU * self.frequency * self.elapsed;
+        let yaw = 0.6 * phase.sin() + 0.4 * (1.73 * phase + 1.3).sin();
+        let pitch = 0.6 * (1.21 * phase + 0.7).cos() + 0.4 * (2.37 * phase).sin();
+        (self.amplitude * decay * yaw, self.amplitude * decay * pitch)
+    }
+}
+
+impl Camera {
+    /// Camera with a shake offset applied to its orientation
+    pub fn with_shake(self, shake: &CameraShake) -> Camera {
+        let (yaw, pitch) = shake.offset();
+        Camera {
+            orientation: (self.orientation
+                * Quat::from_euler(glam::EulerRot::YXZ, yaw, pitch, 0.0))
+            .normalize(),
+            ..self
+        }
+    }
+}
+
+/// Check that clip planes satisfy `0 < near < far` (and are finite)
+pub fn valid_clip_planes(near: f32, far: f32) -> bool {
+    near > 0.0 && near < far && far.is_finite()
+}
+
+/// Compute the perspective projection matrix
+///
+/// Right-handed with WebGPU depth convention: the near plane maps to NDC z = 0
+/// and the far plane to z = 1.
+pub fn projection_matrix(aspect: f32, near: f32, far: f32) -> Mat4 {
+    Mat4::perspective_rh(FOV_Y, aspect, near, far)
+}
+
+/// Sphere enclosing all of `points`, grown by `margin` (e.g. a mesh radius)
+/// Centered on the points' bounding box. None when there are no points.
+pub fn bounding_sphere(
+    points: impl IntoIterator<Item = Vec3> + Clone,
+    margin: f32,
+) -> Option<(Vec3, f32)> {
+    let (min, max) =
+        points
+            .clone()
+            .into_iter()
+            .fold(None, |bounds: Option<(Vec3, Vec3)>, point| {
+                Some(bounds.map_or((point, point), |(min, max)| {
+                    (min.min(point), max.max(point))
+                }))
+            })?;
+    let center = (min + max) * 0.5;
+    let radius = points
+        .into_iter()
+        .map(|point| point.distance(center))
+        .fold(0.0, f32::max);
+    Some((center, radius + margin))
+}
+
+/// The six clip planes of a view-projection matrix, for culling
+///
+/// Planes point inward and are normalized, so `plane.dot(point.extend(1.0))` is
+/// the signed distance inside. Uses the WebGPU depth range (near at z = 0).
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    pub planes: [Vec4; 6],
+}
+
+impl Frustum {
+    /// Extract the planes from a view-projection matrix (Gribb-Hartmann)
+    pub fn from_view_projection(view_proj: Mat4) -> Self {
+        let row = |i: usize| view_proj.row(i);
+        let planes = [
+            row(3) + row(0), // left
+            row(3) - row(0), // right
+            row(3) + row(1), // bottom
+            row(3) - row(1), // top
+            row(2),          // near
+            row(3) - row(2), // far
+        ]
+        .map(|plane| plane / plane.truncate().length());
+        Self { planes }
+    }
+
+    /// Check if any part of a sphere may be inside the frustum
+    /// Conservative near the corners, where a sphere outside can still pass.
+    pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+        let point = center.extend(1.0);
+        self.planes.iter().all(|plane| plane.dot(point) >= -radius)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +376,144 @@ mod tests {
         let zoomed = camera.with_zoom(1.0);
         assert!(zoomed.distance < camera.distance);
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_reset_restores_default_view() {
+        let default = Camera::default();
+        let moved = Camera {
+            turntable_speed: 0.5,
+            ..default
+        }
+        .with_rotation(Vec3::Y, 1.2)
+        .with_rotation(default.right_axis(), 0.3)
+        .with_zoom(-2.0);
+        assert!(!moved.orientation.abs_diff_eq(default.orientation, 1e-3));
+        assert!(moved.distance != default.distance);
+
+        let reset = moved.with_default_view();
+        assert_eq!(reset.orientation, default.orientation);
+        assert_eq!(reset.distance, default.distance);
+        assert_eq!(reset.turntable_speed, 0.5);
+        assert!(reset.view_matrix().abs_diff_eq(default.view_matrix(), 1e-6));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_turntable_advances_azimuth() {
+        let camera = Camera::default();
+        assert_eq!(camera.with_turntable(1.0).orientation, camera.orientation);
+
+        let spinning = Camera {
+            turntable_speed: 90f32.to_radians(),
+            ..camera
+        };
+        let turned = spinning.with_turntable(1.0);
+        let delta = turned.azimuth() - camera.azimuth();
+        assert!(
+            (delta - std::f32::consts::FRAC_PI_2).abs() < 1e-4,
+            "Turned {}",
+            delta
+        );
+
+        // Same elevation, eye swung a quarter turn around the target
+        let (before, after) = (camera.eye_position(), turned.eye_position());
+        assert!((before.y - after.y).abs() < 1e-5);
+        let expected = CAMERA_TARGET + Quat::from_rotation_y(delta) * (before - CAMERA_TARGET);
+        assert!(after.abs_diff_eq(expected, 1e-4));
+        assert!(!turned.view_matrix().abs_diff_eq(camera.view_matrix(), 1e-3));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_camera_shake_decays() {
+        let shake = CameraShake::new(0.05, 0.4);
+        let camera = Camera::default();
+
+        let during = shake.advance(0.01);
+        assert!(during.is_active());
+        let (yaw, pitch) = during.offset();
+        assert!(yaw != 0.0 || pitch != 0.0);
+
+        let after = shake.advance(0.4);
+        assert!(!after.is_active());
+        assert_eq!(after.offset(), (0.0, 0.0));
+        assert_eq!(camera.with_shake(&after).orientation, camera.orientation);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_camera_shake_amplitude_scales_peak() {
+        let peak = |amplitude: f32| {
+            let shake = CameraShake::new(amplitude, 0.5);
+            (0..50)
+                .map(|i| {
+                    let (yaw, pitch) = shake.advance(i as f32 * 0.01).offset();
+                    yaw.abs().max(pitch.abs())
+                })
+                .fold(0.0, f32::max)
+        };
+
+        let small = peak(0.02);
+        let large = peak(0.04);
+        assert!(small > 0.0 && small <= 0.02);
+        assert!((large - 2.0 * small).abs() < 1e-6);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_frustum_culls_far_off_instances() {
+        let camera = Camera::default();
+        let view_proj =
+            projection_matrix(16.0 / 9.0, DEFAULT_NEAR, DEFAULT_FAR) * camera.view_matrix();
+        let frustum = Frustum::from_view_projection(view_proj);
+        let pose = crate::bone::RotationPose::bind_pose();
+
+        let (center, radius) = pose.bounding_sphere();
+        assert!(frustum.intersects_sphere(center, radius));
+
+        // Far to the side, behind the camera and beyond the far plane
+        let eye = camera.eye_position();
+        for offset in [
+            Vec3::new(50.0, 0.0, 0.0),
+            (eye - CAMERA_TARGET) * 3.0,
+            (CAMERA_TARGET - eye).normalize() * (DEFAULT_FAR + 10.0),
+        ] {
+            let moved = pose.clone().with_root_position(pose.root_position + offset);
+            let (center, radius) = moved.bounding_sphere();
+            assert!(!frustum.intersects_sphere(center, radius), "{}", offset);
+        }
+
+        // Partly on screen counts as visible
+        let (center, radius) = pose.bounding_sphere();
+        let edge = frustum.planes[0];
+        let inside = -edge.truncate() * (edge.dot(center.extend(1.0)) + radius * 0.5);
+        assert!(frustum.intersects_sphere(center + inside, radius));
+
+        assert_eq!(bounding_sphere(std::iter::empty(), 1.0), None);
+        let (center, radius) = bounding_sphere([Vec3::X, -Vec3::X], 0.5).unwrap();
+        assert_eq!((center, radius), (Vec3::ZERO, 1.5));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_projection_clip_planes() {
+        let (near, far) = (0.01, 500.0);
+        assert!(valid_clip_planes(near, far));
+        assert!(!valid_clip_planes(0.0, far));
+        assert!(!valid_clip_planes(far, near));
+        assert!(!valid_clip_planes(near, f32::INFINITY));
+
+        let projection = projection_matrix(16.0 / 9.0, near, far);
+        let near_ndc = projection.project_point3(Vec3::new(0.0, 0.0, -near));
+        let far_ndc = projection.project_point3(Vec3::new(0.0, 0.0, -far));
+        assert!(near_ndc.z.abs() < 1e-4, "Near plane z = {}", near_ndc.z);
+        assert!(
+            (far_ndc.z - 1.0).abs() < 1e-4,
+            "Far plane z = {}",
+            far_ndc.z
+        );
+    }
 }
 
 // App methods for camera control
@@ -217,9 +533,10 @@ impl App {
         let pitch_quat = Quat::from_rotation_x(elevation);
         let orientation = (yaw_quat * pitch_quat).normalize();
 
-        self.state.camera = Camera {
+        self.state.engine.camera = Camera {
             orientation,
             distance,
+            ..self.state.engine.camera
         };
     }
 
@@ -233,18 +550,77 @@ impl App {
     /// * `angle` - Rotation angle in radians
     pub fn rotate_camera(&mut self, axis_x: f32, axis_y: f32, axis_z: f32, angle: f32) {
         let axis = Vec3::new(axis_x, axis_y, axis_z);
-        self.state.camera = self.state.camera.with_rotation(axis, angle);
+        self.state.engine.camera = self.state.engine.camera.with_rotation(axis, angle);
+    }
+
+    /// Return the camera to its default orientation and distance
+    /// Pushes the new view to the GPU immediately.
+    pub fn reset_camera(&mut self) {
+        self.state.engine.camera = self.state.engine.camera.with_default_view();
+        self.sync_camera();
     }
 
     /// Get the camera's right axis (for vertical input rotation)
     pub fn get_camera_right_axis(&self) -> Vec<f32> {
-        self.state.camera.right_axis().to_array().to_vec()
+        self.state.engine.camera.right_axis().to_array().to_vec()
     }
 
     /// Zoom the camera by adjusting distance from target
     ///
     /// Positive delta = zoom in (closer), negative = zoom out (farther)
     pub fn zoom_camera(&mut self, delta: f32) {
-        self.state.camera = self.state.camera.with_zoom(delta);
+        self.state.engine.camera = self.state.engine.camera.with_zoom(delta);
+    }
+
+    /// Slowly orbit the camera on its own (turntable), in degrees per second
+    /// 0 disables it. Drive it with `update_turntable`.
+    pub fn set_turntable(&mut self, speed_deg_per_sec: f32) {
+        self.state.engine.camera.turntable_speed = speed_deg_per_sec.to_radians();
+    }
+
+    /// Advance the turntable orbit and push the new view to the GPU
+    /// Call each frame; does nothing while the turntable is off.
+    pub fn update_turntable(&mut self, delta_ms: f32) {
+        let camera = &mut self.state.engine.camera;
+        if camera.turntable_speed == 0.0 {
+            return;
+        }
+        *camera = camera.with_turntable(delta_ms / 1000.0);
+        self.sync_camera();
+    }
+
+    /// Start a camera shake (e.g. on rep complete)
+    /// `amplitude` is the peak angle in radians. Drive it with `update_camera_shake`.
+    pub fn shake_camera(&mut self, amplitude: f32, duration_ms: f32) {
+        self.state.engine.camera_shake = CameraShake::new(amplitude, duration_ms / 1000.0);
+    }
+
+    /// Advance the camera shake and push the shaken view to the GPU
+    /// Call each frame; does nothing once the shake has ended.
+    pub fn update_camera_shake(&mut self, delta_ms: f32) {
+        let was_active = self.state.engine.camera_shake.is_active();
+        self.state.engine.camera_shake = self.state.engine.camera_shake.advance(delta_ms / 1000.0);
+        if was_active {
+            // Also syncs once after the end to restore the unshaken view
+            self.sync_camera();
+        }
+    }
+
+    /// Set the near/far clip plane distances and rebuild the projection
+    /// Ignored unless 0 < near < far. The values are kept across resizes.
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        if !valid_clip_planes(near, far) {
+            return;
+        }
+        let gpu = &mut self.state.gpu;
+        gpu.near = near;
+        gpu.far = far;
+        gpu.uniforms.projection =
+            projection_matrix(gpu.uniforms.aspect, near, far).to_cols_array_2d();
+        gpu.queue.write_buffer(
+            &gpu.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[gpu.uniforms]),
+        );
     }
 }
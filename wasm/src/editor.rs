@@ -0,0 +1,2675 @@
+//! Keyframe editor sessions
+//!
+//! An `EditorSession` owns an editable copy of an animation clip together with
+//! the editor's cursor state (current keyframe, hovered joint).
+//! Sessions live in `AppState` and are addressed from JavaScript by `u32` handles.
+//! Handle 0 is never issued, so JavaScript can use it as "no session".
+
+use crate::animation::Easing;
+use crate::bone::{
+    constraint_chain, default_joint_limits, BoneId, EulerAngles, JointLimits,
+    RotationAnimationClip, RotationKeyframe, RotationPose, BONE_HIERARCHY,
+};
+use crate::math::{swing_twist, twist_angle};
+use crate::skeleton::RENDER_BONE_COUNT;
+use crate::EPSILON;
+use glam::{Mat4, Quat, Vec2, Vec3};
+use std::collections::HashMap;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Highlight color for the hovered joint (RGBA, alpha = tint strength)
+pub const HOVER_COLOR: [f32; 4] = [1.0, 0.6, 0.1, 0.8];
+
+/// Render bone index highlighted for each joint (indexed by `BoneId`).
+///
+/// A joint maps to the mesh segment that ends at it, so hovering the elbow
+/// lights up the upper arm. The pelvis maps to the lower spine segment that
+/// starts at it, and the head maps to the head sphere.
+/// Must match the primitive order in `generate_bind_pose_mesh`.
+pub const JOINT_RENDER_INDEX: [usize; BoneId::COUNT] = [
+    0,  // Pelvis -> Pelvis/Spine1 segment
+    13, // LeftHip
+    17, // RightHip
+    0,  // Spine1
+    14, // LeftKnee
+    18, // RightKnee
+    1,  // Spine2
+    15, // LeftAnkle
+    19, // RightAnkle
+    2,  // Spine3
+    16, // LeftFoot
+    20, // RightFoot
+    3,  // Neck
+    5,  // LeftCollar
+    9,  // RightCollar
+    21, // Head -> head sphere
+    6,  // LeftShoulder
+    10, // RightShoulder
+    7,  // LeftElbow
+    11, // RightElbow
+    8,  // LeftWrist
+    12, // RightWrist
+];
+
+/// Compute the per-bone tint colors for a hovered joint.
+/// `None` clears all highlights.
+pub fn hover_bone_colors(hovered: Option<BoneId>) -> [[f32; 4]; RENDER_BONE_COUNT] {
+    let mut colors = [[0.0; 4]; RENDER_BONE_COUNT];
+    if let Some(bone) = hovered {
+        colors[JOINT_RENDER_INDEX[bone.index()]] = HOVER_COLOR;
+    }
+    colors
+}
+
+/// Why an editor operation failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorError {
+    /// No open session has this handle
+    InvalidHandle,
+    /// A keyframe or bone index is out of range
+    IndexOutOfRange,
+    /// The session's clip has no current keyframe to edit
+    NoKeyframe,
+    /// An argument is outside its valid domain (axis, scale factor, ...)
+    InvalidArgument,
+    /// No pose with this name is in the pose library
+    UnknownPose,
+    /// The operation targets the selected bone, but none is selected
+    NoSelection,
+}
+
+impl std::fmt::Display for EditorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            EditorError::InvalidHandle => "invalid editor session handle",
+            EditorError::IndexOutOfRange => "index out of range",
+            EditorError::NoKeyframe => "no keyframe to edit",
+            EditorError::InvalidArgument => "invalid argument",
+            EditorError::UnknownPose => "unknown pose name",
+            EditorError::NoSelection => "no bone selected",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for EditorError {}
+
+/// Editable clip plus editor cursor state
+#[derive(Debug, Clone)]
+pub struct EditorSession {
+    /// Clip being edited (a copy, the animation library is not modified)
+    pub clip: RotationAnimationClip,
+    /// Keyframe currently shown and edited
+    pub keyframe_index: usize,
+    /// Joint under the cursor, highlighted in the renderer
+    pub hovered_joint: Option<BoneId>,
+    /// Bone targeted by the gizmo and bone edits given index -1
+    pub selected_bone: Option<BoneId>,
+    /// Render translucent ghosts of the neighboring keyframes
+    pub onion_skin: bool,
+    /// Per-bone Euler limits applied by `set_bone_rotation`
+    pub joint_limits: [JointLimits; BoneId::COUNT],
+    /// Active pose recording, if any
+    pub recording: Option<Recording>,
+    /// Fit the clip duration to the last keyframe after `delete_keyframe`
+    pub auto_fit_duration: bool,
+    /// How much the spine joins hand drags (0 = arm only, see `weighted_drag_chain`)
+    pub spine_ik_influence: f32,
+    /// Blend of each IK iteration toward the previous one during drags (0 = off)
+    pub ik_damping: f32,
+    /// Share of an FK rotation passed on to each parent in turn (0 = bone only)
+    pub drag_falloff: f32,
+    /// `step_keyframe` wraps around the clip ends instead of stopping at them
+    pub wrap_navigation: bool,
+}
+
+/// Root movement (world units) that counts as a change while recording
+const RECORD_ROOT_THRESHOLD: f32 = 0.01;
+
+/// Falloff weight below which parents are no longer rotated
+const DRAG_FALLOFF_MIN_WEIGHT: f32 = 0.01;
+
+/// Squared length below which a quaternion has no usable direction to normalize
+const MIN_QUAT_LENGTH_SQUARED: f32 = 1e-12;
+
+/// "Record while I pose" state: samples the live pose into a take
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Seconds between samples
+    pub interval: f32,
+    /// Smallest bone rotation change (degrees) that records a new keyframe
+    pub threshold: f32,
+    /// Time of the first tick (take time 0)
+    pub started_at: Option<f32>,
+    /// Time of the last sample
+    pub last_sample: f32,
+    /// Keyframes recorded so far, times relative to `started_at`
+    pub take: Vec<RotationKeyframe>,
+    /// Clip time the take is inserted at (the keyframe being posed)
+    pub clip_offset: f32,
+}
+
+impl Recording {
+    /// Start an empty take, to be inserted at clip time `clip_offset`
+    pub fn new(interval: f32, threshold: f32, clip_offset: f32) -> Self {
+        Self {
+            interval,
+            threshold,
+            started_at: None,
+            last_sample: 0.0,
+            take: Vec::new(),
+            clip_offset,
+        }
+    }
+
+    /// Append `pose` to the take as a keyframe at take-relative `time`
+    pub fn capture_keyframe(&mut self, time: f32, pose: &RotationPose) {
+        self.take.push(RotationKeyframe {
+            time,
+            pose: pose.clone(),
+            tcb: None,
+        });
+    }
+
+    /// Sample `pose` at absolute `time` if an interval has passed
+    /// A keyframe is only added when the pose moved past the threshold since the
+    /// last recorded one. Returns true if a keyframe was added.
+    pub fn tick(&mut self, time: f32, pose: &RotationPose) -> bool {
+        let Some(started_at) = self.started_at else {
+            self.started_at = Some(time);
+            self.last_sample = time;
+            self.capture_keyframe(0.0, pose);
+            return true;
+        };
+        if time - self.last_sample < self.interval {
+            return false;
+        }
+        self.last_sample = time;
+
+        let changed = self.take.last().is_none_or(|last| {
+            let diff = last.pose.diff(pose);
+            diff.max_angle() > self.threshold || diff.root_delta.length() > RECORD_ROOT_THRESHOLD
+        });
+        if changed {
+            self.capture_keyframe(time - started_at, pose);
+        }
+        changed
+    }
+}
+
+/// Bone matrices for the current keyframe and its onion-skin neighbors
+#[derive(Debug, Clone)]
+pub struct OnionSkinFrames {
+    pub previous: Option<[Mat4; RENDER_BONE_COUNT]>,
+    pub current: [Mat4; RENDER_BONE_COUNT],
+    pub next: Option<[Mat4; RENDER_BONE_COUNT]>,
+}
+
+impl OnionSkinFrames {
+    /// Ghost matrix sets in draw order (previous, then next)
+    pub fn ghosts(&self) -> Vec<[Mat4; RENDER_BONE_COUNT]> {
+        self.previous.into_iter().chain(self.next).collect()
+    }
+}
+
+impl EditorSession {
+    /// Create a session editing the given clip
+    pub fn new(clip: RotationAnimationClip) -> Self {
+        Self {
+            clip,
+            keyframe_index: 0,
+            hovered_joint: None,
+            selected_bone: None,
+            onion_skin: false,
+            joint_limits: default_joint_limits(),
+            recording: None,
+            auto_fit_duration: false,
+            spine_ik_influence: 0.0,
+            ik_damping: 0.0,
+            drag_falloff: 0.0,
+            wrap_navigation: false,
+        }
+    }
+
+    /// Create a session with a single bind pose keyframe
+    pub fn empty(name: String) -> Self {
+        Self::new(RotationAnimationClip {
+            name,
+            duration: 1.0,
+            keyframes: vec![RotationKeyframe {
+                time: 0.0,
+                pose: RotationPose::bind_pose(),
+                tcb: None,
+            }],
+        })
+    }
+
+    /// Select which keyframe is shown and edited
+    pub fn set_keyframe_index(&mut self, index: usize) -> Result<(), EditorError> {
+        if index >= self.clip.keyframes.len() {
+            return Err(EditorError::IndexOutOfRange);
+        }
+        self.keyframe_index = index;
+        Ok(())
+    }
+
+    /// Move the cursor `offset` keyframes and return the new index
+    /// Stops at the first/last keyframe, or wraps if `wrap_navigation` is set.
+    pub fn step_keyframe(&mut self, offset: i32) -> Result<usize, EditorError> {
+        let count = self.clip.keyframes.len() as i64;
+        if count == 0 {
+            return Err(EditorError::NoKeyframe);
+        }
+        let target = self.keyframe_index as i64 + offset as i64;
+        let index = if self.wrap_navigation {
+            target.rem_euclid(count)
+        } else {
+            target.clamp(0, count - 1)
+        };
+        self.keyframe_index = index as usize;
+        Ok(self.keyframe_index)
+    }
+
+    /// Move the cursor to the first keyframe
+    pub fn go_to_first(&mut self) -> Result<usize, EditorError> {
+        if self.clip.keyframes.is_empty() {
+            return Err(EditorError::NoKeyframe);
+        }
+        self.keyframe_index = 0;
+        Ok(0)
+    }
+
+    /// Move the cursor to the last keyframe and return its index
+    pub fn go_to_last(&mut self) -> Result<usize, EditorError> {
+        let last = self
+            .clip
+            .keyframes
+            .len()
+            .checked_sub(1)
+            .ok_or(EditorError::NoKeyframe)?;
+        self.keyframe_index = last;
+        Ok(last)
+    }
+
+    /// Remove keyframe `index`; the last keyframe of a clip cannot be removed
+    /// The cursor stays on the same keyframe, or moves back if it was deleted.
+    pub fn delete_keyframe(&mut self, index: usize) -> Result<(), EditorError> {
+        if index >= self.clip.keyframes.len() {
+            return Err(EditorError::IndexOutOfRange);
+        }
+        if self.clip.keyframes.len() == 1 {
+            return Err(EditorError::InvalidArgument);
+        }
+        self.clip.keyframes.remove(index);
+        if self.keyframe_index > index || self.keyframe_index == self.clip.keyframes.len() {
+            self.keyframe_index -= 1;
+        }
+        if self.auto_fit_duration {
+            self.clip.fit_duration();
+        }
+        Ok(())
+    }
+
+    /// Remove consecutive near-identical keyframes, keeping the first of each run
+    /// The cursor follows its keyframe, or the one kept in its place. Returns the
+    /// number removed.
+    pub fn dedup_keyframes(&mut self, tol: f32) -> Result<usize, EditorError> {
+        if tol.is_nan() || tol < 0.0 {
+            return Err(EditorError::InvalidArgument);
+        }
+        let cursor_time = self.current_keyframe_mut()?.time;
+        let removed = self.clip.dedup_keyframes(tol);
+        self.keyframe_index = self
+            .clip
+            .keyframes
+            .partition_point(|kf| kf.time <= cursor_time)
+            .saturating_sub(1);
+        if removed > 0 && self.auto_fit_duration {
+            self.clip.fit_duration();
+        }
+        Ok(removed)
+    }
+
+    /// Current keyframe for editing
+    fn current_keyframe_mut(&mut self) -> Result<&mut RotationKeyframe, EditorError> {
+        self.clip
+            .keyframes
+            .get_mut(self.keyframe_index)
+            .ok_or(EditorError::NoKeyframe)
+    }
+
+    /// Pose of the current keyframe (bind pose if the clip is empty)
+    pub fn current_pose(&self) -> RotationPose {
+        self.clip
+            .keyframes
+            .get(self.keyframe_index)
+            .map(|kf| kf.pose.clone())
+            .unwrap_or_default()
+    }
+
+    /// Compute bone matrices for the current keyframe and, if onion skin is
+    /// enabled, the keyframes directly before and after it
+    pub fn onion_skin_frames(&self) -> OnionSkinFrames {
+        let matrices_at = |index: usize| {
+            self.clip
+                .keyframes
+                .get(index)
+                .map(|kf| kf.pose.compute_bone_matrices())
+        };
+        let (previous, next) = if self.onion_skin {
+            let previous = self.keyframe_index.checked_sub(1).and_then(matrices_at);
+            (previous, matrices_at(self.keyframe_index + 1))
+        } else {
+            (None, None)
+        };
+        OnionSkinFrames {
+            previous,
+            current: self.current_pose().compute_bone_matrices(),
+            next,
+        }
+    }
+
+    /// Bone matrices of the interpolation between two keyframes at `t` (0 = a, 1 = b)
+    /// Returns None if either index is out of range. The clip is not modified.
+    pub fn interpolated_matrices(
+        &self,
+        index_a: usize,
+        index_b: usize,
+        t: f32,
+    ) -> Option<[Mat4; RENDER_BONE_COUNT]> {
+        let a = self.clip.keyframes.get(index_a)?;
+        let b = self.clip.keyframes.get(index_b)?;
+        Some(RotationPose::lerp(&a.pose, &b.pose, t).compute_bone_matrices())
+    }
+
+    /// The current keyframe with every rotation but `bone`'s reset to identity
+    /// Isolates one bone's channel: the root and lengths are the bind pose's, so
+    /// only the bone and its descendants leave their bind positions.
+    pub fn solo_pose(&self, bone: BoneId) -> Option<RotationPose> {
+        let keyframe = self.clip.keyframes.get(self.keyframe_index)?;
+        let rotation = keyframe.pose.local_rotations[bone.index()];
+        Some(RotationPose::bind_pose().with_rotation(bone, rotation))
+    }
+
+    /// Bone matrices of `solo_pose`; the clip is not modified
+    pub fn solo_bone_matrices(&self, bone: BoneId) -> Option<[Mat4; RENDER_BONE_COUNT]> {
+        Some(self.solo_pose(bone)?.compute_bone_matrices())
+    }
+
+    /// Insert `count` eased inbetweens after keyframe `from_index`
+    ///
+    /// Inbetween `i` sits at the eased fraction `easing(i / (count + 1))` of the
+    /// segment to the next keyframe, both in time and in pose (slerp).
+    /// Fails if `from_index` has no following keyframe.
+    pub fn insert_inbetweens(
+        &mut self,
+        from_index: usize,
+        count: usize,
+        easing: Easing,
+    ) -> Result<(), EditorError> {
+        let keyframes = &self.clip.keyframes;
+        let (Some(from), Some(to)) = (keyframes.get(from_index), keyframes.get(from_index + 1))
+        else {
+            return Err(EditorError::IndexOutOfRange);
+        };
+
+        let inbetweens: Vec<RotationKeyframe> = (1..=count)
+            .map(|i| {
+                let fraction = easing.apply(i as f32 / (count + 1) as f32);
+                RotationKeyframe {
+                    time: from.time + (to.time - from.time) * fraction,
+                    pose: RotationPose::lerp(&from.pose, &to.pose, fraction),
+                    tcb: None,
+                }
+            })
+            .collect();
+
+        self.clip
+            .keyframes
+            .splice(from_index + 1..from_index + 1, inbetweens);
+        if self.keyframe_index > from_index {
+            self.keyframe_index += count;
+        }
+        Ok(())
+    }
+
+    /// Drag a joint of the current keyframe along a world axis
+    ///
+    /// The screen-space drag `delta` (pixels) is projected onto the on-screen
+    /// direction of the axis line through the joint, so only the component along
+    /// the axis moves it. The pelvis is translated; other joints are moved by IK.
+    /// Fails with `InvalidArgument` if the axis points at the camera.
+    pub fn drag_joint_axis(
+        &mut self,
+        joint: BoneId,
+        delta: Vec2,
+        axis: Vec3,
+        view_proj: Mat4,
+        viewport: Vec2,
+    ) -> Result<(), EditorError> {
+        let keyframe = self.current_keyframe_mut()?;
+        let position = keyframe.pose.get_position(joint);
+        let distance = axis_drag_distance(position, delta, axis, view_proj, viewport)?;
+        let target = position + axis * distance;
+
+        self.drag_joint_to(joint, target)
+    }
+
+    /// Move a joint of the current keyframe to a world position
+    /// The pelvis is translated; other joints are moved by IK along
+    /// `weighted_drag_chain`, so hand drags may bend the spine.
+    pub fn drag_joint_to(&mut self, joint: BoneId, target: Vec3) -> Result<(), EditorError> {
+        let influence = self.spine_ik_influence;
+        let damping = self.ik_damping;
+        let keyframe = self.current_keyframe_mut()?;
+        let pose = std::mem::take(&mut keyframe.pose);
+        keyframe.pose = if joint == BoneId::Pelvis {
+            pose.with_root_position(target)
+        } else {
+            let (chain, stiffness) = weighted_drag_chain(joint, influence);
+            pose.apply_ik_weighted(&chain, &stiffness, damping, target)
+        };
+        Ok(())
+    }
+
+    /// Set how much the spine joins hand drags (0..=1)
+    pub fn set_spine_ik_influence(&mut self, influence: f32) -> Result<(), EditorError> {
+        if !(0.0..=1.0).contains(&influence) {
+            return Err(EditorError::InvalidArgument);
+        }
+        self.spine_ik_influence = influence;
+        Ok(())
+    }
+
+    /// Set the IK damping used by joint drags (0 = none, below 1)
+    /// Higher values steady drags toward hard-to-reach targets but converge slower.
+    pub fn set_ik_damping(&mut self, damping: f32) -> Result<(), EditorError> {
+        if !(0.0..1.0).contains(&damping) {
+            return Err(EditorError::InvalidArgument);
+        }
+        self.ik_damping = damping;
+        Ok(())
+    }
+
+    /// Move the root of the current keyframe by `offset`, keeping the feet planted
+    ///
+    /// Ankle and foot joints are captured before the move; afterwards each leg is
+    /// solved back onto its ankle and the ankle re-aimed at its foot, so the hips
+    /// shift while the feet stay put (within IK tolerance, if still in reach).
+    pub fn shift_root_planted(&mut self, offset: Vec3) -> Result<(), EditorError> {
+        let keyframe = self.current_keyframe_mut()?;
+        let pose = std::mem::take(&mut keyframe.pose);
+        let planted = PLANTED_FEET.map(|(ankle, foot)| {
+            (
+                ankle,
+                pose.get_position(ankle),
+                foot,
+                pose.get_position(foot),
+            )
+        });
+
+        let root = pose.root_position + offset;
+        let mut pose = pose.with_root_position(root);
+        for (ankle, ankle_target, foot, foot_target) in planted {
+            pose = pose
+                .apply_ik(&constraint_chain(ankle), ankle_target)
+                .apply_ik(&[foot], foot_target);
+        }
+        pose.sanitize();
+        keyframe.pose = pose;
+        Ok(())
+    }
+
+    /// Drag the root of the current keyframe along a world axis with the feet planted
+    /// Same screen-space projection as `drag_joint_axis` on the pelvis.
+    pub fn drag_root_with_planted_feet(
+        &mut self,
+        delta: Vec2,
+        axis: Vec3,
+        view_proj: Mat4,
+        viewport: Vec2,
+    ) -> Result<(), EditorError> {
+        let position = self.current_pose().get_position(BoneId::Pelvis);
+        let distance = axis_drag_distance(position, delta, axis, view_proj, viewport)?;
+        self.shift_root_planted(axis * distance)
+    }
+
+    /// Rotate a bone of the current keyframe about one of its local axes
+    /// With `drag_falloff` set, each parent up to (not including) the pelvis
+    /// also turns by `drag_falloff^depth` of the angle, for a softer bend.
+    pub fn rotate_bone_axis(
+        &mut self,
+        bone: BoneId,
+        axis: Vec3,
+        degrees: f32,
+    ) -> Result<(), EditorError> {
+        if !degrees.is_finite() {
+            return Err(EditorError::InvalidArgument);
+        }
+        let falloff = self.drag_falloff;
+        let keyframe = self.current_keyframe_mut()?;
+        let mut pose = std::mem::take(&mut keyframe.pose);
+        let mut current = Some(bone);
+        let mut weight = 1.0;
+        while let Some(target) = current {
+            if target == BoneId::Pelvis || weight < DRAG_FALLOFF_MIN_WEIGHT {
+                break;
+            }
+            let local = pose.local_rotations[target.index()];
+            // Post-multiply so the axis is in the bone's local frame
+            let delta = Quat::from_axis_angle(axis, weight * degrees.to_radians());
+            pose = pose.with_rotation(target, (local * delta).normalize());
+            current = BONE_HIERARCHY[target.index()].parent;
+            weight *= falloff;
+        }
+        keyframe.pose = pose;
+        Ok(())
+    }
+
+    /// Set how much of an FK rotation spreads to parent bones (0 = none, below 1)
+    pub fn set_drag_falloff(&mut self, strength: f32) -> Result<(), EditorError> {
+        if !(0.0..1.0).contains(&strength) {
+            return Err(EditorError::InvalidArgument);
+        }
+        self.drag_falloff = strength;
+        Ok(())
+    }
+
+    /// Overwrite every local rotation of the current keyframe at once
+    /// `rotations` holds one [x, y, z, w] quaternion per bone, ordered by bone index.
+    /// Non-finite or zero-length quaternions reject the whole call.
+    pub fn set_pose_rotations(&mut self, rotations: &[f32]) -> Result<(), EditorError> {
+        if rotations.len() != BoneId::COUNT * 4 {
+            return Err(EditorError::InvalidArgument);
+        }
+        let quats: Vec<Quat> = rotations.chunks_exact(4).map(Quat::from_slice).collect();
+        if quats
+            .iter()
+            .any(|q| !q.is_finite() || q.length_squared() < MIN_QUAT_LENGTH_SQUARED)
+        {
+            return Err(EditorError::InvalidArgument);
+        }
+        let keyframe = self.current_keyframe_mut()?;
+        let mut pose = std::mem::take(&mut keyframe.pose);
+        for (bone, quat) in BoneId::ALL.iter().zip(quats) {
+            pose = pose.with_rotation(*bone, quat.normalize());
+        }
+        keyframe.pose = pose;
+        Ok(())
+    }
+
+    /// Move the root of the current keyframe
+    pub fn set_pose_root(&mut self, position: Vec3) -> Result<(), EditorError> {
+        let keyframe = self.current_keyframe_mut()?;
+        keyframe.pose = std::mem::take(&mut keyframe.pose).with_root_position(position);
+        Ok(())
+    }
+
+    /// Replace the current keyframe's pose (rotations and root)
+    pub fn apply_pose(&mut self, pose: &RotationPose) -> Result<(), EditorError> {
+        self.current_keyframe_mut()?.pose = pose.clone();
+        Ok(())
+    }
+
+    /// Set a bone's local rotation in the current keyframe from XYZ Euler degrees
+    /// The angles are clamped to the bone's joint limits first.
+    pub fn set_bone_rotation(
+        &mut self,
+        bone: BoneId,
+        euler: EulerAngles,
+    ) -> Result<(), EditorError> {
+        let rotation = self.joint_limits[bone.index()].clamp(euler).to_quat();
+        let keyframe = self.current_keyframe_mut()?;
+        keyframe.pose = std::mem::take(&mut keyframe.pose).with_rotation(bone, rotation);
+        keyframe.pose.sanitize();
+        Ok(())
+    }
+
+    /// Set the root (pelvis) rotation of the current keyframe from XYZ Euler degrees
+    ///
+    /// The pelvis is the root bone, so this turns the whole body about the root
+    /// joint. A joint lands in world space as
+    /// `root_position + facing * pelvis_rotation * (offset in the pelvis frame)`:
+    /// `root_position` is stored separately in the keyframe, and playback's
+    /// `facing` is not stored at all. It is applied on top when sampling (see
+    /// `RotationPose::with_facing`). Editing one leaves the other two unchanged.
+    pub fn set_root_rotation(&mut self, euler: EulerAngles) -> Result<(), EditorError> {
+        self.set_bone_rotation(BoneId::Pelvis, euler)
+    }
+
+    /// Swing and twist (degrees) of a bone's local rotation in the current keyframe
+    /// The twist is about `twist_axis(bone)`; local rotation = swing * twist.
+    pub fn bone_swing_twist(&self, bone: BoneId) -> (Quat, f32) {
+        let axis = twist_axis(bone);
+        let rotation = self
+            .clip
+            .keyframes
+            .get(self.keyframe_index)
+            .map_or(Quat::IDENTITY, |kf| kf.pose.local_rotations[bone.index()]);
+        let (swing, twist) = swing_twist(rotation, axis);
+        (swing, twist_angle(twist, axis).to_degrees())
+    }
+
+    /// Set the swing (bend) of a bone to `degrees` about `axis`, keeping its twist
+    ///
+    /// Meant for the spine, where editing bend and roll apart avoids the gimbal
+    /// lock of Euler angles. `axis` is in the bone's local frame and is made
+    /// perpendicular to the twist axis; fails if it is parallel to it.
+    pub fn set_bone_swing(
+        &mut self,
+        bone: BoneId,
+        axis: Vec3,
+        degrees: f32,
+    ) -> Result<(), EditorError> {
+        let twist_axis = twist_axis(bone);
+        let swing_axis = axis
+            .reject_from_normalized(twist_axis)
+            .try_normalize()
+            .ok_or(EditorError::InvalidArgument)?;
+        let keyframe = self.current_keyframe_mut()?;
+        let (_, twist) = swing_twist(keyframe.pose.local_rotations[bone.index()], twist_axis);
+        let swing = Quat::from_axis_angle(swing_axis, degrees.to_radians());
+        keyframe.pose =
+            std::mem::take(&mut keyframe.pose).with_rotation(bone, (swing * twist).normalize());
+        keyframe.pose.sanitize();
+        Ok(())
+    }
+
+    /// Set the twist (roll about `twist_axis`) of a bone to `degrees`, keeping its swing
+    pub fn set_bone_twist(&mut self, bone: BoneId, degrees: f32) -> Result<(), EditorError> {
+        let axis = twist_axis(bone);
+        let keyframe = self.current_keyframe_mut()?;
+        let (swing, _) = swing_twist(keyframe.pose.local_rotations[bone.index()], axis);
+        let twist = Quat::from_axis_angle(axis, degrees.to_radians());
+        keyframe.pose =
+            std::mem::take(&mut keyframe.pose).with_rotation(bone, (swing * twist).normalize());
+        keyframe.pose.sanitize();
+        Ok(())
+    }
+
+    /// Limit one Euler axis of a bone to `min..=max` degrees (None frees the axis)
+    pub fn set_joint_limit(
+        &mut self,
+        bone: BoneId,
+        axis: usize,
+        limit: Option<(f32, f32)>,
+    ) -> Result<(), EditorError> {
+        if limit.is_some_and(|(min, max)| min.is_nan() || max.is_nan() || min > max) {
+            return Err(EditorError::InvalidArgument);
+        }
+        let slot = self.joint_limits[bone.index()]
+            .axis_mut(axis)
+            .ok_or(EditorError::InvalidArgument)?;
+        *slot = limit;
+        Ok(())
+    }
+
+    /// Local rotation of a bone in the current keyframe as XYZ Euler degrees
+    pub fn bone_local_euler(&self, bone: BoneId) -> EulerAngles {
+        self.clip
+            .keyframes
+            .get(self.keyframe_index)
+            .map(|kf| EulerAngles::from_quat(kf.pose.local_rotations[bone.index()]))
+            .unwrap_or_default()
+    }
+
+    /// Start recording the current keyframe's pose (see `Recording`)
+    /// The take is inserted into the clip from the current keyframe's time on.
+    pub fn start_recording(&mut self, interval: f32, threshold: f32) -> Result<(), EditorError> {
+        if !(interval > 0.0 && threshold >= 0.0) {
+            return Err(EditorError::InvalidArgument);
+        }
+        let clip_offset = self
+            .clip
+            .keyframes
+            .get(self.keyframe_index)
+            .map_or(0.0, |kf| kf.time);
+        self.recording = Some(Recording::new(interval, threshold, clip_offset));
+        Ok(())
+    }
+
+    /// Sample the live pose into the recording at absolute `time` (seconds)
+    pub fn record_tick(&mut self, time: f32) -> Result<bool, EditorError> {
+        let pose = self.current_pose();
+        let recording = self
+            .recording
+            .as_mut()
+            .ok_or(EditorError::InvalidArgument)?;
+        Ok(recording.tick(time, &pose))
+    }
+
+    /// Stop recording and insert the take into the clip as keyframes
+    ///
+    /// Keyframes are placed from the time recording started at (see
+    /// `start_recording`); existing keyframes inside the recorded time range are
+    /// replaced, those outside it kept. The clip is lengthened if the take runs
+    /// past its end, and the last recorded keyframe becomes current. An empty
+    /// take leaves the clip unchanged.
+    pub fn stop_recording(&mut self) -> Result<(), EditorError> {
+        let recording = self.recording.take().ok_or(EditorError::InvalidArgument)?;
+        let Some(last) = recording.take.last() else {
+            return Ok(());
+        };
+        let start = recording.clip_offset;
+        let end = start + last.time;
+        self.clip
+            .keyframes
+            .retain(|kf| kf.time < start || kf.time > end);
+        self.clip
+            .keyframes
+            .extend(recording.take.into_iter().map(|kf| RotationKeyframe {
+                time: start + kf.time,
+                ..kf
+            }));
+        self.clip
+            .keyframes
+            .sort_by(|a, b| a.time.total_cmp(&b.time));
+        self.clip.duration = self.clip.duration.max(end + recording.interval);
+        self.keyframe_index = self
+            .clip
+            .keyframes
+            .iter()
+            .rposition(|kf| kf.time == end)
+            .unwrap_or(0);
+        Ok(())
+    }
+
+    /// Select a bone (None clears the selection)
+    pub fn select_bone(&mut self, bone: Option<BoneId>) {
+        self.selected_bone = bone;
+    }
+
+    /// Decode a bone index from JavaScript where -1 means the selected bone
+    pub fn bone_or_selected(&self, bone_index: i32) -> Result<BoneId, EditorError> {
+        match bone_index {
+            -1 => self.selected_bone.ok_or(EditorError::NoSelection),
+            _ => usize::try_from(bone_index)
+                .map_err(|_| EditorError::IndexOutOfRange)
+                .and_then(bone_arg),
+        }
+    }
+
+    /// Set the hovered joint, returning the tint colors to upload
+    pub fn set_hovered_joint(&mut self, joint: Option<BoneId>) -> [[f32; 4]; RENDER_BONE_COUNT] {
+        self.hovered_joint = joint;
+        hover_bone_colors(joint)
+    }
+}
+
+/// All open editor sessions, keyed by handle
+#[derive(Debug, Default)]
+pub struct EditorSessions {
+    sessions: HashMap<u32, EditorSession>,
+    next_handle: u32,
+    /// Handles of destroyed sessions, reused before new ones are issued
+    free_handles: Vec<u32>,
+    /// Outcome of the last checked operation (None = it succeeded)
+    last_error: Option<EditorError>,
+}
+
+impl EditorSessions {
+    /// Create an empty session registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a session and return its handle (never 0)
+    /// The most recently freed handle is reused first, so handles stay small.
+    pub fn create(&mut self, session: EditorSession) -> u32 {
+        let handle = self.free_handles.pop().unwrap_or_else(|| {
+            self.next_handle += 1;
+            self.next_handle
+        });
+        self.sessions.insert(handle, session);
+        handle
+    }
+
+    /// Remove a session. Returns false if the handle was not open.
+    pub fn destroy(&mut self, handle: u32) -> bool {
+        let removed = self.sessions.remove(&handle).is_some();
+        if removed {
+            self.free_handles.push(handle);
+        }
+        removed
+    }
+
+    /// Get a session by handle
+    pub fn get(&self, handle: u32) -> Option<&EditorSession> {
+        self.sessions.get(&handle)
+    }
+
+    /// Get a session by handle for modification
+    pub fn get_mut(&mut self, handle: u32) -> Option<&mut EditorSession> {
+        self.sessions.get_mut(&handle)
+    }
+
+    /// Run an operation on a session, recording its outcome as the last error
+    pub fn update<T>(
+        &mut self,
+        handle: u32,
+        op: impl FnOnce(&mut EditorSession) -> Result<T, EditorError>,
+    ) -> Result<T, EditorError> {
+        let result = match self.sessions.get_mut(&handle) {
+            Some(session) => op(session),
+            None => Err(EditorError::InvalidHandle),
+        };
+        self.record(result)
+    }
+
+    /// Record the outcome of an operation as the last error
+    pub fn record<T>(&mut self, result: Result<T, EditorError>) -> Result<T, EditorError> {
+        self.last_error = result.as_ref().err().copied();
+        result
+    }
+
+    /// Error from the most recent checked operation, if it failed
+    pub fn last_error(&self) -> Option<EditorError> {
+        self.last_error
+    }
+}
+
+/// Name of the built-in bind pose preset (arms straight out)
+pub const T_POSE: &str = "t_pose";
+/// Name of the built-in A-pose preset (arms lowered)
+pub const A_POSE: &str = "a_pose";
+/// How far the A-pose lowers the upper arms from horizontal
+const A_POSE_ARM_ANGLE: f32 = 45.0;
+
+/// Named single-frame poses that can be snapped into a keyframe
+/// Unlike clips these carry no timing; starts with the built-in presets.
+#[derive(Debug, Clone)]
+pub struct PoseLibrary {
+    poses: HashMap<String, RotationPose>,
+}
+
+impl Default for PoseLibrary {
+    fn default() -> Self {
+        let a_pose = RotationPose::bind_pose()
+            .with_rotation(
+                BoneId::LeftShoulder,
+                Quat::from_rotation_z(-A_POSE_ARM_ANGLE.to_radians()),
+            )
+            .with_rotation(
+                BoneId::RightShoulder,
+                Quat::from_rotation_z(A_POSE_ARM_ANGLE.to_radians()),
+            );
+        let poses = HashMap::from([
+            (T_POSE.to_string(), RotationPose::bind_pose()),
+            (A_POSE.to_string(), a_pose),
+        ]);
+        Self { poses }
+    }
+}
+
+impl PoseLibrary {
+    /// Create a library holding the built-in presets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a pose under a name, replacing any pose already there
+    pub fn save(&mut self, name: &str, pose: RotationPose) {
+        self.poses.insert(name.to_string(), pose);
+    }
+
+    /// Get a pose by name
+    pub fn get(&self, name: &str) -> Option<&RotationPose> {
+        self.poses.get(name)
+    }
+
+    /// Names of all stored poses, sorted
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.poses.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// Decode a joint index from JavaScript (-1 or out of range = none)
+pub fn joint_from_index(joint_index: i32) -> Option<BoneId> {
+    usize::try_from(joint_index)
+        .ok()
+        .and_then(BoneId::from_index)
+}
+
+/// Decode a local axis index from JavaScript (0 = X, 1 = Y, 2 = Z)
+pub fn axis_from_index(axis: u32) -> Option<Vec3> {
+    match axis {
+        0 => Some(Vec3::X),
+        1 => Some(Vec3::Y),
+        2 => Some(Vec3::Z),
+        _ => None,
+    }
+}
+
+/// Decode a bone index argument from JavaScript
+pub fn bone_arg(bone_index: usize) -> Result<BoneId, EditorError> {
+    BoneId::from_index(bone_index).ok_or(EditorError::IndexOutOfRange)
+}
+
+/// Decode an axis argument from JavaScript
+pub fn axis_arg(axis: u32) -> Result<Vec3, EditorError> {
+    axis_from_index(axis).ok_or(EditorError::InvalidArgument)
+}
+
+/// Axis a bone twists (rolls) about, in its local frame
+///
+/// The rest direction of its only child, or its own for a bone without
+/// children. Bones that branch (the pelvis and the top of the spine) twist
+/// about local Y, up the torso.
+pub fn twist_axis(bone: BoneId) -> Vec3 {
+    let mut children = BoneId::ALL
+        .into_iter()
+        .filter(|child| BONE_HIERARCHY[child.index()].parent == Some(bone));
+    let direction = match (children.next(), children.next()) {
+        (Some(child), None) => BONE_HIERARCHY[child.index()].direction,
+        (None, _) => BONE_HIERARCHY[bone.index()].direction,
+        (Some(_), Some(_)) => Vec3::Y,
+    };
+    direction.normalize()
+}
+
+/// Bones moved by IK when dragging a joint
+///
+/// The joint plus its parent, so a wrist drag bends the elbow and shoulder.
+/// Bones hanging directly off the pelvis are not extended upward, since that
+/// would rotate the pelvis and swing the whole body.
+pub fn drag_chain(joint: BoneId) -> Vec<BoneId> {
+    let parent_of = |bone: BoneId| BONE_HIERARCHY[bone.index()].parent;
+    match parent_of(joint) {
+        Some(parent) if parent_of(parent).is_some_and(|p| p != BoneId::Pelvis) => {
+            vec![parent, joint]
+        }
+        _ => vec![joint],
+    }
+}
+
+/// (ankle, foot) joints kept in place by `shift_root_planted`
+const PLANTED_FEET: [(BoneId, BoneId); 2] = [
+    (BoneId::LeftAnkle, BoneId::LeftFoot),
+    (BoneId::RightAnkle, BoneId::RightFoot),
+];
+
+/// World distance along `axis` matching a screen drag of `delta` pixels at `position`
+/// Fails with `InvalidArgument` if the axis points at the camera.
+fn axis_drag_distance(
+    position: Vec3,
+    delta: Vec2,
+    axis: Vec3,
+    view_proj: Mat4,
+    viewport: Vec2,
+) -> Result<f32, EditorError> {
+    let (Some(start), Some(end)) = (
+        world_to_screen(position, view_proj, viewport),
+        world_to_screen(position + axis, view_proj, viewport),
+    ) else {
+        return Err(EditorError::InvalidArgument);
+    };
+
+    // Pixels moved on screen per world unit along the axis
+    let axis_screen = end - start;
+    if axis_screen.length_squared() < EPSILON {
+        return Err(EditorError::InvalidArgument);
+    }
+    Ok(delta.dot(axis_screen) / axis_screen.length_squared())
+}
+
+/// `drag_chain` plus stiffness; hand drags can reach back into the spine
+///
+/// With `spine_influence` > 0 a wrist drag also rotates the collar and the two
+/// upper spine bones, each segment at stiffness `1 - spine_influence` so the
+/// torso leans a little while the arm does most of the reach.
+pub fn weighted_drag_chain(joint: BoneId, spine_influence: f32) -> (Vec<BoneId>, Vec<f32>) {
+    let mut chain = drag_chain(joint);
+    let is_hand = matches!(joint, BoneId::LeftWrist | BoneId::RightWrist);
+    if !is_hand || spine_influence <= 0.0 {
+        let stiffness = vec![0.0; chain.len()];
+        return (chain, stiffness);
+    }
+
+    // Prepend shoulder, collar and Spine3, stopping below Spine2
+    let mut torso = Vec::new();
+    let mut bone = chain[0];
+    while let Some(parent) = BONE_HIERARCHY[bone.index()].parent {
+        if parent == BoneId::Spine2 {
+            break;
+        }
+        torso.push(parent);
+        bone = parent;
+    }
+    torso.reverse();
+    let stiffness = std::iter::repeat_n(1.0 - spine_influence.min(1.0), torso.len())
+        .chain(std::iter::repeat_n(0.0, chain.len()))
+        .collect();
+    torso.append(&mut chain);
+    (torso, stiffness)
+}
+
+/// Project a world point to screen pixels (origin top-left, y down)
+/// Returns None for points behind the camera.
+pub fn world_to_screen(point: Vec3, view_proj: Mat4, viewport: Vec2) -> Option<Vec2> {
+    let clip = view_proj * point.extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate() / clip.w;
+    Some(Vec2::new(
+        (ndc.x * 0.5 + 0.5) * viewport.x,
+        (0.5 - ndc.y * 0.5) * viewport.y,
+    ))
+}
+
+/// Flatten a pose's joint world positions into [x, y, z, ...] ordered by `BoneId`
+pub fn joint_positions(pose: &RotationPose) -> Vec<f32> {
+    let prepared = pose.prepared();
+    BoneId::ALL
+        .iter()
+        .flat_map(|&bone| prepared.position(bone).to_array())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_positions_match_corrected_matrices() {
+        use crate::skeleton::{rest_joint_position, RENDER_SEGMENTS};
+        use crate::skinning::{compute_instance_matrices, SkinningInstance, SkinningTables};
+
+        // Raised far enough for a large collar corrective
+        let pose = RotationPose::bind_pose().with_rotation(
+            BoneId::LeftShoulder,
+            Quat::from_rotation_z(150f32.to_radians()),
+        );
+        let joints = joint_positions(&pose);
+        let joint = |bone: BoneId| Vec3::from_slice(&joints[bone.index() * 3..]);
+
+        let gpu_matrices =
+            compute_instance_matrices(&SkinningTables::default(), &SkinningInstance::from(&pose));
+        for matrices in [pose.compute_bone_matrices(), gpu_matrices] {
+            for (matrix, &(start, end)) in matrices.iter().zip(&RENDER_SEGMENTS) {
+                // Each matrix carries its bind segment start onto the reported joint
+                let rest_start = Vec3::from(rest_joint_position(start));
+                assert!(
+                    matrix
+                        .transform_point3(rest_start)
+                        .abs_diff_eq(joint(start), 1e-4),
+                    "{:?} -> {:?}",
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_session_handles_are_nonzero_and_unique() {
+        let mut sessions = EditorSessions::new();
+        let a = sessions.create(EditorSession::empty("a".to_string()));
+        let b = sessions.create(EditorSession::empty("b".to_string()));
+
+        assert_ne!(a, 0);
+        assert_ne!(a, b);
+        assert!(sessions.destroy(a));
+        assert!(!sessions.destroy(a));
+        assert!(sessions.get(a).is_none());
+        assert!(sessions.get(b).is_some());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_destroyed_handles_are_reused() {
+        let mut sessions = EditorSessions::new();
+        let a = sessions.create(EditorSession::empty("a".to_string()));
+        let b = sessions.create(EditorSession::empty("b".to_string()));
+
+        assert!(sessions.destroy(a));
+        // A double destroy must not free the handle twice
+        assert!(!sessions.destroy(a));
+        let c = sessions.create(EditorSession::empty("c".to_string()));
+        assert_eq!(c, a);
+        assert_eq!(sessions.get(c).unwrap().clip.name, "c");
+
+        // Nothing left to reuse: a fresh handle that aliases no live session
+        let d = sessions.create(EditorSession::empty("d".to_string()));
+        assert!(d != 0 && d != b && d != c);
+        assert_eq!(sessions.get(b).unwrap().clip.name, "b");
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_keyframe_navigation() {
+        let mut session = EditorSession::empty("nav".to_string());
+        for time in [0.25, 0.5] {
+            session.clip.keyframes.push(RotationKeyframe {
+                time,
+                pose: RotationPose::bind_pose(),
+                tcb: None,
+            });
+        }
+
+        assert_eq!(session.step_keyframe(1), Ok(1));
+        assert_eq!(session.go_to_last(), Ok(2));
+        // Clamped by default
+        assert_eq!(session.step_keyframe(1), Ok(2));
+        assert_eq!(session.go_to_first(), Ok(0));
+        assert_eq!(session.step_keyframe(-1), Ok(0));
+
+        session.wrap_navigation = true;
+        assert_eq!(session.step_keyframe(-1), Ok(2));
+        assert_eq!(session.step_keyframe(1), Ok(0));
+
+        session.clip.keyframes.clear();
+        assert_eq!(session.step_keyframe(1), Err(EditorError::NoKeyframe));
+        assert_eq!(session.go_to_first(), Err(EditorError::NoKeyframe));
+        assert_eq!(session.go_to_last(), Err(EditorError::NoKeyframe));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_spine_ik_influence() {
+        let target = Vec3::new(0.9, 1.3, 0.5);
+        let spine_bones = [BoneId::Spine2, BoneId::Spine3];
+        let reach = |influence: f32| {
+            let mut session = EditorSession::empty("reach".to_string());
+            session.set_spine_ik_influence(influence).unwrap();
+            session.drag_joint_to(BoneId::LeftWrist, target).unwrap();
+            session.current_pose()
+        };
+
+        let arm_only = reach(0.0);
+        for bone in spine_bones {
+            assert_eq!(arm_only.local_rotations[bone.index()], Quat::IDENTITY);
+        }
+
+        let leaning = reach(0.3);
+        let spine_turn: f32 = spine_bones
+            .iter()
+            .map(|bone| leaning.local_rotations[bone.index()].angle_between(Quat::IDENTITY))
+            .sum();
+        assert!(spine_turn > 0.01 && spine_turn < 0.6, "{}", spine_turn);
+        // The spine helps the hand get closer to the far target
+        let miss = |pose: &RotationPose| pose.get_position(BoneId::LeftWrist).distance(target);
+        assert!(miss(&leaning) < miss(&arm_only));
+
+        let mut session = EditorSession::empty("reach".to_string());
+        assert_eq!(
+            session.set_spine_ik_influence(1.5),
+            Err(EditorError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_root_shift_keeps_feet_planted() {
+        // Bent knees, so the legs can shorten as the hips drop
+        let mut session = EditorSession::empty("weight shift".to_string());
+        for knee in [BoneId::LeftKnee, BoneId::RightKnee] {
+            session.rotate_bone_axis(knee, Vec3::X, 30.0).unwrap();
+        }
+        let feet_before: Vec<Vec3> = PLANTED_FEET
+            .iter()
+            .flat_map(|&(ankle, foot)| [ankle, foot])
+            .map(|joint| session.current_pose().get_position(joint))
+            .collect();
+        let root_before = session.current_pose().root_position;
+
+        let offset = Vec3::new(0.08, -0.05, 0.03);
+        session.shift_root_planted(offset).unwrap();
+
+        let pose = session.current_pose();
+        assert!(pose.root_position.abs_diff_eq(root_before + offset, 1e-6));
+        let joints = PLANTED_FEET.iter().flat_map(|&(ankle, foot)| [ankle, foot]);
+        for (joint, before) in joints.zip(feet_before) {
+            let after = pose.get_position(joint);
+            assert!(
+                after.distance(before) < 2.0 * RotationPose::IK_TOLERANCE,
+                "{:?} moved: {} -> {}",
+                joint,
+                before,
+                after
+            );
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fit_duration_after_delete() {
+        let mut session = EditorSession::empty("fit".to_string());
+        session.clip.duration = 2.0;
+        for time in [0.5, 1.5] {
+            session.clip.keyframes.push(RotationKeyframe {
+                time,
+                pose: RotationPose::bind_pose(),
+                tcb: None,
+            });
+        }
+        session.set_keyframe_index(2).unwrap();
+
+        session.delete_keyframe(2).unwrap();
+        assert_eq!(session.keyframe_index, 1);
+        assert_eq!(session.clip.duration, 2.0);
+        session.clip.fit_duration();
+        assert_eq!(session.clip.duration, 0.5);
+
+        // A lone keyframe at 0 keeps a nonzero duration, so sampling stays finite
+        session.auto_fit_duration = true;
+        session.delete_keyframe(1).unwrap();
+        assert!(session.clip.duration > 0.0);
+        assert!(session.clip.sample(0.7).is_finite());
+        assert_eq!(
+            session.delete_keyframe(0),
+            Err(EditorError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_root_rotation_is_independent_of_facing_and_position() {
+        let mut session = EditorSession::empty("root".to_string());
+        let root = Vec3::new(0.5, 1.0, -0.2);
+        session.set_pose_root(root).unwrap();
+        let before = session.current_pose();
+
+        session
+            .set_root_rotation(EulerAngles {
+                x: 0.0,
+                y: 90.0,
+                z: 0.0,
+            })
+            .unwrap();
+        let pose = session.current_pose();
+        let turn = Quat::from_rotation_y(90f32.to_radians());
+        assert!(pose.root_position.abs_diff_eq(root, 1e-6));
+        // Every joint swings about the root
+        for bone in BoneId::ALL {
+            let expected = root + turn * (before.get_position(bone) - root);
+            assert!(
+                pose.get_position(bone).abs_diff_eq(expected, 1e-4),
+                "{:?}",
+                bone
+            );
+        }
+        let euler = session.bone_local_euler(BoneId::Pelvis);
+        assert!((euler.y - 90.0).abs() < 1e-3, "{:?}", euler);
+
+        // Facing composes on top without touching the stored keyframe
+        let facing = Quat::from_rotation_y(-90f32.to_radians());
+        let faced = session.current_pose().with_facing(facing);
+        for bone in BoneId::ALL {
+            assert!(faced
+                .get_position(bone)
+                .abs_diff_eq(before.get_position(bone), 1e-4));
+        }
+        assert!((session.bone_local_euler(BoneId::Pelvis).y - 90.0).abs() < 1e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_dedup_collapses_consecutive_keyframes() {
+        let mut session = EditorSession::empty("dedup".to_string());
+        session.clip.duration = 3.0;
+        let bent =
+            RotationPose::bind_pose().with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(0.5));
+        // bind, bind, bind, bent, bind
+        for (time, pose) in [
+            (0.5, RotationPose::bind_pose()),
+            (1.0, RotationPose::bind_pose()),
+            (1.5, bent.clone()),
+            (2.0, RotationPose::bind_pose()),
+        ] {
+            session.clip.keyframes.push(RotationKeyframe {
+                time,
+                pose,
+                tcb: None,
+            });
+        }
+        let keyframes = &session.clip.keyframes;
+        assert!(keyframes[0].approx_eq(&keyframes[1], 1e-4));
+        assert!(!keyframes[2].approx_eq(&keyframes[3], 1e-4));
+        session.set_keyframe_index(1).unwrap();
+
+        assert_eq!(session.dedup_keyframes(1e-4), Ok(2));
+        let times: Vec<f32> = session.clip.keyframes.iter().map(|kf| kf.time).collect();
+        // The non-consecutive bind pose at 2.0 is kept
+        assert_eq!(times, [0.0, 1.5, 2.0]);
+        assert_eq!(session.keyframe_index, 0);
+        assert_eq!(session.dedup_keyframes(1e-4), Ok(0));
+        assert_eq!(
+            session.dedup_keyframes(f32::NAN),
+            Err(EditorError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_solo_bone_keeps_only_its_rotation() {
+        let mut session = EditorSession::empty("solo".to_string());
+        let elbow_rot = Quat::from_rotation_z(0.9);
+        let pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::Spine2, Quat::from_rotation_x(0.3))
+            .with_rotation(BoneId::LeftElbow, elbow_rot)
+            .with_root_position(Vec3::new(0.2, 1.1, 0.0));
+        session.apply_pose(&pose).unwrap();
+
+        let solo = session.solo_pose(BoneId::LeftElbow).unwrap();
+        let bind = RotationPose::bind_pose();
+        assert_eq!(solo.local_rotations[BoneId::LeftElbow.index()], elbow_rot);
+        // The elbow joint stays put; the wrist below it swings with the keyframe
+        for bone in BoneId::ALL {
+            let position = solo.get_position(bone);
+            if bone == BoneId::LeftWrist {
+                assert!(position.distance(bind.get_position(bone)) > 0.01);
+            } else {
+                assert!(
+                    position.abs_diff_eq(bind.get_position(bone), 1e-6),
+                    "{:?}",
+                    bone
+                );
+            }
+        }
+        assert_eq!(
+            session.solo_bone_matrices(BoneId::LeftElbow).unwrap(),
+            solo.compute_bone_matrices()
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_hover_head_tints_head_sphere() {
+        let mut session = EditorSession::empty("hover".to_string());
+
+        let colors = session.set_hovered_joint(joint_from_index(BoneId::Head.index() as i32));
+        let head_sphere = RENDER_BONE_COUNT - 1;
+        assert_eq!(colors[head_sphere], HOVER_COLOR);
+        for (i, color) in colors.iter().enumerate() {
+            if i != head_sphere {
+                assert_eq!(color[3], 0.0, "Render bone {} should not be tinted", i);
+            }
+        }
+
+        // -1 clears the highlight
+        let colors = session.set_hovered_joint(joint_from_index(-1));
+        assert!(session.hovered_joint.is_none());
+        assert!(colors.iter().all(|c| c[3] == 0.0));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_onion_skin_frames() {
+        let mut clip = EditorSession::empty("onion".to_string()).clip;
+        for i in 1..3 {
+            clip.keyframes.push(RotationKeyframe {
+                time: i as f32 * 0.5,
+                pose: RotationPose::bind_pose()
+                    .with_rotation(BoneId::Spine1, glam::Quat::from_rotation_x(i as f32 * 0.3)),
+                tcb: None,
+            });
+        }
+        let mut session = EditorSession::new(clip);
+        session.keyframe_index = 1;
+
+        // Disabled: no ghosts
+        assert!(session.onion_skin_frames().ghosts().is_empty());
+
+        // Middle keyframe: previous, current and next
+        session.onion_skin = true;
+        let frames = session.onion_skin_frames();
+        assert!(frames.previous.is_some());
+        assert!(frames.next.is_some());
+        assert_eq!(frames.ghosts().len(), 2);
+        assert_ne!(frames.previous.unwrap(), frames.current);
+        assert_ne!(frames.next.unwrap(), frames.current);
+
+        // First keyframe has no previous neighbor
+        session.keyframe_index = 0;
+        let frames = session.onion_skin_frames();
+        assert!(frames.previous.is_none());
+        assert!(frames.next.is_some());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_rotate_bone_axis_accumulates() {
+        let mut session = EditorSession::empty("axis".to_string());
+        let x = axis_from_index(0).unwrap();
+
+        assert!(session.rotate_bone_axis(BoneId::LeftElbow, x, 30.0).is_ok());
+        assert!(session.rotate_bone_axis(BoneId::LeftElbow, x, 30.0).is_ok());
+
+        let euler = session.bone_local_euler(BoneId::LeftElbow);
+        assert!(
+            (euler.x - 60.0).abs() < 1e-3,
+            "Expected ~60 deg, got {}",
+            euler.x
+        );
+        assert!(euler.y.abs() < 1e-3);
+        assert!(euler.z.abs() < 1e-3);
+        assert!(axis_from_index(3).is_none());
+
+        // A non-finite angle is rejected and leaves the rotation as it was
+        for degrees in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(
+                session.rotate_bone_axis(BoneId::LeftElbow, x, degrees),
+                Err(EditorError::InvalidArgument)
+            );
+        }
+        assert!((session.bone_local_euler(BoneId::LeftElbow).x - 60.0).abs() < 1e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_spine_swing_and_twist_edit_independently() {
+        let mut session = EditorSession::empty("spine".to_string());
+        let bone = BoneId::Spine1;
+        assert_eq!(twist_axis(bone), Vec3::Y);
+
+        session.set_bone_twist(bone, 40.0).unwrap();
+        session.set_bone_swing(bone, Vec3::X, 25.0).unwrap();
+        let (swing, twist) = session.bone_swing_twist(bone);
+        assert!((twist - 40.0).abs() < 1e-3);
+        assert!(swing.abs_diff_eq(Quat::from_rotation_x(25f32.to_radians()), 1e-5));
+
+        // Changing the twist leaves the bend alone, and the other way round
+        session.set_bone_twist(bone, -15.0).unwrap();
+        let (swing_after, twist) = session.bone_swing_twist(bone);
+        assert!((twist + 15.0).abs() < 1e-3);
+        assert!(swing_after.abs_diff_eq(swing, 1e-5));
+        session.set_bone_swing(bone, Vec3::Z, 10.0).unwrap();
+        assert!((session.bone_swing_twist(bone).1 + 15.0).abs() < 1e-3);
+
+        // A swing about the twist axis itself is meaningless
+        assert_eq!(
+            session.set_bone_swing(bone, Vec3::Y, 10.0),
+            Err(EditorError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_drag_falloff_rotates_parents_less() {
+        let mut session = EditorSession::empty("falloff".to_string());
+        let angle = |session: &EditorSession, bone: BoneId| {
+            session.current_pose().local_rotations[bone.index()]
+                .to_axis_angle()
+                .1
+                .to_degrees()
+        };
+
+        // Strength 0 keeps the rotation on the dragged bone
+        session
+            .rotate_bone_axis(BoneId::LeftElbow, Vec3::X, 40.0)
+            .unwrap();
+        assert!(angle(&session, BoneId::LeftShoulder).abs() < 1e-3);
+
+        let mut session = EditorSession::empty("falloff".to_string());
+        session.set_drag_falloff(0.5).unwrap();
+        session
+            .rotate_bone_axis(BoneId::LeftElbow, Vec3::X, 40.0)
+            .unwrap();
+        let elbow = angle(&session, BoneId::LeftElbow);
+        let shoulder = angle(&session, BoneId::LeftShoulder);
+        let collar = angle(&session, BoneId::LeftCollar);
+        assert!((elbow - 40.0).abs() < 1e-2, "elbow {elbow}");
+        assert!((shoulder - 20.0).abs() < 1e-2, "shoulder {shoulder}");
+        assert!((collar - 10.0).abs() < 1e-2, "collar {collar}");
+        assert!(angle(&session, BoneId::Pelvis).abs() < 1e-3);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_set_pose_rotations_matches_per_bone() {
+        let rotations: Vec<Quat> = BoneId::ALL
+            .iter()
+            .map(|bone| {
+                Quat::from_euler(glam::EulerRot::XYZ, 0.1, bone.index() as f32 * 0.05, -0.2)
+            })
+            .collect();
+        let flat: Vec<f32> = rotations.iter().flat_map(|q| q.to_array()).collect();
+
+        let mut session = EditorSession::empty("batch".to_string());
+        assert!(session.set_pose_rotations(&flat).is_ok());
+        assert!(session.set_pose_root(Vec3::new(0.0, 0.9, 0.1)).is_ok());
+        assert_eq!(
+            session.set_pose_rotations(&flat[..8]),
+            Err(EditorError::InvalidArgument)
+        );
+        // A zero or NaN quaternion rejects the call and leaves the pose untouched
+        for bad in [
+            [0.0; 4],
+            [f32::NAN, 0.0, 0.0, 1.0],
+            [0.0, f32::INFINITY, 0.0, 1.0],
+        ] {
+            let mut invalid = flat.clone();
+            invalid[4 * BoneId::LeftElbow.index()..][..4].copy_from_slice(&bad);
+            assert_eq!(
+                session.set_pose_rotations(&invalid),
+                Err(EditorError::InvalidArgument)
+            );
+        }
+        assert!(session.current_pose().is_finite());
+
+        let mut expected = RotationPose::bind_pose().with_root_position(Vec3::new(0.0, 0.9, 0.1));
+        for (bone, rotation) in BoneId::ALL.iter().zip(&rotations) {
+            expected = expected.with_rotation(*bone, *rotation);
+        }
+
+        let pose = session.current_pose();
+        for bone in BoneId::ALL {
+            assert!(pose.local_rotations[bone.index()]
+                .abs_diff_eq(expected.local_rotations[bone.index()], 1e-6));
+            assert!(pose
+                .get_position(bone)
+                .abs_diff_eq(expected.get_position(bone), 1e-5));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_set_bone_rotation_clamps_to_limits() {
+        let mut session = EditorSession::empty("limits".to_string());
+        let bent_back = EulerAngles {
+            x: -40.0,
+            y: 0.0,
+            z: 0.0,
+        };
+
+        assert!(session
+            .set_bone_rotation(BoneId::LeftKnee, bent_back)
+            .is_ok());
+        assert!(session.bone_local_euler(BoneId::LeftKnee).x.abs() < 1e-3);
+
+        assert!(session.set_bone_rotation(BoneId::Spine1, bent_back).is_ok());
+        assert!((session.bone_local_euler(BoneId::Spine1).x + 40.0).abs() < 1e-3);
+
+        // Overridden and freed limits
+        assert!(session
+            .set_joint_limit(BoneId::LeftKnee, 0, Some((-10.0, 10.0)))
+            .is_ok());
+        session
+            .set_bone_rotation(BoneId::LeftKnee, bent_back)
+            .unwrap();
+        assert!((session.bone_local_euler(BoneId::LeftKnee).x + 10.0).abs() < 1e-3);
+        session.set_joint_limit(BoneId::LeftKnee, 0, None).unwrap();
+        session
+            .set_bone_rotation(BoneId::LeftKnee, bent_back)
+            .unwrap();
+        assert!((session.bone_local_euler(BoneId::LeftKnee).x + 40.0).abs() < 1e-3);
+        assert_eq!(
+            session.set_joint_limit(BoneId::LeftKnee, 0, Some((5.0, -5.0))),
+            Err(EditorError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_recording_skips_static_poses() {
+        let mut session = EditorSession::empty("record".to_string());
+
+        // Static pose: only the first tick records
+        session.start_recording(0.1, 5.0).unwrap();
+        for i in 0..10 {
+            session.record_tick(i as f32 * 0.1).unwrap();
+        }
+        session.stop_recording().unwrap();
+        assert_eq!(session.clip.keyframes.len(), 1);
+
+        // Turning 3 degrees per tick records every other tick (6 > 5 degrees)
+        session.start_recording(0.1, 5.0).unwrap();
+        for i in 0..10 {
+            session.record_tick(i as f32 * 0.1).unwrap();
+            session
+                .rotate_bone_axis(BoneId::LeftElbow, Vec3::Z, 3.0)
+                .unwrap();
+        }
+        let take = session.recording.clone().unwrap().take;
+        session.stop_recording().unwrap();
+
+        assert!(take.len() > 1 && take.len() < 10);
+        for pair in take.windows(2) {
+            assert!(pair[0].pose.diff(&pair[1].pose).max_angle() > 5.0);
+            assert!(pair[1].time > pair[0].time);
+        }
+        assert_eq!(session.clip.keyframes.len(), take.len());
+        assert_eq!(session.record_tick(2.0), Err(EditorError::InvalidArgument));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_recording_keeps_keyframes_outside_take() {
+        let mut session = EditorSession::empty("merge".to_string());
+        let authored = |time: f32, angle: f32| RotationKeyframe {
+            time,
+            pose: RotationPose::bind_pose()
+                .with_rotation(BoneId::RightElbow, Quat::from_rotation_z(angle)),
+            tcb: None,
+        };
+        session.clip.duration = 4.0;
+        session.clip.keyframes = vec![
+            authored(0.0, 0.1),
+            authored(1.0, 0.2),
+            authored(1.2, 0.3),
+            authored(3.0, 0.4),
+        ];
+
+        // Record from the keyframe at 1.0 for about half a second
+        session.set_keyframe_index(1).unwrap();
+        session.start_recording(0.1, 1.0).unwrap();
+        for i in 0..6 {
+            session.record_tick(10.0 + i as f32 * 0.1).unwrap();
+            session
+                .rotate_bone_axis(BoneId::LeftElbow, Vec3::Z, 3.0)
+                .unwrap();
+        }
+        let take = session.recording.clone().unwrap().take;
+        let end = 1.0 + take.last().unwrap().time;
+        session.stop_recording().unwrap();
+
+        let keyframes = &session.clip.keyframes;
+        assert_eq!(keyframes.len(), take.len() + 2);
+        // Authored keyframes outside 1.0..=end survive, the one inside is replaced
+        assert_eq!(keyframes[0].time, 0.0);
+        assert!(keyframes[0].approx_eq(&authored(0.0, 0.1), 1e-6));
+        let last = keyframes.last().unwrap();
+        assert!(last.approx_eq(&authored(3.0, 0.4), 1e-6));
+        assert!(!keyframes.iter().any(|kf| kf.time == 1.2));
+        for (kf, recorded) in keyframes[1..=take.len()].iter().zip(&take) {
+            assert!((kf.time - (1.0 + recorded.time)).abs() < 1e-6);
+        }
+        assert_eq!(session.clip.duration, 4.0);
+        assert_eq!(session.keyframe_index, take.len());
+        assert!((keyframes[session.keyframe_index].time - end).abs() < 1e-6);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_insert_inbetweens() {
+        let mut session = EditorSession::empty("inbetween".to_string());
+        session.clip.keyframes.push(RotationKeyframe {
+            time: 1.0,
+            pose: RotationPose::bind_pose()
+                .with_rotation(BoneId::RightShoulder, Quat::from_rotation_z(1.2)),
+            tcb: None,
+        });
+        let (from, to) = (
+            session.clip.keyframes[0].clone(),
+            session.clip.keyframes[1].clone(),
+        );
+
+        assert!(session.insert_inbetweens(0, 3, Easing::EaseInOut).is_ok());
+        assert_eq!(
+            session.insert_inbetweens(4, 1, Easing::Linear),
+            Err(EditorError::IndexOutOfRange)
+        );
+        assert_eq!(session.clip.keyframes.len(), 5);
+
+        for (i, kf) in session.clip.keyframes[1..4].iter().enumerate() {
+            let fraction = Easing::EaseInOut.apply((i + 1) as f32 / 4.0);
+            assert!(kf.time > from.time && kf.time < to.time);
+            assert!((kf.time - fraction).abs() < 1e-6);
+
+            let expected = RotationPose::lerp(&from.pose, &to.pose, fraction);
+            let bone = BoneId::RightShoulder.index();
+            assert!(kf.pose.local_rotations[bone].abs_diff_eq(expected.local_rotations[bone], 1e-6));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_interpolated_matrices_endpoints() {
+        let mut session = EditorSession::empty("preview".to_string());
+        session.clip.keyframes.push(RotationKeyframe {
+            time: 0.5,
+            pose: RotationPose::bind_pose()
+                .with_rotation(BoneId::LeftHip, Quat::from_rotation_x(-0.9))
+                .with_root_position(Vec3::new(0.0, 0.8, 0.2)),
+            tcb: None,
+        });
+        let matrices_a = session.clip.keyframes[0].pose.compute_bone_matrices();
+        let matrices_b = session.clip.keyframes[1].pose.compute_bone_matrices();
+
+        let at_a = session.interpolated_matrices(0, 1, 0.0).unwrap();
+        let at_b = session.interpolated_matrices(0, 1, 1.0).unwrap();
+        for i in 0..RENDER_BONE_COUNT {
+            assert!(
+                at_a[i].abs_diff_eq(matrices_a[i], 1e-5),
+                "Bone {} at t=0",
+                i
+            );
+            assert!(
+                at_b[i].abs_diff_eq(matrices_b[i], 1e-5),
+                "Bone {} at t=1",
+                i
+            );
+        }
+        assert!(session.interpolated_matrices(0, 2, 0.5).is_none());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_drag_joint_along_x_axis() {
+        // Bend the elbow so the arm isn't fully straight (IK can't shorten a straight chain)
+        let mut session = EditorSession::empty("drag".to_string());
+        session
+            .rotate_bone_axis(BoneId::LeftElbow, Vec3::Y, 50.0)
+            .unwrap();
+
+        let viewport = Vec2::new(800.0, 600.0);
+        let view = Mat4::look_at_rh(Vec3::new(0.0, 0.3, 3.0), Vec3::new(0.0, 0.3, 0.0), Vec3::Y);
+        let proj = crate::camera::projection_matrix(viewport.x / viewport.y, 0.1, 100.0);
+        let view_proj = proj * view;
+
+        let before = session.current_pose().get_position(BoneId::LeftWrist);
+        let screen_before = world_to_screen(before, view_proj, viewport).unwrap();
+
+        let x = axis_from_index(0).unwrap();
+        assert!(session
+            .drag_joint_axis(
+                BoneId::LeftWrist,
+                Vec2::new(-20.0, 15.0),
+                x,
+                view_proj,
+                viewport
+            )
+            .is_ok());
+
+        let after = session.current_pose().get_position(BoneId::LeftWrist);
+        let tolerance = 2.0 * RotationPose::IK_TOLERANCE;
+        assert!(
+            (after.y - before.y).abs() < tolerance,
+            "Y moved: {} -> {}",
+            before.y,
+            after.y
+        );
+        assert!(
+            (after.z - before.z).abs() < tolerance,
+            "Z moved: {} -> {}",
+            before.z,
+            after.z
+        );
+        assert!(after.x < before.x - 0.01, "X should follow the cursor");
+
+        // Only the horizontal part of the drag applies along X
+        let screen_after = world_to_screen(after, view_proj, viewport).unwrap();
+        assert!(
+            (screen_after.x - screen_before.x + 20.0).abs() < 1.0,
+            "Moved {} px on screen",
+            screen_after.x - screen_before.x
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_pose_library_save_and_apply() {
+        let mut library = PoseLibrary::new();
+        let mut session = EditorSession::empty("poses".to_string());
+        session
+            .set_bone_rotation(
+                BoneId::Spine2,
+                EulerAngles {
+                    x: 20.0,
+                    y: -10.0,
+                    z: 5.0,
+                },
+            )
+            .unwrap();
+        session.set_pose_root(Vec3::new(0.2, 0.9, -0.1)).unwrap();
+        let saved = session.current_pose();
+        library.save("lean", saved.clone());
+
+        session.apply_pose(library.get(A_POSE).unwrap()).unwrap();
+        session.apply_pose(library.get("lean").unwrap()).unwrap();
+        let restored = session.current_pose();
+        assert_eq!(restored.root_position, saved.root_position);
+        assert_eq!(restored.local_rotations, saved.local_rotations);
+        assert!(library.get("missing").is_none());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_a_pose_lowers_arms() {
+        let library = PoseLibrary::new();
+        let t_pose = library.get(T_POSE).unwrap();
+        let a_pose = library.get(A_POSE).unwrap();
+
+        for (shoulder, hand) in [
+            (BoneId::LeftShoulder, BoneId::LeftWrist),
+            (BoneId::RightShoulder, BoneId::RightWrist),
+        ] {
+            // Shoulders stay put, wrists drop well below them
+            let drop = t_pose.get_position(hand).y - a_pose.get_position(hand).y;
+            assert!(drop > 0.2, "{:?} dropped only {}", hand, drop);
+            assert!(a_pose
+                .get_position(shoulder)
+                .abs_diff_eq(t_pose.get_position(shoulder), 1e-6));
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_editor_error_paths() {
+        let mut sessions = EditorSessions::new();
+        let handle = sessions.create(EditorSession::empty("errors".to_string()));
+
+        // Invalid handle
+        let result = sessions.update(handle + 1, |session| session.set_keyframe_index(0));
+        assert_eq!(result, Err(EditorError::InvalidHandle));
+        assert_eq!(sessions.last_error(), Some(EditorError::InvalidHandle));
+
+        // Success clears the last error
+        assert!(sessions
+            .update(handle, |session| session.set_keyframe_index(0))
+            .is_ok());
+        assert_eq!(sessions.last_error(), None);
+
+        // Index out of range
+        let _ = sessions.update(handle, |session| session.set_keyframe_index(5));
+        assert_eq!(sessions.last_error(), Some(EditorError::IndexOutOfRange));
+        let _ = sessions.record(bone_arg(BoneId::COUNT));
+        assert_eq!(sessions.last_error(), Some(EditorError::IndexOutOfRange));
+
+        // Invalid argument
+        let _ = sessions.record(axis_arg(3));
+        assert_eq!(sessions.last_error(), Some(EditorError::InvalidArgument));
+        let _ = sessions.update(handle, |session| session.set_ik_damping(1.0));
+        assert_eq!(sessions.last_error(), Some(EditorError::InvalidArgument));
+        let _ = sessions.update(handle, |session| session.set_drag_falloff(-0.5));
+        assert_eq!(sessions.last_error(), Some(EditorError::InvalidArgument));
+
+        // No keyframe
+        sessions.get_mut(handle).unwrap().clip.keyframes.clear();
+        let _ = sessions.update(handle, |session| {
+            session.rotate_bone_axis(BoneId::Head, Vec3::X, 10.0)
+        });
+        assert_eq!(sessions.last_error(), Some(EditorError::NoKeyframe));
+        assert_eq!(EditorError::NoKeyframe.to_string(), "no keyframe to edit");
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_bone_selection() {
+        let mut session = EditorSession::empty("selection".to_string());
+        assert_eq!(session.bone_or_selected(-1), Err(EditorError::NoSelection));
+
+        session.select_bone(joint_from_index(BoneId::LeftElbow.index() as i32));
+        assert_eq!(session.selected_bone, Some(BoneId::LeftElbow));
+        assert_eq!(session.bone_or_selected(-1), Ok(BoneId::LeftElbow));
+        assert_eq!(session.bone_or_selected(0), Ok(BoneId::Pelvis));
+        assert_eq!(
+            session.bone_or_selected(-2),
+            Err(EditorError::IndexOutOfRange)
+        );
+
+        // Editing with -1 targets the selection
+        let bone = session.bone_or_selected(-1).unwrap();
+        session
+            .set_bone_rotation(
+                bone,
+                EulerAngles {
+                    x: 30.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            )
+            .unwrap();
+        let bind = RotationPose::bind_pose();
+        let elbow = BoneId::LeftElbow.index();
+        assert_ne!(
+            session.current_pose().local_rotations[elbow],
+            bind.local_rotations[elbow]
+        );
+
+        session.select_bone(joint_from_index(-1));
+        assert_eq!(session.selected_bone, None);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_joint_render_index_in_range() {
+        for index in JOINT_RENDER_INDEX {
+            assert!(index < RENDER_BONE_COUNT);
+        }
+    }
+}
+
+// App methods for the editor
+#[cfg(target_arch = "wasm32")]
+use crate::bone::{AnimationId, BoneMask};
+#[cfg(target_arch = "wasm32")]
+use crate::state::App;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl App {
+    /// Open an editor session on a copy of a loaded clip
+    /// Falls back to a single bind pose keyframe if the clip is not loaded.
+    /// Returns the session handle.
+    pub fn create_editor_session(&mut self, id: AnimationId) -> u32 {
+        self.state.engine.create_editor_session(id)
+    }
+
+    /// Close an editor session
+    pub fn destroy_editor_session(&mut self, handle: u32) {
+        let editor = &mut self.state.engine.editor;
+        let result = match editor.destroy(handle) {
+            true => Ok(()),
+            false => Err(EditorError::InvalidHandle),
+        };
+        let _ = editor.record(result);
+    }
+
+    /// Get a description of why the last editor operation failed
+    /// Returns an empty string if it succeeded.
+    pub fn last_editor_error(&self) -> String {
+        self.state
+            .engine
+            .editor
+            .last_error()
+            .map(|error| error.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Select which keyframe the session shows and edits
+    pub fn set_keyframe_index(&mut self, handle: u32, index: usize) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_keyframe_index(index));
+    }
+
+    /// Step to the next keyframe; returns the new index, or -1 on error
+    pub fn next_keyframe(&mut self, handle: u32) -> i32 {
+        self.state
+            .engine
+            .editor
+            .update(handle, |session| session.step_keyframe(1))
+            .map_or(-1, |index| index as i32)
+    }
+
+    /// Step to the previous keyframe; returns the new index, or -1 on error
+    pub fn prev_keyframe(&mut self, handle: u32) -> i32 {
+        self.state
+            .engine
+            .editor
+            .update(handle, |session| session.step_keyframe(-1))
+            .map_or(-1, |index| index as i32)
+    }
+
+    /// Jump to the first keyframe; returns the new index, or -1 on error
+    pub fn go_to_first(&mut self, handle: u32) -> i32 {
+        self.state
+            .engine
+            .editor
+            .update(handle, EditorSession::go_to_first)
+            .map_or(-1, |index| index as i32)
+    }
+
+    /// Jump to the last keyframe; returns the new index, or -1 on error
+    pub fn go_to_last(&mut self, handle: u32) -> i32 {
+        self.state
+            .engine
+            .editor
+            .update(handle, EditorSession::go_to_last)
+            .map_or(-1, |index| index as i32)
+    }
+
+    /// Make next/previous wrap around the clip ends instead of stopping
+    pub fn set_keyframe_wrap(&mut self, handle: u32, wrap: bool) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.wrap_navigation = wrap;
+            Ok(())
+        });
+    }
+
+    /// Get the world positions of all joints in the current keyframe
+    /// Returns [x, y, z] per joint, ordered by bone index.
+    pub fn get_joint_positions(&self, handle: u32) -> Vec<f32> {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .map(|session| joint_positions(&session.current_pose()))
+            .unwrap_or_default()
+    }
+
+    /// Get the world rotations of all bones in the current keyframe
+    /// Returns [x, y, z, w] per bone, ordered by bone index.
+    pub fn get_world_rotations(&self, handle: u32) -> Vec<f32> {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .map(|session| {
+                let rotations = session.current_pose().world_rotations_array();
+                rotations.iter().flat_map(|q| q.to_array()).collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get how far the current keyframe sinks into the floor (0 if clear)
+    /// Uses the configured bone radius, like the floor constraint during playback.
+    pub fn get_floor_penetration(&self, handle: u32) -> f32 {
+        let engine = &self.state.engine;
+        engine.editor.get(handle).map_or(0.0, |session| {
+            session
+                .current_pose()
+                .floor_penetration_with_radius(engine.radii.bone)
+        })
+    }
+
+    /// Get a capsule enclosing the current keyframe's skeleton
+    /// Returns [start x, y, z, end x, y, z, radius], or an empty array for an invalid handle.
+    pub fn get_bounding_capsule(&self, handle: u32) -> Vec<f32> {
+        let Some(session) = self.state.engine.editor.get(handle) else {
+            return Vec::new();
+        };
+        let (start, end, radius) = session.current_pose().bounding_capsule();
+        vec![start.x, start.y, start.z, end.x, end.y, end.z, radius]
+    }
+
+    /// Cast a world-space ray at the current keyframe's skeleton
+    /// Returns [bone index, distance] of the nearest hit, or an empty array on a
+    /// miss or an invalid handle. For picking without a screen projection (e.g. VR).
+    #[allow(clippy::too_many_arguments)]
+    pub fn raycast_skeleton(
+        &self,
+        handle: u32,
+        ox: f32,
+        oy: f32,
+        oz: f32,
+        dx: f32,
+        dy: f32,
+        dz: f32,
+    ) -> Vec<f32> {
+        let Some(session) = self.state.engine.editor.get(handle) else {
+            return Vec::new();
+        };
+        session
+            .current_pose()
+            .raycast(Vec3::new(ox, oy, oz), Vec3::new(dx, dy, dz))
+            .map(|(bone, distance)| vec![bone.index() as f32, distance])
+            .unwrap_or_default()
+    }
+
+    /// Get the sphere a joint can be dragged within: [center x, y, z, radius]
+    /// Returns an empty array for an invalid handle or joint index.
+    pub fn get_reach_extent(&self, handle: u32, joint_index: usize) -> Vec<f32> {
+        let (Some(session), Ok(joint)) =
+            (self.state.engine.editor.get(handle), bone_arg(joint_index))
+        else {
+            return Vec::new();
+        };
+        let (center, radius) = session.current_pose().reach_extent(joint);
+        vec![center.x, center.y, center.z, radius]
+    }
+
+    /// Get the path a joint traces over the session's clip: [x, y, z] per sample
+    /// Returns an empty array for an invalid handle or joint index.
+    pub fn get_trajectory(&self, handle: u32, joint_index: usize, samples: usize) -> Vec<f32> {
+        let (Some(session), Ok(joint)) =
+            (self.state.engine.editor.get(handle), bone_arg(joint_index))
+        else {
+            return Vec::new();
+        };
+        session
+            .clip
+            .end_effector_trajectory(joint, samples)
+            .into_iter()
+            .flat_map(|point| point.to_array())
+            .collect()
+    }
+
+    /// Get which bones of the current keyframe have stale FK results
+    /// Bit i is set when the bone with index i is dirty; 0 for an invalid handle.
+    pub fn get_pose_dirty_mask(&self, handle: u32) -> u32 {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .map(|session| session.current_pose().dirty_mask())
+            .unwrap_or(0)
+    }
+
+    /// Upload the session's current keyframe pose to the GPU
+    /// Also refreshes the onion-skin ghosts when enabled.
+    pub fn update_skeleton_from_session(&mut self, handle: u32) {
+        if let Some(session) = self.state.engine.editor.get(handle) {
+            let frames = session.onion_skin_frames();
+            self.update_bone_uniforms(&frames.current);
+            self.update_ghost_uniforms(&frames.ghosts());
+        }
+    }
+
+    /// Upload an interpolation between two keyframes without modifying the clip
+    /// Used for scrubbing; call `update_skeleton_from_session` to go back.
+    pub fn preview_interpolated_pose(
+        &mut self,
+        handle: u32,
+        index_a: usize,
+        index_b: usize,
+        t: f32,
+    ) {
+        if let Some(matrices) = self
+            .state
+            .engine
+            .editor
+            .get(handle)
+            .and_then(|session| session.interpolated_matrices(index_a, index_b, t))
+        {
+            self.update_bone_uniforms(&matrices);
+        }
+    }
+
+    /// Upload the current keyframe with only one bone's rotation applied
+    /// For debugging a single channel; call `update_skeleton_from_session` to go back.
+    pub fn preview_solo_bone(&mut self, handle: u32, bone_index: usize) {
+        let editor = &mut self.state.engine.editor;
+        let Ok(bone) = editor.record(bone_arg(bone_index)) else {
+            return;
+        };
+        if let Some(matrices) = editor
+            .get(handle)
+            .and_then(|session| session.solo_bone_matrices(bone))
+        {
+            self.update_bone_uniforms(&matrices);
+        }
+    }
+
+    /// Show translucent ghosts of the previous and next keyframes
+    pub fn set_onion_skin(&mut self, handle: u32, enabled: bool) {
+        if let Some(session) = self.state.engine.editor.get_mut(handle) {
+            session.onion_skin = enabled;
+            self.update_skeleton_from_session(handle);
+        }
+    }
+
+    /// Remove quaternion sign flips between keyframes of the session's clip
+    pub fn enforce_clip_continuity(&mut self, handle: u32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip.enforce_continuity();
+            Ok(())
+        });
+    }
+
+    /// Delete keyframe `index` from the session's clip (the last one is kept)
+    pub fn delete_keyframe(&mut self, handle: u32, index: usize) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.delete_keyframe(index));
+    }
+
+    /// Remove consecutive keyframes that repeat the previous one within `tol`
+    /// Returns the number removed (0 on error).
+    pub fn dedup_keyframes(&mut self, handle: u32, tol: f32) -> u32 {
+        self.state
+            .engine
+            .editor
+            .update(handle, |session| session.dedup_keyframes(tol))
+            .map_or(0, |removed| removed as u32)
+    }
+
+    /// Let hand drags bend the spine: 0 = arm only, up to 1 = spine as free as the arm
+    pub fn set_spine_ik_influence(&mut self, handle: u32, influence: f32) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_spine_ik_influence(influence));
+    }
+
+    /// Damp the IK solve of joint drags against jitter: 0 = off, up to (not including) 1
+    pub fn set_ik_damping(&mut self, handle: u32, factor: f32) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_ik_damping(factor));
+    }
+
+    /// Spread FK rotations to parent bones: 0 = bone only, up to (not including) 1
+    pub fn set_drag_falloff(&mut self, handle: u32, strength: f32) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_drag_falloff(strength));
+    }
+
+    /// Fit the clip duration to the last keyframe after every deletion
+    pub fn set_auto_fit_duration(&mut self, handle: u32, enabled: bool) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.auto_fit_duration = enabled;
+            Ok(())
+        });
+    }
+
+    /// Set the clip duration to the last keyframe's time, removing dead loop time
+    pub fn fit_clip_duration(&mut self, handle: u32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip.fit_duration();
+            Ok(())
+        });
+    }
+
+    /// Insert `count` eased inbetweens between keyframe `from_index` and the next one
+    pub fn insert_inbetweens(
+        &mut self,
+        handle: u32,
+        from_index: usize,
+        count: usize,
+        easing: Easing,
+    ) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.insert_inbetweens(from_index, count, easing)
+        });
+    }
+
+    /// Drag a joint of the current keyframe along a world axis (0 = X, 1 = Y, 2 = Z)
+    /// `dx`, `dy` are the cursor delta in pixels; `view` and `proj` are column-major
+    /// matrices as returned by `get_current_view_matrix` / `get_current_projection_matrix`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn drag_joint_axis(
+        &mut self,
+        handle: u32,
+        joint_index: usize,
+        dx: f32,
+        dy: f32,
+        axis: u32,
+        view: &[f32],
+        proj: &[f32],
+        width: f32,
+        height: f32,
+    ) {
+        let editor = &mut self.state.engine.editor;
+        let args = bone_arg(joint_index).and_then(|joint| Ok((joint, axis_arg(axis)?)));
+        let Ok((joint, axis)) = editor.record(args) else {
+            return;
+        };
+        if view.len() != 16 || proj.len() != 16 {
+            let _ = editor.record::<()>(Err(EditorError::InvalidArgument));
+            return;
+        }
+        let view_proj = Mat4::from_cols_slice(proj) * Mat4::from_cols_slice(view);
+        let _ = editor.update(handle, |session| {
+            session.drag_joint_axis(
+                joint,
+                Vec2::new(dx, dy),
+                axis,
+                view_proj,
+                Vec2::new(width, height),
+            )
+        });
+    }
+
+    /// Drag the root along a world axis (0 = X, 1 = Y, 2 = Z) with the feet planted
+    /// Arguments as for `drag_joint_axis`; the legs are re-solved to keep the feet still.
+    #[allow(clippy::too_many_arguments)]
+    pub fn drag_root_with_planted_feet(
+        &mut self,
+        handle: u32,
+        dx: f32,
+        dy: f32,
+        axis: u32,
+        view: &[f32],
+        proj: &[f32],
+        width: f32,
+        height: f32,
+    ) {
+        let editor = &mut self.state.engine.editor;
+        let Ok(axis) = editor.record(axis_arg(axis)) else {
+            return;
+        };
+        if view.len() != 16 || proj.len() != 16 {
+            let _ = editor.record::<()>(Err(EditorError::InvalidArgument));
+            return;
+        }
+        let view_proj = Mat4::from_cols_slice(proj) * Mat4::from_cols_slice(view);
+        let _ = editor.update(handle, |session| {
+            session.drag_root_with_planted_feet(
+                Vec2::new(dx, dy),
+                axis,
+                view_proj,
+                Vec2::new(width, height),
+            )
+        });
+    }
+
+    /// Rotate a bone of the current keyframe about a local axis (0 = X, 1 = Y, 2 = Z)
+    pub fn rotate_bone_axis(&mut self, handle: u32, bone_index: usize, axis: u32, degrees: f32) {
+        let editor = &mut self.state.engine.editor;
+        let args = bone_arg(bone_index).and_then(|bone| Ok((bone, axis_arg(axis)?)));
+        let Ok((bone, axis)) = editor.record(args) else {
+            return;
+        };
+        let _ = editor.update(handle, |session| {
+            session.rotate_bone_axis(bone, axis, degrees)
+        });
+    }
+
+    /// Overwrite all local rotations of the current keyframe in one call
+    /// Takes one [x, y, z, w] quaternion per bone (4 * bone count floats).
+    pub fn set_pose_rotations(&mut self, handle: u32, rotations: &[f32]) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_pose_rotations(rotations));
+    }
+
+    /// Move the root of the session's current keyframe
+    pub fn set_pose_root(&mut self, handle: u32, x: f32, y: f32, z: f32) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.set_pose_root(Vec3::new(x, y, z)));
+    }
+
+    /// Save the session's current keyframe pose in the pose library under `name`
+    pub fn save_pose(&mut self, handle: u32, name: &str) {
+        let editor = &mut self.state.engine.editor;
+        let result = editor
+            .get(handle)
+            .map(EditorSession::current_pose)
+            .ok_or(EditorError::InvalidHandle);
+        if let Ok(pose) = editor.record(result) {
+            self.state.engine.pose_library.save(name, pose);
+        }
+    }
+
+    /// Write a named pose from the pose library into the session's current keyframe
+    /// Built-in presets are "t_pose" and "a_pose".
+    pub fn apply_pose(&mut self, handle: u32, name: &str) {
+        let editor = &mut self.state.engine.editor;
+        let Ok(pose) = editor.record(
+            self.state
+                .engine
+                .pose_library
+                .get(name)
+                .ok_or(EditorError::UnknownPose),
+        ) else {
+            return;
+        };
+        let _ = editor.update(handle, |session| session.apply_pose(pose));
+    }
+
+    /// Names of all poses in the pose library
+    pub fn pose_names(&self) -> Vec<String> {
+        self.state.engine.pose_library.names()
+    }
+
+    /// Set a bone's local rotation in the current keyframe from XYZ Euler degrees
+    /// Clamped to the bone's joint limits (see `set_joint_limits`).
+    /// A `bone_index` of -1 edits the selected bone (see `select_bone`).
+    pub fn set_bone_rotation(&mut self, handle: u32, bone_index: i32, x: f32, y: f32, z: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            let bone = session.bone_or_selected(bone_index)?;
+            session.set_bone_rotation(bone, EulerAngles { x, y, z })
+        });
+    }
+
+    /// Set the root (pelvis) rotation of the current keyframe from XYZ Euler degrees
+    /// Turns the whole body about the root; root position and playback facing
+    /// are separate and left unchanged. Read back with `get_bone_local_euler(handle, 0)`.
+    pub fn set_root_rotation(&mut self, handle: u32, x: f32, y: f32, z: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.set_root_rotation(EulerAngles { x, y, z })
+        });
+    }
+
+    /// Set the swing (bend) of a bone's local rotation, keeping its twist
+    /// Turns `degrees` about the local axis (x, y, z), made perpendicular to the
+    /// bone's twist axis. A `bone_index` of -1 edits the selected bone.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_bone_swing(
+        &mut self,
+        handle: u32,
+        bone_index: i32,
+        x: f32,
+        y: f32,
+        z: f32,
+        degrees: f32,
+    ) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            let bone = session.bone_or_selected(bone_index)?;
+            session.set_bone_swing(bone, Vec3::new(x, y, z), degrees)
+        });
+    }
+
+    /// Set the twist (roll along the bone) of a bone's local rotation in degrees,
+    /// keeping its swing. A `bone_index` of -1 edits the selected bone.
+    pub fn set_bone_twist(&mut self, handle: u32, bone_index: i32, degrees: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            let bone = session.bone_or_selected(bone_index)?;
+            session.set_bone_twist(bone, degrees)
+        });
+    }
+
+    /// Select the bone edited by the gizmo and by bone edits given index -1
+    /// -1 clears the selection.
+    pub fn select_bone(&mut self, handle: u32, bone_index: i32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            let bone = match bone_index {
+                -1 => None,
+                _ => Some(session.bone_or_selected(bone_index)?),
+            };
+            session.select_bone(bone);
+            Ok(())
+        });
+    }
+
+    /// Get the selected bone index, or -1 if none is selected
+    pub fn get_selected_bone(&self, handle: u32) -> i32 {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .and_then(|session| session.selected_bone)
+            .map_or(-1, |bone| bone.index() as i32)
+    }
+
+    /// Override a bone's joint limit on one axis (0 = X, 1 = Y, 2 = Z), in degrees
+    /// Pass NaN for `min` or `max` to leave the axis free.
+    pub fn set_joint_limits(
+        &mut self,
+        handle: u32,
+        bone_index: usize,
+        axis: usize,
+        min: f32,
+        max: f32,
+    ) {
+        let editor = &mut self.state.engine.editor;
+        let Ok(bone) = editor.record(bone_arg(bone_index)) else {
+            return;
+        };
+        let limit = (!min.is_nan() && !max.is_nan()).then_some((min, max));
+        let _ = editor.update(handle, |session| session.set_joint_limit(bone, axis, limit));
+    }
+
+    /// Start recording the session's live pose every `interval_ms`
+    /// Keyframes are only added when a bone turned more than `threshold` degrees.
+    pub fn start_recording(&mut self, handle: u32, interval_ms: f32, threshold: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.start_recording(interval_ms / 1000.0, threshold)
+        });
+    }
+
+    /// Feed the recorder the current time in milliseconds (call each frame)
+    /// Returns true if a keyframe was recorded.
+    pub fn record_tick(&mut self, handle: u32, time_ms: f32) -> bool {
+        self.state
+            .engine
+            .editor
+            .update(handle, |session| session.record_tick(time_ms / 1000.0))
+            .unwrap_or(false)
+    }
+
+    /// Stop recording; the take replaces the keyframes in its time range
+    pub fn stop_recording(&mut self, handle: u32) {
+        let _ = self
+            .state
+            .engine
+            .editor
+            .update(handle, |session| session.stop_recording());
+    }
+
+    /// Get a bone's local rotation in the current keyframe as [x, y, z] Euler degrees
+    pub fn get_bone_local_euler(&self, handle: u32, bone_index: usize) -> Vec<f32> {
+        match (
+            self.state.engine.editor.get(handle),
+            BoneId::from_index(bone_index),
+        ) {
+            (Some(session), Some(bone)) => {
+                let euler = session.bone_local_euler(bone);
+                vec![euler.x, euler.y, euler.z]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get a bone's local rotation in the current keyframe as swing and twist
+    /// Returns [x, y, z, w] of the swing quaternion followed by the twist in degrees.
+    pub fn get_bone_swing_twist(&self, handle: u32, bone_index: usize) -> Vec<f32> {
+        match (
+            self.state.engine.editor.get(handle),
+            BoneId::from_index(bone_index),
+        ) {
+            (Some(session), Some(bone)) => {
+                let (swing, twist) = session.bone_swing_twist(bone);
+                vec![swing.x, swing.y, swing.z, swing.w, twist]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get a stable hash of the session's clip contents
+    /// Compare against a hash taken at save time to detect unsaved changes.
+    pub fn get_clip_hash(&self, handle: u32) -> u64 {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .map(|session| session.clip.content_hash())
+            .unwrap_or(0)
+    }
+
+    /// Mirror the session's clip left-to-right
+    pub fn mirror_clip(&mut self, handle: u32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip = session.clip.mirror();
+            Ok(())
+        });
+    }
+
+    /// Convert the session's clip from A-pose-authored arms to this T-pose skeleton
+    /// `arm_down_deg` is how far below horizontal the source rest pose holds the arms.
+    pub fn rebind_clip_to_a_pose(&mut self, handle: u32, arm_down_deg: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip = session.clip.rebind_to_a_pose(arm_down_deg.to_radians());
+            Ok(())
+        });
+    }
+
+    /// Overwrite bones of the session's clip with a loaded clip within `[start, end]` seconds
+    /// `mask` has bit i set for bone index i (e.g. the legs, to fix them while
+    /// keeping the arms). Blends in and out at the range boundaries.
+    pub fn overlay_clip_range(
+        &mut self,
+        handle: u32,
+        source: AnimationId,
+        mask: u32,
+        start: f32,
+        end: f32,
+    ) {
+        let engine = &mut self.state.engine;
+        let source = engine.animation_library.get_clip(source);
+        let _ = engine.editor.update(handle, |session| {
+            let source = source.ok_or(EditorError::InvalidArgument)?;
+            if start.is_nan() || end.is_nan() || start > end {
+                return Err(EditorError::InvalidArgument);
+            }
+            session.clip =
+                session
+                    .clip
+                    .overlay_range(source, BoneMask::from_bits(mask), start, end);
+            Ok(())
+        });
+    }
+
+    /// Time-shift the session's clip so `time` (e.g. foot contact) becomes phase 0
+    pub fn align_clip_phase(&mut self, handle: u32, time: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip = session.clip.normalize_phase(time);
+            session.keyframe_index = 0;
+            Ok(())
+        });
+    }
+
+    /// Stretch the session's clip in time (2.0 = twice as long)
+    /// Non-positive factors are rejected.
+    pub fn scale_clip_time(&mut self, handle: u32, factor: f32) {
+        let _ = self.state.engine.editor.update(handle, |session| {
+            session.clip = session
+                .clip
+                .scale_time(factor)
+                .map_err(|_| EditorError::InvalidArgument)?;
+            Ok(())
+        });
+    }
+
+    /// Compare two keyframes of the session's clip
+    /// Returns per-bone angles in degrees (bone index order) followed by the
+    /// root position delta [x, y, z]. Empty if either index is out of range.
+    pub fn get_keyframe_diff(&self, handle: u32, index_a: usize, index_b: usize) -> Vec<f32> {
+        let Some(session) = self.state.engine.editor.get(handle) else {
+            return Vec::new();
+        };
+        let keyframes = &session.clip.keyframes;
+        match (keyframes.get(index_a), keyframes.get(index_b)) {
+            (Some(a), Some(b)) => {
+                let diff = a.pose.diff(&b.pose);
+                diff.bone_angles
+                    .iter()
+                    .copied()
+                    .chain(diff.root_delta.to_array())
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the session clip's root motion track
+    /// Returns [time, x, y, z] per keyframe: XZ root displacement from the first keyframe.
+    pub fn get_root_motion_track(&self, handle: u32) -> Vec<f32> {
+        self.state
+            .engine
+            .editor
+            .get(handle)
+            .map(|session| {
+                let (_, track) = session.clip.extract_root_motion();
+                track
+                    .into_iter()
+                    .flat_map(|(time, offset)| [time, offset.x, offset.y, offset.z])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Highlight the joint under the cursor
+    /// `joint_index` is a bone index as used by `get_joint_positions`; -1 clears the highlight.
+    pub fn set_hovered_joint(&mut self, handle: u32, joint_index: i32) {
+        if let Some(session) = self.state.engine.editor.get_mut(handle) {
+            let colors = session.set_hovered_joint(joint_from_index(joint_index));
+            self.update_bone_colors(&colors);
+        }
+    }
+}
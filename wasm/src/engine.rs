@@ -0,0 +1,253 @@
+//! Platform-agnostic engine state
+//!
+//! `Engine` owns everything except the GPU context: loaded clips, playback,
+//! camera and editor sessions. The wasm `AppState` wraps one `Engine` next to
+//! its `GpuContext`; native hosts can create as many independent engines as
+//! they need (e.g. one per window) and upload the matrices themselves.
+
+use crate::animation::{
+    AnimationLibrary, BlendSpace2D, EventMarkers, FixedTimestep, FloorSmoothing, PlaybackState,
+    SecondaryMotion, SpringSettings, WindSettings,
+};
+use crate::bone::{AnimationId, ConstraintClip, RotationAnimationClip};
+use crate::camera::{Camera, CameraShake};
+use crate::editor::{EditorSession, EditorSessions, PoseLibrary};
+use crate::skeleton::{RadiiConfig, RENDER_BONE_COUNT};
+use crate::time::TimeSource;
+use crate::{compute_matrices_from_playback, compute_matrices_with_secondary_motion};
+
+/// All non-GPU application state
+#[derive(Default)]
+pub struct Engine {
+    /// Loaded animation clips (read-only during playback)
+    pub animation_library: AnimationLibrary,
+    /// Current animation playback state (exercise, time)
+    pub playback: PlaybackState,
+    /// Leftover time for fixed-step playback
+    pub timestep: FixedTimestep,
+    /// Labeled clip times reported as playback passes them
+    pub event_markers: EventMarkers,
+    /// Secondary motion springs, active when `spring` is set
+    pub secondary_motion: SecondaryMotion,
+    pub spring: Option<SpringSettings>,
+    /// Ambient breeze layered on playback, if enabled
+    pub wind: Option<WindSettings>,
+    /// Easing of the floor constraint's root lift (instant by default)
+    pub floor_smoothing: FloorSmoothing,
+    /// Locomotion clips blended by `blend_parameter`
+    pub blend_space: BlendSpace2D,
+    pub blend_parameter: glam::Vec2,
+    /// Camera orientation and distance
+    pub camera: Camera,
+    /// Transient shake applied on top of `camera`
+    pub camera_shake: CameraShake,
+    /// Open keyframe editor sessions
+    pub editor: EditorSessions,
+    /// Named single-frame poses for the editor
+    pub pose_library: PoseLibrary,
+    /// Mesh radii; the renderer's vertex buffer is generated from these
+    pub radii: RadiiConfig,
+    /// Clock reading at the last `tick`, None before the first
+    pub last_tick_ms: Option<f64>,
+}
+
+impl Engine {
+    /// Create an engine with no clips loaded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a JSON clip and store it under `id`
+    pub fn load_animation(&mut self, id: AnimationId, json: &str) -> Result<(), serde_json::Error> {
+        let clip = RotationAnimationClip::from_json(json)?;
+        self.animation_library.add_clip(id, clip);
+        Ok(())
+    }
+
+    /// Parse a JSON constraint clip and store it under `id`
+    pub fn load_constraint_animation(
+        &mut self,
+        id: AnimationId,
+        json: &str,
+    ) -> Result<(), serde_json::Error> {
+        let clip = ConstraintClip::from_json(json)?;
+        self.animation_library.add_constraint_clip(id, clip);
+        Ok(())
+    }
+
+    /// Parse a binary clip (full or delta encoded) and store it under `id`
+    pub fn load_animation_binary(
+        &mut self,
+        id: AnimationId,
+        data: &[u8],
+    ) -> Result<(), &'static str> {
+        let clip = RotationAnimationClip::from_binary_any(data, format!("{:?}", id))?;
+        self.animation_library.add_clip(id, clip);
+        Ok(())
+    }
+
+    /// Switch the playing exercise and restart from time 0
+    pub fn set_exercise(&mut self, id: AnimationId) {
+        self.playback = self.playback.clone().set_exercise(id);
+    }
+
+    /// Advance playback time by `delta_seconds`
+    pub fn advance_time(&mut self, delta_seconds: f32) {
+        self.playback.time += delta_seconds;
+    }
+
+    /// Labels of the current clip's event markers passed since clip time `from`
+    /// Markers are placed in clip time, so `from` is `playback.sample_time()`
+    /// before advancing (after any time warp), like the current end of the range.
+    /// See `EventMarkers::crossed`; empty if no keyframe clip is playing.
+    pub fn crossed_events(&self, from: f32) -> Vec<String> {
+        let Some(id) = self.playback.exercise else {
+            return Vec::new();
+        };
+        let Some(clip) = self.animation_library.get_clip(id) else {
+            return Vec::new();
+        };
+        self.event_markers
+            .crossed(id, from, self.playback.sample_time(), clip.duration)
+    }
+
+    /// Advance playback by the time `clock` moved since the previous tick
+    /// The first tick only starts the clock. Returns the seconds advanced.
+    pub fn tick(&mut self, clock: &dyn TimeSource) -> f32 {
+        let now = clock.now_ms();
+        let delta_ms = self.last_tick_ms.map_or(0.0, |last| (now - last).max(0.0));
+        self.last_tick_ms = Some(now);
+        let delta_seconds = (delta_ms / 1000.0) as f32;
+        self.advance_time(delta_seconds);
+        delta_seconds
+    }
+
+    /// Open an editor session on a copy of a loaded clip
+    /// Falls back to a single bind pose keyframe if the clip is not loaded.
+    pub fn create_editor_session(&mut self, id: AnimationId) -> u32 {
+        let session = match self.animation_library.get_clip(id) {
+            Some(clip) => EditorSession::new(clip.clone()),
+            None => EditorSession::empty(format!("{:?}", id)),
+        };
+        self.editor.create(session)
+    }
+
+    /// Bone matrices for the current playback state, with springs and wind if enabled
+    pub fn playback_matrices(&mut self) -> [glam::Mat4; RENDER_BONE_COUNT] {
+        match self.spring {
+            Some(spring) => compute_matrices_with_secondary_motion(
+                &self.animation_library,
+                &self.playback,
+                &mut self.secondary_motion,
+                spring,
+                self.wind,
+                &mut self.floor_smoothing,
+                self.radii.bone,
+            ),
+            None => compute_matrices_from_playback(
+                &self.animation_library,
+                &self.playback,
+                self.wind,
+                &mut self.floor_smoothing,
+                self.radii.bone,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    fn clip_json(root_x: f32) -> String {
+        format!(
+            r#"{{ "n": "clip", "d": 1.0, "kf": [{{ "t": 0.0, "p": {{ "rp": [{}, 1.0, 0.0] }} }}] }}"#,
+            root_x
+        )
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_engines_are_independent() {
+        let mut a = Engine::new();
+        let mut b = Engine::new();
+        a.load_animation(AnimationId::PushUps, &clip_json(-1.0))
+            .unwrap();
+        b.load_animation(AnimationId::PushUps, &clip_json(2.0))
+            .unwrap();
+        a.set_exercise(AnimationId::PushUps);
+        b.set_exercise(AnimationId::PushUps);
+        a.advance_time(0.25);
+
+        assert_eq!(a.playback.time, 0.25);
+        assert_eq!(b.playback.time, 0.0);
+        let root_x = |engine: &mut Engine| engine.playback_matrices()[0].w_axis.x;
+        assert!((root_x(&mut b) - root_x(&mut a) - 3.0).abs() < 1e-4);
+
+        // Sessions copy each engine's own clip; handles are per engine
+        let session_a = a.create_editor_session(AnimationId::PushUps);
+        let session_b = b.create_editor_session(AnimationId::PushUps);
+        assert_eq!(session_a, session_b);
+        let root = |engine: &Engine, handle| {
+            engine
+                .editor
+                .get(handle)
+                .unwrap()
+                .current_pose()
+                .root_position
+                .x
+        };
+        assert_eq!(root(&a, session_a), -1.0);
+        assert_eq!(root(&b, session_b), 2.0);
+
+        // Unloading from one leaves the other untouched
+        assert!(a.animation_library.remove_clip(AnimationId::PushUps));
+        assert!(b.animation_library.has_clip(AnimationId::PushUps));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_tick_samples_exact_poses_with_mock_clock() {
+        use crate::animation::sample_animation;
+        use crate::time::MockTimeSource;
+
+        let mut engine = Engine::new();
+        let json = r#"{ "n": "clip", "d": 1.0, "kf": [
+            { "t": 0.0, "p": { "rp": [0.0, 1.0, 0.0] } },
+            { "t": 0.5, "p": { "rp": [2.0, 1.0, 0.0] } }
+        ] }"#;
+        engine.load_animation(AnimationId::PushUps, json).unwrap();
+        engine.set_exercise(AnimationId::PushUps);
+        let clock = MockTimeSource::new(10_000.0);
+        let root_x = |engine: &Engine| {
+            sample_animation(&engine.animation_library, &engine.playback)
+                .root_position
+                .x
+        };
+
+        // The first tick starts the clock without moving playback
+        assert_eq!(engine.tick(&clock), 0.0);
+        assert_eq!(root_x(&engine), 0.0);
+
+        clock.advance(250.0);
+        assert_eq!(engine.tick(&clock), 0.25);
+        assert_eq!(engine.playback.time, 0.25);
+        assert_eq!(root_x(&engine), 1.0);
+
+        clock.advance(250.0);
+        engine.tick(&clock);
+        assert_eq!(root_x(&engine), 2.0);
+
+        // Loops back towards the first keyframe
+        clock.set(10_750.0);
+        engine.tick(&clock);
+        assert_eq!(engine.playback.time, 0.75);
+        assert_eq!(root_x(&engine), 1.0);
+
+        // A clock that steps backwards does not rewind playback
+        clock.set(10_000.0);
+        assert_eq!(engine.tick(&clock), 0.0);
+        assert_eq!(engine.playback.time, 0.75);
+    }
+}
@@ -1,9 +1,29 @@
 use static_assertions::const_assert_eq;
+use std::collections::BTreeMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures;
 use wgpu::util::DeviceExt;
 
-use crate::skeleton::{generate_bind_pose_mesh, SkinnedVertex, RENDER_BONE_COUNT};
+use crate::bone::RotationPose;
+use crate::camera::{projection_matrix, Frustum, DEFAULT_FAR, DEFAULT_NEAR};
+use crate::gpu_error::{create_surface, GpuInitError};
+use crate::profiler::{supports_timestamps, GpuProfiler};
+pub use crate::render_targets::DepthFormat;
+use crate::render_targets::{
+    depth_stencil_state, surface_recovery, RenderTargets, SurfaceRecovery, MSAA_SAMPLE_COUNT,
+};
+use crate::skeleton::{
+    apply_bone_visibility, generate_bind_pose_mesh, generate_bind_pose_mesh_with_radii,
+    generate_box_mesh, matrices_bounding_sphere, BoneUploadCache, RadiiConfig, SkinnedVertex,
+    SkinningMode, ALL_BONES_VISIBLE, RENDER_BONE_COUNT,
+};
+use crate::skinning::{supports_compute, GpuSkinning, SkinningInstance};
+
+// Color convention: shaders produce display (gamma-encoded) colors, applying
+// the 1/2.2 gamma themselves since WebGL has no sRGB surfaces. Tint, outline and
+// sky colors are given in display space too. On an sRGB surface the shaders'
+// `SRGB_SURFACE` override decodes their output back to linear, so the surface's
+// own encode lands on the same display value; the clear color is decoded here.
 
 // Shared background/sky color
 const SKY_COLOR: wgpu::Color = wgpu::Color {
@@ -13,9 +33,128 @@ const SKY_COLOR: wgpu::Color = wgpu::Color {
     a: 1.0,
 };
 
-/// MSAA sample count (4x anti-aliasing)
-/// Use 1 to disable, 4 for high quality
-const MSAA_SAMPLE_COUNT: u32 = 4;
+/// Maximum onion-skin ghost instances (previous and next keyframe)
+pub const MAX_GHOSTS: usize = 2;
+
+/// Skeleton instances: the main skeleton, then each ghost
+pub const INSTANCE_COUNT: usize = 1 + MAX_GHOSTS;
+
+/// Bytes between instance tints in their buffer (WebGPU's default
+/// `min_uniform_buffer_offset_alignment`, so each can be bound at its offset)
+pub const INSTANCE_TINT_STRIDE: u64 = 256;
+
+/// Opacity of onion-skin ghosts until `set_instance_tint` changes it
+pub const GHOST_ALPHA: f32 = 0.25;
+
+/// Default RGBA tint per instance: white, opaque except the ghosts
+pub const DEFAULT_INSTANCE_TINTS: [[f32; 4]; INSTANCE_COUNT] = {
+    let mut tints = [[1.0, 1.0, 1.0, GHOST_ALPHA]; INSTANCE_COUNT];
+    tints[0] = [1.0; 4];
+    tints
+};
+
+/// Byte offset of an instance's tint in the instance tint buffer
+pub fn instance_tint_offset(instance: usize) -> u64 {
+    instance as u64 * INSTANCE_TINT_STRIDE
+}
+
+/// Contents of the instance tint buffer: each tint at `instance_tint_offset`
+pub fn instance_tint_bytes(tints: &[[f32; 4]; INSTANCE_COUNT]) -> Vec<u8> {
+    let mut bytes = vec![0; INSTANCE_COUNT * INSTANCE_TINT_STRIDE as usize];
+    for (instance, tint) in tints.iter().enumerate() {
+        let offset = instance_tint_offset(instance) as usize;
+        bytes[offset..offset + 16].copy_from_slice(bytemuck::bytes_of(tint));
+    }
+    bytes
+}
+
+/// Instances with alpha below 1 are blended (see `mesh_passes`)
+pub fn is_translucent(tint: [f32; 4]) -> bool {
+    tint[3] < 1.0
+}
+
+/// Frame presentation (vsync) mode
+///
+/// The `Auto*` modes are always available; the others depend on the browser and
+/// fall back per `choose_present_mode`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Vsync, using the lowest-latency mode that supports it
+    #[default]
+    AutoVsync,
+    /// No vsync where possible (for benchmarking), otherwise vsync
+    AutoNoVsync,
+    /// Strict vsync queue
+    Fifo,
+    /// Present immediately, may tear
+    Immediate,
+    /// Vsync, replacing queued frames with newer ones
+    Mailbox,
+}
+
+impl PresentMode {
+    /// Matching wgpu present mode
+    pub fn wgpu_mode(self) -> wgpu::PresentMode {
+        match self {
+            PresentMode::AutoVsync => wgpu::PresentMode::AutoVsync,
+            PresentMode::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+            PresentMode::Fifo => wgpu::PresentMode::Fifo,
+            PresentMode::Immediate => wgpu::PresentMode::Immediate,
+            PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+        }
+    }
+}
+
+/// Present mode to configure: `requested` if the surface supports it (the
+/// `Auto*` modes always are), otherwise the first supported mode
+pub fn choose_present_mode(
+    requested: PresentMode,
+    supported: &[wgpu::PresentMode],
+) -> wgpu::PresentMode {
+    let mode = requested.wgpu_mode();
+    let is_auto = matches!(
+        mode,
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+    );
+    if is_auto || supported.contains(&mode) {
+        return mode;
+    }
+    log::warn!("Present mode {:?} unsupported, falling back", mode);
+    supported
+        .first()
+        .copied()
+        .unwrap_or(wgpu::PresentMode::AutoVsync)
+}
+
+/// Surface format to render to: the first sRGB (or non-sRGB) format offered,
+/// per `prefer_srgb`, otherwise the surface's preferred format
+/// Returns None if the surface offers no formats at all.
+pub fn choose_surface_format(
+    formats: &[wgpu::TextureFormat],
+    prefer_srgb: bool,
+) -> Option<wgpu::TextureFormat> {
+    formats
+        .iter()
+        .find(|format| format.is_srgb() == prefer_srgb)
+        .or(formats.first())
+        .copied()
+}
+
+/// Display-space color as the clear value for a surface
+/// sRGB surfaces encode the clear color, so it is decoded to linear first.
+pub fn surface_clear_color(display: wgpu::Color, srgb_surface: bool) -> wgpu::Color {
+    if !srgb_surface {
+        return display;
+    }
+    let decode = |c: f64| c.powf(2.2);
+    wgpu::Color {
+        r: decode(display.r),
+        g: decode(display.g),
+        b: decode(display.b),
+        a: display.a,
+    }
+}
 
 /// WGSL Uniform struct
 #[repr(C)]
@@ -25,10 +164,13 @@ pub struct Uniforms {
     pub projection: [[f32; 4]; 4], // 64 bytes
     pub aspect: f32,               // 4 bytes
     pub screen_height: f32,        // 4 bytes
-    pub _padding: [f32; 6],        // 24 bytes -> total 160 bytes
+    pub skinning_mode: u32,        // 4 bytes (SkinningMode)
+    pub outline_thickness: f32,    // 4 bytes (world units along the normal)
+    pub outline_color: [f32; 4],   // 16 bytes -> total 160 bytes
 }
 // Size of the uniforms struct needs to be a multiple of 16 bytes
 const_assert_eq!(std::mem::size_of::<Uniforms>(), 160);
+const_assert_eq!(std::mem::size_of::<SkinnedVertex>(), 40);
 
 impl Default for Uniforms {
     fn default() -> Self {
@@ -37,9 +179,139 @@ impl Default for Uniforms {
             projection: glam::Mat4::IDENTITY.to_cols_array_2d(),
             aspect: 1.0,
             screen_height: 600.0,
-            _padding: [0.0; 6],
+            skinning_mode: SkinningMode::Rigid as u32,
+            outline_thickness: DEFAULT_OUTLINE_THICKNESS,
+            outline_color: DEFAULT_OUTLINE_COLOR,
+        }
+    }
+}
+
+/// Outline hull offset when none is configured
+pub const DEFAULT_OUTLINE_THICKNESS: f32 = 0.008;
+/// Near-black outline (display color, alpha unused)
+pub const DEFAULT_OUTLINE_COLOR: [f32; 4] = [0.05, 0.05, 0.08, 1.0];
+
+impl Uniforms {
+    /// Set the outline hull offset and color
+    /// Fails on a negative or non-finite thickness.
+    pub fn set_outline(&mut self, thickness: f32, color: [f32; 4]) -> Result<(), &'static str> {
+        if !thickness.is_finite() || thickness < 0.0 {
+            return Err("Outline thickness must be a non-negative number");
+        }
+        self.outline_thickness = thickness;
+        self.outline_color = color;
+        Ok(())
+    }
+
+    /// Set the viewport size and rebuild the projection for its aspect ratio
+    /// `near`/`far` are the configured clip planes, kept across resizes.
+    pub fn set_viewport(&mut self, width: u32, height: u32, near: f32, far: f32) {
+        self.aspect = width as f32 / height as f32;
+        self.screen_height = height as f32;
+        self.projection = projection_matrix(self.aspect, near, far).to_cols_array_2d();
+    }
+}
+
+/// Line parameters of the floor grid shader (`grid.wgsl`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GridUniforms {
+    /// Width of the 1 unit lines, in grid cells
+    pub line_width: f32,
+    /// Width of the lines every `MAJOR_GRID_STEP` units, in (major) grid cells
+    pub major_line_width: f32,
+    /// Antialiasing falloff in pixels (`fwidth` multiples)
+    pub aa_strength: f32,
+    pub _padding: f32,
+}
+const_assert_eq!(std::mem::size_of::<GridUniforms>(), 16);
+
+/// Minor grid line width when none is configured
+pub const DEFAULT_GRID_LINE_WIDTH: f32 = 0.02;
+/// Major grid lines are this much wider than the minor ones
+const MAJOR_GRID_LINE_RATIO: f32 = 1.5;
+
+impl Default for GridUniforms {
+    fn default() -> Self {
+        Self {
+            line_width: DEFAULT_GRID_LINE_WIDTH,
+            major_line_width: DEFAULT_GRID_LINE_WIDTH * MAJOR_GRID_LINE_RATIO,
+            aa_strength: 1.0,
+            _padding: 0.0,
+        }
+    }
+}
+
+impl GridUniforms {
+    /// Set the minor line width; major lines keep their ratio to it
+    /// Fails on a width outside 0..=0.5 (a line can't be wider than half a cell).
+    pub fn set_line_width(&mut self, width: f32) -> Result<(), &'static str> {
+        if !(0.0..=0.5).contains(&width) {
+            return Err("Grid line width must be between 0 and 0.5");
+        }
+        self.line_width = width;
+        self.major_line_width = width * MAJOR_GRID_LINE_RATIO;
+        Ok(())
+    }
+}
+
+/// A draw of the skeleton mesh
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshPass {
+    Shadow,
+    /// Inverted hull behind the skeleton (front faces culled)
+    Outline,
+    Skeleton,
+    /// Onion-skin ghost with this index
+    Ghost(usize),
+    /// Scene props, on the opaque skeleton pipeline
+    Props,
+}
+
+/// Mesh draws of a frame, in order
+///
+/// `instances` holds the tint and camera distance of the main skeleton, then
+/// of each drawn ghost. Opaque instances are drawn first; translucent ones
+/// (`is_translucent`) follow farthest first so each blends over what is behind
+/// it, and fully transparent ones are skipped. Props are opaque and drawn
+/// before the translucent instances, which don't write depth and would
+/// otherwise be painted over by a prop behind them. The outline goes right before an
+/// opaque skeleton so the skeleton covers its inner half; it is left out for a
+/// translucent one, whose hull would show through.
+pub fn mesh_passes(outline: bool, instances: &[([f32; 4], f32)]) -> Vec<MeshPass> {
+    let pass = |instance: usize| match instance {
+        0 => MeshPass::Skeleton,
+        i => MeshPass::Ghost(i - 1),
+    };
+    let mut passes = vec![MeshPass::Shadow];
+    let mut translucent = Vec::new();
+    for (instance, &(tint, _)) in instances.iter().enumerate() {
+        if is_translucent(tint) {
+            if tint[3] > 0.0 {
+                translucent.push(instance);
+            }
+            continue;
+        }
+        if instance == 0 && outline {
+            passes.push(MeshPass::Outline);
         }
+        passes.push(pass(instance));
     }
+    passes.push(MeshPass::Props);
+    translucent.sort_by(|&a, &b| instances[b].1.total_cmp(&instances[a].1));
+    passes.extend(translucent.into_iter().map(pass));
+    passes
+}
+
+/// A static mesh drawn next to the skeleton (barbell, box, ...)
+///
+/// Props reuse the skeleton pipeline: every vertex is bound to matrix 0 of the
+/// prop's own bone buffer, which holds its transform, and its tint stays zero.
+pub struct Prop {
+    pub vertex_buffer: wgpu::Buffer,
+    pub vertex_count: u32,
+    pub transform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
 }
 
 pub struct GpuContext {
@@ -47,28 +319,217 @@ pub struct GpuContext {
     pub queue: wgpu::Queue,
     pub surface: wgpu::Surface<'static>,
     pub config: wgpu::SurfaceConfiguration,
+    /// Present modes the surface reported at initialization
+    pub present_modes: Vec<wgpu::PresentMode>,
+    /// Surface must be reconfigured from `config` before the next frame
+    pub surface_dirty: bool,
     // Render pipelines
     pub skeleton_pipeline: wgpu::RenderPipeline,
+    /// Blended skeleton pipeline for translucent instances (ghosts by default)
+    pub ghost_pipeline: wgpu::RenderPipeline,
+    pub outline_pipeline: wgpu::RenderPipeline,
     pub shadow_pipeline: wgpu::RenderPipeline,
     pub grid_pipeline: wgpu::RenderPipeline,
     // GPU Buffers
     pub vertex_buffer: wgpu::Buffer,
     pub bone_uniform_buffer: wgpu::Buffer,
+    /// Skips `bone_uniform_buffer` writes when the pose is unchanged
+    pub bone_upload: BoneUploadCache,
+    pub bone_color_buffer: wgpu::Buffer,
+    /// RGBA tint of each instance at `instance_tint_offset`, multiplied in the shader
+    pub instance_tint_buffer: wgpu::Buffer,
+    /// CPU copy of the instance tints, for draw ordering
+    pub instance_tints: [[f32; 4]; INSTANCE_COUNT],
     pub uniform_buffer: wgpu::Buffer,
-    // Depth texture
-    pub depth_texture: wgpu::Texture,
-    pub depth_view: wgpu::TextureView,
-    // MSAA render target
-    pub msaa_texture: wgpu::Texture,
-    pub msaa_view: wgpu::TextureView,
+    /// `GridUniforms` for the grid fragment shader
+    pub grid_uniform_buffer: wgpu::Buffer,
+    pub grid_uniforms: GridUniforms,
+    // Depth format (shared with every pipeline's depth state)
+    pub depth_format: wgpu::TextureFormat,
+    /// MSAA color and depth targets, recreated by `reconfigure_surface`
+    pub targets: RenderTargets,
+    /// `SKY_COLOR` as the surface expects it (see `surface_clear_color`)
+    pub clear_color: wgpu::Color,
     // Bind groups
     pub uniform_bind_group: wgpu::BindGroup,
+    /// Camera uniforms and `grid_uniform_buffer`
+    pub grid_bind_group: wgpu::BindGroup,
     pub bone_bind_group: wgpu::BindGroup,
+    /// Layout of `bone_bind_group`, kept to create prop bind groups later
+    pub bone_bind_group_layout: wgpu::BindGroupLayout,
+    // Onion-skin ghost instances (one bone matrix buffer + bind group each)
+    pub ghost_bone_buffers: Vec<wgpu::Buffer>,
+    pub ghost_bind_groups: Vec<wgpu::BindGroup>,
+    pub ghost_count: u32,
+    /// Render bones drawn (bit i = render bone i); hidden ones are collapsed
+    pub visible_bones: u32,
+    /// Bounding spheres (center, radius) of the main skeleton, then each ghost
+    pub instance_bounds: [(glam::Vec3, f32); INSTANCE_COUNT],
+    /// Skeleton instances that passed frustum culling in the last frame
+    pub visible_instances: u32,
+    /// Draw the inverted-hull outline (thickness and color live in `uniforms`)
+    pub outline_enabled: bool,
+    /// Compute skinning pipeline, None where compute is unsupported (WebGL2)
+    pub skinning: Option<GpuSkinning>,
+    /// Render pass timestamp queries, None where the feature is unavailable
+    pub profiler: Option<GpuProfiler>,
+    /// Scene props by id, drawn with the opaque skeleton passes
+    pub props: BTreeMap<u32, Prop>,
+    pub next_prop_id: u32,
     // Render state
     pub uniforms: Uniforms,
+    /// Clip plane distances, reused when the projection is rebuilt
+    pub near: f32,
+    pub far: f32,
     pub vertex_count: u32,
 }
 
+impl GpuContext {
+    /// Replace the skeleton mesh (e.g. after the radii change)
+    pub fn set_mesh(&mut self, vertices: &[SkinnedVertex]) {
+        self.vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Skeleton Vertex Buffer"),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    /// Upload a prop mesh with an identity transform and return its id
+    pub fn add_prop(&mut self, vertices: &[SkinnedVertex]) -> u32 {
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Prop Vertex Buffer"),
+                contents: bytemuck::cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        // Sized like the skeleton's bone buffer to satisfy the shader's binding
+        let transforms = [glam::Mat4::IDENTITY.to_cols_array_2d(); RENDER_BONE_COUNT];
+        let transform_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Prop Transform Buffer"),
+                contents: bytemuck::cast_slice(&transforms),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let tint_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Prop Colors Buffer"),
+                contents: bytemuck::cast_slice(&[[0.0f32; 4]; RENDER_BONE_COUNT]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let instance_tint_buffer =
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Prop Instance Tint Buffer"),
+                    contents: bytemuck::cast_slice(&[1.0f32; 4]),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Prop Bind Group"),
+            layout: &self.bone_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: transform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: tint_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: instance_tint_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let id = self.next_prop_id;
+        self.next_prop_id += 1;
+        self.props.insert(
+            id,
+            Prop {
+                vertex_buffer,
+                vertex_count: vertices.len() as u32,
+                transform_buffer,
+                bind_group,
+            },
+        );
+        id
+    }
+
+    /// Switch the present mode (see `choose_present_mode`) and reconfigure
+    /// Returns the mode actually configured.
+    pub fn set_present_mode(&mut self, requested: PresentMode) -> wgpu::PresentMode {
+        self.config.present_mode = choose_present_mode(requested, &self.present_modes);
+        self.reconfigure_surface();
+        self.config.present_mode
+    }
+
+    /// Configure the surface from `config` and recreate the depth and MSAA
+    /// targets to match. Shared by resizing and surface-loss recovery.
+    pub fn reconfigure_surface(&mut self) {
+        let (width, height) = (self.config.width, self.config.height);
+        self.surface.configure(&self.device, &self.config);
+
+        // Recreate the MSAA color and depth targets at the new size
+        self.targets = RenderTargets::new(
+            &self.device,
+            self.config.format,
+            self.depth_format,
+            width,
+            height,
+        );
+
+        self.surface_dirty = false;
+    }
+
+    /// Acquire the next surface texture, recovering from surface loss
+    ///
+    /// Lost/Outdated surfaces are reconfigured and acquisition is retried once.
+    /// Returns None when the frame should be skipped.
+    fn acquire_frame(&mut self) -> Option<wgpu::SurfaceTexture> {
+        if self.surface_dirty {
+            self.reconfigure_surface();
+        }
+
+        let mut retried = false;
+        loop {
+            let error = match self.surface.get_current_texture() {
+                Ok(frame) => return Some(frame),
+                Err(error) => error,
+            };
+            match surface_recovery(&error, retried) {
+                SurfaceRecovery::Reconfigure => {
+                    log::warn!("Surface lost or outdated, reconfiguring");
+                    self.reconfigure_surface();
+                    retried = true;
+                }
+                SurfaceRecovery::ReconfigureNextFrame => {
+                    // Try again from scratch on the next frame
+                    log::warn!("Surface still unavailable after reconfigure: {:?}", error);
+                    self.surface_dirty = true;
+                    return None;
+                }
+                SurfaceRecovery::SkipFrame => {
+                    match error {
+                        wgpu::SurfaceError::OutOfMemory => {
+                            log::error!("Out of memory acquiring surface texture")
+                        }
+                        wgpu::SurfaceError::Timeout => {}
+                        _ => log::warn!("Failed to acquire surface texture: {:?}", error),
+                    }
+                    return None;
+                }
+            }
+        }
+    }
+}
+
 /// Shader sources
 const SKELETON_SHADER: &str = include_str!("shaders/skeleton.wgsl");
 const SHADOW_SHADER: &str = include_str!("shaders/shadow.wgsl");
@@ -87,21 +548,94 @@ fn get_canvas_size(window: &web_sys::Window, canvas: &web_sys::HtmlCanvasElement
 /// Initialize WebGPU context from a canvas element
 /// wasm_bindgen + pub async fn
 /// -> Generates a promise for JS, returns App instance owned by JavaScript
+/// `prefer_srgb` asks for an sRGB surface where the browser offers one.
+#[wasm_bindgen]
+pub async fn init_gpu(
+    canvas_id: String,
+    force_webgl: bool,
+    prefer_srgb: bool,
+) -> Result<crate::state::App, JsValue> {
+    init_gpu_with_depth_format(canvas_id, force_webgl, prefer_srgb, DepthFormat::default()).await
+}
+
+/// Initialize WebGPU context with a specific present mode (e.g. no vsync)
+/// Unsupported modes fall back to one the surface offers.
+#[wasm_bindgen]
+pub async fn init_gpu_with_present_mode(
+    canvas_id: String,
+    force_webgl: bool,
+    prefer_srgb: bool,
+    present_mode: PresentMode,
+) -> Result<crate::state::App, JsValue> {
+    init_gpu_with_options(
+        canvas_id,
+        force_webgl,
+        prefer_srgb,
+        DepthFormat::default(),
+        present_mode,
+    )
+    .await
+}
+
+/// Initialize WebGPU context with a specific depth buffer format
+/// Fails if the adapter cannot render to the format with MSAA.
 #[wasm_bindgen]
-pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::state::App, JsValue> {
+pub async fn init_gpu_with_depth_format(
+    canvas_id: String,
+    force_webgl: bool,
+    prefer_srgb: bool,
+    depth_format: DepthFormat,
+) -> Result<crate::state::App, JsValue> {
+    init_gpu_with_options(
+        canvas_id,
+        force_webgl,
+        prefer_srgb,
+        depth_format,
+        PresentMode::default(),
+    )
+    .await
+}
+
+/// Shared body of the `init_gpu*` entry points
+async fn init_gpu_with_options(
+    canvas_id: String,
+    force_webgl: bool,
+    prefer_srgb: bool,
+    depth_format: DepthFormat,
+    present_mode: PresentMode,
+) -> Result<crate::state::App, JsValue> {
     // Set up panic hook for better error messages in browser console
     console_error_panic_hook::set_once();
     console_log::init_with_level(log::Level::Info).ok();
 
-    let window = web_sys::window().ok_or_else(|| JsValue::from_str("No window"))?;
-    let document = window
-        .document()
-        .ok_or_else(|| JsValue::from_str("No document"))?;
+    init_app(
+        canvas_id,
+        force_webgl,
+        prefer_srgb,
+        depth_format,
+        present_mode,
+    )
+    .await
+    .map_err(|e| {
+        log::error!("GPU initialization failed: {:?}", e);
+        JsValue::from(e)
+    })
+}
+
+/// Body of `init_gpu_with_depth_format`, tagging failures with their stage
+async fn init_app(
+    canvas_id: String,
+    force_webgl: bool,
+    prefer_srgb: bool,
+    depth_format: DepthFormat,
+    present_mode: PresentMode,
+) -> Result<crate::state::App, GpuInitError> {
+    let window = web_sys::window().ok_or(GpuInitError::NoWindow)?;
+    let document = window.document().ok_or(GpuInitError::NoWindow)?;
     let canvas = document
         .get_element_by_id(&canvas_id)
-        .ok_or_else(|| JsValue::from_str("Canvas not found"))?
-        .dyn_into::<web_sys::HtmlCanvasElement>()
-        .map_err(|_| JsValue::from_str("Not a canvas"))?;
+        .and_then(|element| element.dyn_into::<web_sys::HtmlCanvasElement>().ok())
+        .ok_or_else(|| GpuInitError::NoCanvas(canvas_id.clone()))?;
 
     let (width, height) = get_canvas_size(&window, &canvas);
     canvas.set_width(width);
@@ -126,9 +660,7 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     });
 
     // Create surface from canvas
-    let surface = instance
-        .create_surface(wgpu::SurfaceTarget::Canvas(canvas))
-        .map_err(|e| JsValue::from_str(&format!("Failed to create surface: {}", e)))?;
+    let surface = create_surface(&instance, wgpu::SurfaceTarget::Canvas(canvas))?;
 
     // Request adapter
     let adapter = instance
@@ -139,7 +671,25 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
             force_fallback_adapter: false,
         })
         .await
-        .map_err(|_| JsValue::from_str("Failed to find GPU adapter"))?;
+        .map_err(|e| GpuInitError::NoAdapter(e.to_string()))?;
+
+    // Validate the depth format before any pipeline is built with it
+    let depth_features = depth_format.required_features();
+    let depth_format_features = adapter.get_texture_format_features(depth_format.texture_format());
+    if !adapter.features().contains(depth_features)
+        || !depth_format_features
+            .allowed_usages
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        || !depth_format_features
+            .flags
+            .sample_count_supported(MSAA_SAMPLE_COUNT)
+    {
+        return Err(GpuInitError::UnsupportedDepthFormat(format!(
+            "{:?}",
+            depth_format
+        )));
+    }
+    let depth_format = depth_format.texture_format();
 
     // Request device and queue
     // Use appropriate limits based on backend
@@ -149,17 +699,25 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
         wgpu::Limits::default()
     };
 
+    // Timestamp queries are optional; without them GPU profiling is left out
+    let timestamps = supports_timestamps(&adapter);
+    let timestamp_features = if timestamps {
+        wgpu::Features::TIMESTAMP_QUERY
+    } else {
+        wgpu::Features::empty()
+    };
+
     let (device, queue): (wgpu::Device, wgpu::Queue) = adapter
         .request_device(&wgpu::DeviceDescriptor {
             label: Some("Main Device"),
-            required_features: wgpu::Features::empty(),
+            required_features: depth_features | timestamp_features,
             required_limits,
             memory_hints: Default::default(),
             experimental_features: Default::default(),
             trace: wgpu::Trace::Off,
         })
         .await
-        .map_err(|e| JsValue::from_str(&format!("Failed to create device: {}", e)))?;
+        .map_err(|e| GpuInitError::DeviceRequest(e.to_string()))?;
 
     // Configure surface
     let surface_caps = surface.get_capabilities(&adapter);
@@ -177,23 +735,18 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     let height = height.min(max_dim);
     log::info!("Clamped surface size: {}x{}", width, height);
 
-    // Force LINEAR (non-sRGB) format for consistent color handling.
-    // This avoids double-gamma issues: sRGB formats auto-apply gamma,
-    // but we apply manual gamma in shaders for cross-backend consistency (WebGL does not support sRGB surface).
-    // So we prefer a linear format and let shaders handle gamma.
-    let surface_format = surface_caps
-        .formats
-        .iter()
-        .find(|f| !f.is_srgb()) // Prefer non-sRGB (linear)
-        .copied()
-        .unwrap_or(surface_caps.formats[0]);
+    // WebGL only offers linear formats; the shaders adapt to either (see SKY_COLOR)
+    let surface_format = choose_surface_format(&surface_caps.formats, prefer_srgb)
+        .ok_or_else(|| GpuInitError::SurfaceCreation("surface offers no formats".to_string()))?;
+    let srgb_surface = surface_format.is_srgb();
+    log::info!("Surface format: {:?}", surface_format);
 
     let config = wgpu::SurfaceConfiguration {
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
         format: surface_format,
         width,
         height,
-        present_mode: wgpu::PresentMode::AutoVsync,
+        present_mode: choose_present_mode(present_mode, &surface_caps.present_modes),
         alpha_mode: surface_caps.alpha_modes[0],
         view_formats: vec![],
         desired_maximum_frame_latency: 2,
@@ -230,7 +783,8 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
             label: Some("Uniform Bind Group Layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
                 binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
+                // The outline fragment shader reads its color from here
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -260,6 +814,12 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
         mapped_at_creation: false,
     });
 
+    // GPU skinning writes the bone buffers by copy; otherwise poses are skinned on the CPU
+    let skinning = supports_compute(&adapter, &device).then(|| GpuSkinning::new(&device));
+    log::info!("GPU skinning: {}", skinning.is_some());
+    let profiler = timestamps.then(|| GpuProfiler::new(&device, &queue));
+    log::info!("GPU profiling: {}", profiler.is_some());
+
     // Initialize with identity matrices to prevent zero-scale geometry before first update
     let initial_bones = vec![glam::Mat4::IDENTITY.to_cols_array_2d(); RENDER_BONE_COUNT];
     queue.write_buffer(
@@ -268,32 +828,123 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
         bytemuck::cast_slice(&initial_bones),
     );
 
+    // Create per-bone tint buffer (RGBA per render bone, alpha = tint strength)
+    // Zero-initialized so nothing is tinted until the editor highlights a bone
+    let bone_color_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Bone Colors Buffer"),
+        contents: bytemuck::cast_slice(&[[0.0f32; 4]; RENDER_BONE_COUNT]),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+
     // Create bone bind group layout
     let bone_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Bone Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                // Bone tint colors (only read by the skeleton fragment shader)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                // Instance tint (one slot of the instance tint buffer)
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
         });
 
+    // Create per-instance tint buffer (main skeleton, then ghosts)
+    let instance_tint_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Instance Tint Buffer"),
+        contents: &instance_tint_bytes(&DEFAULT_INSTANCE_TINTS),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let instance_tint_binding = |instance: usize| {
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &instance_tint_buffer,
+            offset: instance_tint_offset(instance),
+            size: wgpu::BufferSize::new(16),
+        })
+    };
+
     // Create bone bind group
     let bone_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: Some("Bone Bind Group"),
         layout: &bone_bind_group_layout,
-        entries: &[wgpu::BindGroupEntry {
-            binding: 0,
-            resource: bone_uniform_buffer.as_entire_binding(),
-        }],
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: bone_uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: bone_color_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: instance_tint_binding(0),
+            },
+        ],
     });
 
+    // Create onion-skin ghost bone buffers, sharing the tint buffer with the main skeleton
+    let ghost_bone_buffers: Vec<wgpu::Buffer> = (0..MAX_GHOSTS)
+        .map(|_| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Ghost Bone Matrices Buffer"),
+                contents: bytemuck::cast_slice(&initial_bones),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        })
+        .collect();
+    let ghost_bind_groups: Vec<wgpu::BindGroup> = ghost_bone_buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Ghost Bone Bind Group"),
+                layout: &bone_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: bone_color_buffer.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: instance_tint_binding(i + 1),
+                    },
+                ],
+            })
+        })
+        .collect();
+
     // Create pipeline layout
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Skeleton Pipeline Layout"),
@@ -302,10 +953,12 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     });
 
     // Constants for the grid shader
+    let surface_constants = [("SRGB_SURFACE", f64::from(u8::from(srgb_surface)))];
     let grid_constants = [
         ("SKY_R", SKY_COLOR.r),
         ("SKY_G", SKY_COLOR.g),
         ("SKY_B", SKY_COLOR.b),
+        surface_constants[0],
     ];
 
     // Create skeleton render pipeline
@@ -339,6 +992,24 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
                             shader_location: 2,
                             format: wgpu::VertexFormat::Uint32,
                         },
+                        // ao
+                        wgpu::VertexAttribute {
+                            offset: 28,
+                            shader_location: 3,
+                            format: wgpu::VertexFormat::Float32,
+                        },
+                        // blend_index
+                        wgpu::VertexAttribute {
+                            offset: 32,
+                            shader_location: 4,
+                            format: wgpu::VertexFormat::Uint32,
+                        },
+                        // blend_weight
+                        wgpu::VertexAttribute {
+                            offset: 36,
+                            shader_location: 5,
+                            format: wgpu::VertexFormat::Float32,
+                        },
                     ],
                 },
             ],
@@ -352,7 +1023,10 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
                 blend: None, // No blending for solid 3D objects
                 write_mask: wgpu::ColorWrites::ALL,
             })],
-            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &surface_constants,
+                ..Default::default()
+            },
         }),
         primitive: wgpu::PrimitiveState {
             topology: wgpu::PrimitiveTopology::TriangleList,
@@ -363,13 +1037,171 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
+        depth_stencil: Some(depth_stencil_state(
+            depth_format,
+            true,
+            wgpu::StencilState::default(),
+        )),
+        multisample: wgpu::MultisampleState {
+            count: MSAA_SAMPLE_COUNT,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview_mask: None,
+        cache: None,
+    });
+
+    // Create the translucent pipeline for ghosts and other instances with alpha
+    // below 1 (skeleton shader, blended, no depth writes)
+    let ghost_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Ghost Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &skeleton_shader,
+            entry_point: Some("vs_main"),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x3,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 12,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x3,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 24,
+                        shader_location: 2,
+                        format: wgpu::VertexFormat::Uint32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 28,
+                        shader_location: 3,
+                        format: wgpu::VertexFormat::Float32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 32,
+                        shader_location: 4,
+                        format: wgpu::VertexFormat::Uint32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 36,
+                        shader_location: 5,
+                        format: wgpu::VertexFormat::Float32,
+                    },
+                ],
+            }],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &skeleton_shader,
+            entry_point: Some("fs_ghost"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &surface_constants,
+                ..Default::default()
+            },
         }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        // Ghosts must not hide each other or the skeleton
+        depth_stencil: Some(depth_stencil_state(
+            depth_format,
+            false,
+            wgpu::StencilState::default(),
+        )),
+        multisample: wgpu::MultisampleState {
+            count: MSAA_SAMPLE_COUNT,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview_mask: None,
+        cache: None,
+    });
+
+    // Create outline pipeline (inverted hull: skinned mesh pushed out along its
+    // normals, front faces culled so only the rim behind the skeleton remains)
+    let outline_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Outline Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &skeleton_shader,
+            entry_point: Some("vs_outline"),
+            buffers: &[wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<SkinnedVertex>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[
+                    wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 0,
+                        format: wgpu::VertexFormat::Float32x3,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 12,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x3,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 24,
+                        shader_location: 2,
+                        format: wgpu::VertexFormat::Uint32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 32,
+                        shader_location: 4,
+                        format: wgpu::VertexFormat::Uint32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 36,
+                        shader_location: 5,
+                        format: wgpu::VertexFormat::Float32,
+                    },
+                ],
+            }],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &skeleton_shader,
+            entry_point: Some("fs_outline"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format: surface_format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &surface_constants,
+                ..Default::default()
+            },
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Front),
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(depth_stencil_state(
+            depth_format,
+            true,
+            wgpu::StencilState::default(),
+        )),
         multisample: wgpu::MultisampleState {
             count: MSAA_SAMPLE_COUNT,
             mask: !0,
@@ -405,6 +1237,16 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
                         shader_location: 2,
                         format: wgpu::VertexFormat::Uint32,
                     },
+                    wgpu::VertexAttribute {
+                        offset: 32,
+                        shader_location: 4,
+                        format: wgpu::VertexFormat::Uint32,
+                    },
+                    wgpu::VertexAttribute {
+                        offset: 36,
+                        shader_location: 5,
+                        format: wgpu::VertexFormat::Float32,
+                    },
                 ],
             }],
             compilation_options: wgpu::PipelineCompilationOptions::default(),
@@ -428,11 +1270,11 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            depth_write_enabled: false, // Don't write depth (shadow is on floor)
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState {
+        // Don't write depth (shadow is on floor)
+        depth_stencil: Some(depth_stencil_state(
+            depth_format,
+            false,
+            wgpu::StencilState {
                 front: wgpu::StencilFaceState {
                     compare: wgpu::CompareFunction::NotEqual, // Draw if stencil != reference (1 != 0 -> Pass)
                     fail_op: wgpu::StencilOperation::Keep,
@@ -448,8 +1290,7 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
                 read_mask: 0xFF,
                 write_mask: 0xFF,
             },
-            bias: wgpu::DepthBiasState::default(),
-        }),
+        )),
         multisample: wgpu::MultisampleState {
             count: MSAA_SAMPLE_COUNT,
             mask: !0,
@@ -459,39 +1300,8 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
         cache: None,
     });
 
-    // Create depth texture with MSAA
-    let depth_texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("Depth Texture"),
-        size: wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: MSAA_SAMPLE_COUNT,
-        dimension: wgpu::TextureDimension::D2,
-        format: wgpu::TextureFormat::Depth24PlusStencil8,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        view_formats: &[],
-    });
-    let depth_view = depth_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-    // Create MSAA render target (color)
-    let msaa_texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("MSAA Texture"),
-        size: wgpu::Extent3d {
-            width,
-            height,
-            depth_or_array_layers: 1,
-        },
-        mip_level_count: 1,
-        sample_count: MSAA_SAMPLE_COUNT,
-        dimension: wgpu::TextureDimension::D2,
-        format: surface_format,
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        view_formats: &[],
-    });
-    let msaa_view = msaa_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    // Create the MSAA color and depth targets
+    let targets = RenderTargets::new(&device, surface_format, depth_format, width, height);
 
     // Generate bind pose mesh (static)
     let mesh_vertices = generate_bind_pose_mesh();
@@ -506,18 +1316,52 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     let grid_bind_group_layout =
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Grid Bind Group Layout"),
-            entries: &[wgpu::BindGroupLayoutEntry {
-                binding: 0,
-                visibility: wgpu::ShaderStages::VERTEX,
-                ty: wgpu::BindingType::Buffer {
-                    ty: wgpu::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: None,
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
                 },
-                count: None,
-            }],
+                // Line parameters
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
         });
 
+    let grid_uniforms = GridUniforms::default();
+    let grid_uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some("Grid Uniform Buffer"),
+        contents: bytemuck::bytes_of(&grid_uniforms),
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+    });
+    let grid_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Grid Bind Group"),
+        layout: &grid_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: grid_uniform_buffer.as_entire_binding(),
+            },
+        ],
+    });
+
     let grid_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Grid Pipeline Layout"),
         bind_group_layouts: &[&grid_bind_group_layout],
@@ -558,13 +1402,11 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
             polygon_mode: wgpu::PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: Some(wgpu::DepthStencilState {
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            depth_write_enabled: true,
-            depth_compare: wgpu::CompareFunction::Less,
-            stencil: wgpu::StencilState::default(),
-            bias: wgpu::DepthBiasState::default(),
-        }),
+        depth_stencil: Some(depth_stencil_state(
+            depth_format,
+            true,
+            wgpu::StencilState::default(),
+        )),
         multisample: wgpu::MultisampleState {
             count: MSAA_SAMPLE_COUNT,
             mask: !0,
@@ -581,16 +1423,7 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     let up = glam::Vec3::Y;
     uniforms.view = glam::Mat4::look_at_rh(eye, target, up).to_cols_array_2d();
 
-    let aspect = width as f32 / height as f32;
-    uniforms.aspect = aspect;
-    uniforms.screen_height = height as f32;
-    uniforms.projection = glam::Mat4::perspective_rh(
-        std::f32::consts::FRAC_PI_4, // 45 degrees FOV
-        aspect,
-        0.1,
-        100.0,
-    )
-    .to_cols_array_2d();
+    uniforms.set_viewport(width, height, DEFAULT_NEAR, DEFAULT_FAR);
 
     // Update uniform buffer
     queue.write_buffer(&uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
@@ -600,19 +1433,43 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
         queue,
         surface,
         config,
+        present_modes: surface_caps.present_modes,
+        surface_dirty: false,
         skeleton_pipeline,
+        ghost_pipeline,
+        outline_pipeline,
         shadow_pipeline,
         grid_pipeline,
         vertex_buffer,
         bone_uniform_buffer,
+        bone_upload: BoneUploadCache::default(),
+        bone_color_buffer,
+        instance_tint_buffer,
+        instance_tints: DEFAULT_INSTANCE_TINTS,
         uniform_buffer,
-        depth_texture,
-        depth_view,
-        msaa_texture,
-        msaa_view,
+        grid_uniform_buffer,
+        grid_uniforms,
+        depth_format,
+        targets,
+        clear_color: surface_clear_color(SKY_COLOR, srgb_surface),
         uniform_bind_group,
+        grid_bind_group,
         bone_bind_group,
+        bone_bind_group_layout,
+        ghost_bone_buffers,
+        ghost_bind_groups,
+        ghost_count: 0,
+        visible_bones: ALL_BONES_VISIBLE,
+        instance_bounds: [(glam::Vec3::ZERO, f32::INFINITY); INSTANCE_COUNT],
+        visible_instances: 0,
+        outline_enabled: false,
+        skinning,
+        profiler,
+        props: BTreeMap::new(),
+        next_prop_id: 0,
         uniforms,
+        near: DEFAULT_NEAR,
+        far: DEFAULT_FAR,
         vertex_count,
     };
 
@@ -624,11 +1481,73 @@ pub async fn init_gpu(canvas_id: String, force_webgl: bool) -> Result<crate::sta
     log::info!("WebGPU initialized with skeleton pipeline!");
     Ok(app)
 }
+
 // App methods for GPU operations
 use crate::state::App;
 
 #[wasm_bindgen]
 impl App {
+    /// Set the bone and head radii and regenerate the skeleton mesh
+    /// The bone radius also sets the floor clearance during playback.
+    pub fn set_radii(&mut self, bone: f32, head: f32) -> Result<(), JsValue> {
+        let radii = RadiiConfig { bone, head };
+        if !radii.is_valid() {
+            return Err(JsValue::from_str("Radii must be positive"));
+        }
+        if radii != self.state.engine.radii {
+            self.state
+                .gpu
+                .set_mesh(&generate_bind_pose_mesh_with_radii(&radii));
+            self.state.engine.radii = radii;
+        }
+        Ok(())
+    }
+
+    /// Add a box prop (meters, centered on its origin) and return its id
+    pub fn add_box_prop(&mut self, width: f32, height: f32, depth: f32) -> Result<u32, JsValue> {
+        if !([width, height, depth]
+            .iter()
+            .all(|&size| size > 0.0 && size.is_finite()))
+        {
+            return Err(JsValue::from_str("Prop dimensions must be positive"));
+        }
+        Ok(self
+            .state
+            .gpu
+            .add_prop(&generate_box_mesh(width, height, depth)))
+    }
+
+    /// Place a prop with a column-major 4x4 world matrix
+    pub fn set_prop_transform(&mut self, id: u32, matrix: &[f32]) -> Result<(), JsValue> {
+        if matrix.len() != 16 {
+            return Err(JsValue::from_str("Prop transform must have 16 elements"));
+        }
+        let gpu = &self.state.gpu;
+        let prop = gpu
+            .props
+            .get(&id)
+            .ok_or_else(|| JsValue::from_str("Unknown prop id"))?;
+        let transform = glam::Mat4::from_cols_slice(matrix).to_cols_array_2d();
+        gpu.queue.write_buffer(
+            &prop.transform_buffer,
+            0,
+            bytemuck::cast_slice(&[transform]),
+        );
+        Ok(())
+    }
+
+    /// Remove a prop; returns false if the id is unknown
+    pub fn remove_prop(&mut self, id: u32) -> bool {
+        self.state.gpu.props.remove(&id).is_some()
+    }
+
+    /// Change the present mode (vsync) at runtime
+    /// Unsupported modes fall back to one the surface offers; returns whether
+    /// the requested mode was applied as is.
+    pub fn set_present_mode(&mut self, mode: PresentMode) -> bool {
+        self.state.gpu.set_present_mode(mode) == mode.wgpu_mode()
+    }
+
     /// Resize the WebGPU surface when canvas size changes
     /// Call this from a window resize event listener
     pub fn resize_surface(&mut self, canvas_id: String) -> Result<(), JsValue> {
@@ -654,58 +1573,13 @@ impl App {
         canvas.set_width(width);
         canvas.set_height(height);
 
-        // Update surface configuration
+        // Update surface configuration and size-dependent render targets
         gpu.config.width = width;
         gpu.config.height = height;
-        gpu.surface.configure(&gpu.device, &gpu.config);
-
-        // Recreate depth texture with new dimensions and MSAA
-        let depth_texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("depth_texture"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: MSAA_SAMPLE_COUNT,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Depth24PlusStencil8,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-        gpu.depth_texture = depth_texture;
-        gpu.depth_view = gpu
-            .depth_texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
-
-        // Recreate MSAA color texture with new dimensions
-        let msaa_texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("msaa_texture"),
-            size: wgpu::Extent3d {
-                width,
-                height,
-                depth_or_array_layers: 1,
-            },
-            mip_level_count: 1,
-            sample_count: MSAA_SAMPLE_COUNT,
-            dimension: wgpu::TextureDimension::D2,
-            format: gpu.config.format,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            view_formats: &[],
-        });
-        gpu.msaa_texture = msaa_texture;
-        gpu.msaa_view = gpu
-            .msaa_texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        gpu.reconfigure_surface();
 
         // Update aspect ratio and projection matrix
-        let aspect = width as f32 / height as f32;
-        gpu.uniforms.aspect = aspect;
-        gpu.uniforms.screen_height = height as f32;
-        gpu.uniforms.projection =
-            glam::Mat4::perspective_rh(std::f32::consts::FRAC_PI_4, aspect, 0.1, 100.0)
-                .to_cols_array_2d();
+        gpu.uniforms.set_viewport(width, height, gpu.near, gpu.far);
 
         // Write updated uniforms to GPU
         gpu.queue.write_buffer(
@@ -722,7 +1596,12 @@ impl App {
     ///
     /// Call this after rotate_camera() to push the updated view matrix to the GPU.
     pub fn sync_camera(&mut self) {
-        let view = self.state.camera.view_matrix();
+        let camera = self
+            .state
+            .engine
+            .camera
+            .with_shake(&self.state.engine.camera_shake);
+        let view = camera.view_matrix();
         self.state.gpu.uniforms.view = view.to_cols_array_2d();
         self.state.gpu.queue.write_buffer(
             &self.state.gpu.uniform_buffer,
@@ -731,6 +1610,46 @@ impl App {
         );
     }
 
+    /// Select rigid segments or linear blend skinning at joints
+    pub fn set_skinning_mode(&mut self, mode: SkinningMode) {
+        let gpu = &mut self.state.gpu;
+        gpu.uniforms.skinning_mode = mode as u32;
+        gpu.queue.write_buffer(
+            &gpu.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[gpu.uniforms]),
+        );
+    }
+
+    /// Toggle the dark silhouette outline around the skeleton
+    /// `thickness` is in world units; `color` is RGB or RGBA display color (alpha unused).
+    pub fn set_outline(
+        &mut self,
+        enabled: bool,
+        thickness: f32,
+        color: &[f32],
+    ) -> Result<(), JsValue> {
+        let color = match *color {
+            [r, g, b] | [r, g, b, _] => [r, g, b, 1.0],
+            _ => {
+                return Err(JsValue::from_str(
+                    "Outline color must have 3 or 4 components",
+                ))
+            }
+        };
+        let gpu = &mut self.state.gpu;
+        gpu.uniforms
+            .set_outline(thickness, color)
+            .map_err(JsValue::from_str)?;
+        gpu.outline_enabled = enabled;
+        gpu.queue.write_buffer(
+            &gpu.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[gpu.uniforms]),
+        );
+        Ok(())
+    }
+
     /// Get the current camera view matrix as a Float32Array (16 floats, column-major)
     /// Used by TypeScript for gizmo rendering
     pub fn get_current_view_matrix(&self) -> Vec<f32> {
@@ -756,15 +1675,37 @@ impl App {
     }
 
     /// Render a frame
-    pub fn render_frame(&self) {
-        let gpu = &self.state.gpu;
-        let output = match gpu.surface.get_current_texture() {
-            Ok(t) => t,
-            Err(e) => {
-                log::warn!("Surface lost: {:?}", e);
-                return;
-            }
+    pub fn render_frame(&mut self) {
+        let Some(output) = self.state.gpu.acquire_frame() else {
+            return;
         };
+        if let Some(profiler) = self.state.gpu.profiler.as_mut() {
+            profiler.collect();
+        }
+
+        // Frustum culling of the skeleton instances (main, then ghosts)
+        let gpu = &mut self.state.gpu;
+        let uniforms = &gpu.uniforms;
+        let view_proj = glam::Mat4::from_cols_array_2d(&uniforms.projection)
+            * glam::Mat4::from_cols_array_2d(&uniforms.view);
+        let frustum = Frustum::from_view_projection(view_proj);
+        let eye = glam::Mat4::from_cols_array_2d(&uniforms.view)
+            .inverse()
+            .w_axis
+            .truncate();
+        let visible: Vec<bool> = gpu.instance_bounds[..=gpu.ghost_count as usize]
+            .iter()
+            .map(|&(center, radius)| frustum.intersects_sphere(center, radius))
+            .collect();
+        gpu.visible_instances = visible.iter().filter(|&&v| v).count() as u32;
+        let gpu = &self.state.gpu;
+
+        // Tint and camera distance per instance, to order the translucent draws
+        let instances: Vec<([f32; 4], f32)> = gpu.instance_bounds[..=gpu.ghost_count as usize]
+            .iter()
+            .zip(&gpu.instance_tints)
+            .map(|(&(center, _), &tint)| (tint, center.distance(eye)))
+            .collect();
 
         let view = output
             .texture
@@ -781,16 +1722,16 @@ impl App {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Skeleton Render Pass"),
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &gpu.msaa_view,        // Render to MSAA texture
-                    resolve_target: Some(&view), // Resolve to surface
+                    view: &gpu.targets.msaa_view, // Render to MSAA texture
+                    resolve_target: Some(&view),  // Resolve to surface
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(SKY_COLOR),
+                        load: wgpu::LoadOp::Clear(gpu.clear_color),
                         store: wgpu::StoreOp::Store,
                     },
                     depth_slice: None,
                 })],
                 depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                    view: &gpu.depth_view,
+                    view: &gpu.targets.depth_view,
                     depth_ops: Some(wgpu::Operations {
                         load: wgpu::LoadOp::Clear(1.0),
                         store: wgpu::StoreOp::Store,
@@ -800,48 +1741,407 @@ impl App {
                         store: wgpu::StoreOp::Store,
                     }),
                 }),
-                timestamp_writes: None,
+                timestamp_writes: gpu.profiler.as_ref().map(GpuProfiler::timestamp_writes),
                 occlusion_query_set: None,
                 multiview_mask: None,
             });
 
             // Draw background grid
             render_pass.set_pipeline(&gpu.grid_pipeline);
-            // Grid uses uniform bind group at index 0
-            render_pass.set_bind_group(0, &gpu.uniform_bind_group, &[]);
+            // Grid bind group: camera uniforms plus its line parameters
+            render_pass.set_bind_group(0, &gpu.grid_bind_group, &[]);
             render_pass.draw(0..6, 0..1);
 
-            // Draw drop shadow (before skeleton so it appears under the character)
-            render_pass.set_pipeline(&gpu.shadow_pipeline);
-            render_pass.set_bind_group(0, &gpu.uniform_bind_group, &[]);
-            render_pass.set_bind_group(1, &gpu.bone_bind_group, &[]);
+            // Draw the skeleton mesh passes: drop shadow (under the character),
+            // outline, opaque instances, then translucent ones (onion-skin ghosts)
             render_pass.set_vertex_buffer(0, gpu.vertex_buffer.slice(..));
             render_pass.set_stencil_reference(1);
-            render_pass.draw(0..gpu.vertex_count, 0..1);
-
-            // Draw skinned mesh
-            render_pass.set_pipeline(&gpu.skeleton_pipeline);
-            render_pass.set_bind_group(0, &gpu.uniform_bind_group, &[]);
-            render_pass.set_bind_group(1, &gpu.bone_bind_group, &[]);
-            render_pass.set_vertex_buffer(0, gpu.vertex_buffer.slice(..));
-
-            render_pass.draw(0..gpu.vertex_count, 0..1);
+            let instance_pipeline = |instance: usize| {
+                if is_translucent(gpu.instance_tints[instance]) {
+                    &gpu.ghost_pipeline
+                } else {
+                    &gpu.skeleton_pipeline
+                }
+            };
+            for pass in mesh_passes(gpu.outline_enabled, &instances) {
+                // The shadow is kept: it lands on the floor, outside the sphere
+                let culled = match pass {
+                    MeshPass::Shadow | MeshPass::Props => false,
+                    MeshPass::Outline | MeshPass::Skeleton => !visible[0],
+                    MeshPass::Ghost(i) => !visible[i + 1],
+                };
+                if culled {
+                    continue;
+                }
+                let (pipeline, bone_bind_group) = match pass {
+                    MeshPass::Shadow => (&gpu.shadow_pipeline, &gpu.bone_bind_group),
+                    MeshPass::Outline => (&gpu.outline_pipeline, &gpu.bone_bind_group),
+                    MeshPass::Skeleton => (instance_pipeline(0), &gpu.bone_bind_group),
+                    MeshPass::Ghost(i) => (instance_pipeline(i + 1), &gpu.ghost_bind_groups[i]),
+                    MeshPass::Props => {
+                        // Props share the skeleton pipeline, each with its own transform
+                        render_pass.set_pipeline(&gpu.skeleton_pipeline);
+                        render_pass.set_bind_group(0, &gpu.uniform_bind_group, &[]);
+                        for prop in gpu.props.values() {
+                            render_pass.set_vertex_buffer(0, prop.vertex_buffer.slice(..));
+                            render_pass.set_bind_group(1, &prop.bind_group, &[]);
+                            render_pass.draw(0..prop.vertex_count, 0..1);
+                        }
+                        render_pass.set_vertex_buffer(0, gpu.vertex_buffer.slice(..));
+                        continue;
+                    }
+                };
+                render_pass.set_pipeline(pipeline);
+                render_pass.set_bind_group(0, &gpu.uniform_bind_group, &[]);
+                render_pass.set_bind_group(1, bone_bind_group, &[]);
+                render_pass.draw(0..gpu.vertex_count, 0..1);
+            }
         }
 
+        // Read the timestamps back unless the previous readback is still in flight
+        let resolved = gpu
+            .profiler
+            .as_ref()
+            .filter(|profiler| profiler.resolve(&mut encoder));
         gpu.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(profiler) = resolved {
+            profiler.request_readback();
+        }
         output.present();
     }
+
+    /// Show or hide a render bone (e.g. to focus on the arms)
+    /// Takes effect with the next pose upload. Out-of-range indices are ignored.
+    pub fn set_bone_visible(&mut self, render_index: usize, visible: bool) {
+        if render_index >= RENDER_BONE_COUNT {
+            return;
+        }
+        let bit = 1 << render_index;
+        let gpu = &mut self.state.gpu;
+        if visible {
+            gpu.visible_bones |= bit;
+        } else {
+            gpu.visible_bones &= !bit;
+        }
+    }
+
+    /// Make every render bone visible again
+    pub fn show_all_bones(&mut self) {
+        self.state.gpu.visible_bones = ALL_BONES_VISIBLE;
+    }
+
+    /// Set the width of the floor grid lines (fraction of a 1 unit cell, default 0.02)
+    /// Lines every 5 units are drawn 1.5 times as wide. Lines thinner than a
+    /// pixel fade out rather than shimmer.
+    pub fn set_grid_line_width(&mut self, width: f32) -> Result<(), JsValue> {
+        let gpu = &mut self.state.gpu;
+        gpu.grid_uniforms
+            .set_line_width(width)
+            .map_err(JsValue::from_str)?;
+        gpu.queue.write_buffer(
+            &gpu.grid_uniform_buffer,
+            0,
+            bytemuck::bytes_of(&gpu.grid_uniforms),
+        );
+        Ok(())
+    }
+
+    /// Tint a skeleton instance (0 = main skeleton, then each ghost)
+    /// The color multiplies the shaded mesh; alpha below 1 draws the instance
+    /// blended after the opaque ones (ghosts default to `GHOST_ALPHA`).
+    /// Returns false for an out-of-range instance or components outside 0..=1.
+    pub fn set_instance_tint(&mut self, instance: usize, r: f32, g: f32, b: f32, a: f32) -> bool {
+        let tint = [r, g, b, a];
+        if instance >= INSTANCE_COUNT || !tint.iter().all(|c| (0.0..=1.0).contains(c)) {
+            return false;
+        }
+        let gpu = &mut self.state.gpu;
+        gpu.instance_tints[instance] = tint;
+        gpu.queue.write_buffer(
+            &gpu.instance_tint_buffer,
+            instance_tint_offset(instance),
+            bytemuck::cast_slice(&tint),
+        );
+        true
+    }
+
+    /// Number of skeleton instances (main and ghosts) drawn in the last frame
+    /// Debug counter for frustum culling.
+    pub fn get_visible_instance_count(&self) -> u32 {
+        self.state.gpu.visible_instances
+    }
+
+    /// GPU time of the last profiled render pass in milliseconds
+    /// 0 where timestamp queries are unavailable (common in browsers) or before
+    /// the first result; results lag a frame or more behind.
+    pub fn get_last_gpu_time_ms(&self) -> f32 {
+        self.state
+            .gpu
+            .profiler
+            .as_ref()
+            .map_or(0.0, |profiler| profiler.last_gpu_time_ms)
+    }
 }
 
 // Internal App methods (not exported to JavaScript)
 impl App {
     /// Update bone matrices uniform buffer
-    /// Call this to push new skeleton pose to the GPU
-    pub fn update_bone_uniforms(&self, matrices: &[glam::Mat4]) {
+    /// Call this to push new skeleton pose to the GPU; an unchanged pose is not re-uploaded.
+    pub fn update_bone_uniforms(&mut self, matrices: &[glam::Mat4]) {
+        let gpu = &mut self.state.gpu;
+        if let Ok(matrices) = matrices.try_into() {
+            gpu.instance_bounds[0] = matrices_bounding_sphere(matrices, &self.state.engine.radii);
+        }
+        let mut matrices = matrices.to_vec();
+        apply_bone_visibility(&mut matrices, gpu.visible_bones);
+        let matrices = matrices.as_slice();
+        let (queue, buffer) = (&gpu.queue, &gpu.bone_uniform_buffer);
+        gpu.bone_upload.upload_if_changed(matrices, |matrices| {
+            queue.write_buffer(buffer, 0, bytemuck::cast_slice(matrices));
+        });
+    }
+
+    /// Skin and upload several poses: the first drives the main skeleton, the
+    /// rest the ghost instances (see `update_ghost_uniforms`)
+    /// Uses the compute pass when available, otherwise the CPU path.
+    pub fn update_skeleton_poses(&mut self, poses: &[RotationPose]) {
+        let Some((main, ghosts)) = poses.split_first() else {
+            return;
+        };
+        let ghosts = &ghosts[..ghosts.len().min(MAX_GHOSTS)];
+        let gpu = &mut self.state.gpu;
+        let Some(skinning) = gpu.skinning.as_mut() else {
+            self.update_bone_uniforms(&main.compute_bone_matrices());
+            let ghost_matrices: Vec<_> = ghosts.iter().map(|p| p.compute_bone_matrices()).collect();
+            self.update_ghost_uniforms(&ghost_matrices);
+            return;
+        };
+
+        let instances: Vec<SkinningInstance> = poses[..=ghosts.len()]
+            .iter()
+            .map(|pose| SkinningInstance::from(pose).with_visible_bones(gpu.visible_bones))
+            .collect();
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Skinning Encoder"),
+            });
+        skinning.dispatch(&gpu.device, &gpu.queue, &mut encoder, &instances);
+        skinning.copy_matrices(&mut encoder, 0, &gpu.bone_uniform_buffer);
+        for (i, buffer) in gpu.ghost_bone_buffers.iter().take(ghosts.len()).enumerate() {
+            skinning.copy_matrices(&mut encoder, i + 1, buffer);
+        }
+        gpu.queue.submit(std::iter::once(encoder.finish()));
+        gpu.bone_upload.invalidate();
+        gpu.ghost_count = ghosts.len() as u32;
+        for (bounds, pose) in gpu.instance_bounds.iter_mut().zip(&poses[..=ghosts.len()]) {
+            *bounds = pose.bounding_sphere();
+        }
+    }
+
+    /// Upload onion-skin ghost poses (at most `MAX_GHOSTS`, extra ones are ignored)
+    /// An empty slice disables ghost rendering.
+    pub fn update_ghost_uniforms(&mut self, ghosts: &[[glam::Mat4; RENDER_BONE_COUNT]]) {
+        let gpu = &mut self.state.gpu;
+        let count = ghosts.len().min(MAX_GHOSTS);
+        for (buffer, matrices) in gpu.ghost_bone_buffers.iter().zip(&ghosts[..count]) {
+            let mut matrices = *matrices;
+            apply_bone_visibility(&mut matrices, gpu.visible_bones);
+            gpu.queue
+                .write_buffer(buffer, 0, bytemuck::cast_slice(&matrices));
+        }
+        for (bounds, matrices) in gpu.instance_bounds[1..].iter_mut().zip(&ghosts[..count]) {
+            *bounds = matrices_bounding_sphere(matrices, &self.state.engine.radii);
+        }
+        gpu.ghost_count = count as u32;
+    }
+
+    /// Update per-bone tint colors uniform buffer
+    /// RGBA per render bone; alpha is the tint strength (0 = untinted)
+    pub fn update_bone_colors(&self, colors: &[[f32; 4]]) {
         self.state.gpu.queue.write_buffer(
-            &self.state.gpu.bone_uniform_buffer,
+            &self.state.gpu.bone_color_buffer,
             0,
-            bytemuck::cast_slice(matrices),
+            bytemuck::cast_slice(colors),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_resize_keeps_configured_clip_planes() {
+        let (near, far) = (0.5, 50.0);
+        let mut uniforms = Uniforms::default();
+        uniforms.set_viewport(800, 600, near, far);
+        uniforms.set_viewport(1920, 1080, near, far);
+
+        assert!((uniforms.aspect - 16.0 / 9.0).abs() < 1e-6);
+        assert_eq!(uniforms.screen_height, 1080.0);
+        let projection = glam::Mat4::from_cols_array_2d(&uniforms.projection);
+        assert!(projection.abs_diff_eq(projection_matrix(16.0 / 9.0, near, far), 1e-6));
+
+        // The configured planes still map to the ends of the depth range
+        let near_ndc = projection.project_point3(glam::Vec3::new(0.0, 0.0, -near));
+        let far_ndc = projection.project_point3(glam::Vec3::new(0.0, 0.0, -far));
+        assert!(near_ndc.z.abs() < 1e-4, "Near plane z = {}", near_ndc.z);
+        assert!(
+            (far_ndc.z - 1.0).abs() < 1e-4,
+            "Far plane z = {}",
+            far_ndc.z
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_surface_format_prefers_srgb_when_asked() {
+        use wgpu::TextureFormat::{Bgra8Unorm, Bgra8UnormSrgb, Rgba16Float};
+
+        let offered = [Bgra8Unorm, Rgba16Float, Bgra8UnormSrgb];
+        assert_eq!(choose_surface_format(&offered, true), Some(Bgra8UnormSrgb));
+        assert_eq!(choose_surface_format(&offered, false), Some(Bgra8Unorm));
+        // WebGL offers no sRGB surface: fall back to the first format
+        assert_eq!(
+            choose_surface_format(&[Rgba16Float, Bgra8Unorm], true),
+            Some(Rgba16Float)
+        );
+        assert_eq!(choose_surface_format(&[], true), None);
+
+        let clear = surface_clear_color(SKY_COLOR, true);
+        assert!(clear.r < SKY_COLOR.r && clear.b == 1.0);
+        assert_eq!(surface_clear_color(SKY_COLOR, false), SKY_COLOR);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_present_mode_falls_back_when_unsupported() {
+        use wgpu::PresentMode as Mode;
+
+        let supported = [Mode::Fifo, Mode::Mailbox];
+        let mut config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8Unorm,
+            width: 640,
+            height: 480,
+            present_mode: Mode::AutoVsync,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        };
+
+        config.present_mode = choose_present_mode(PresentMode::Mailbox, &supported);
+        assert_eq!(config.present_mode, Mode::Mailbox);
+        config.present_mode = choose_present_mode(PresentMode::Immediate, &supported);
+        assert_eq!(config.present_mode, Mode::Fifo);
+        // Auto modes are resolved by wgpu, so they are always accepted
+        config.present_mode = choose_present_mode(PresentMode::AutoNoVsync, &supported);
+        assert_eq!(config.present_mode, Mode::AutoNoVsync);
+        assert_eq!(
+            choose_present_mode(PresentMode::Immediate, &[]),
+            Mode::AutoVsync
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_outline_adds_mesh_draw() {
+        let instances = [
+            (DEFAULT_INSTANCE_TINTS[0], 3.0),
+            (DEFAULT_INSTANCE_TINTS[1], 3.0),
+        ];
+        let plain = mesh_passes(false, &instances);
+        assert_eq!(
+            plain,
+            vec![
+                MeshPass::Shadow,
+                MeshPass::Skeleton,
+                MeshPass::Props,
+                MeshPass::Ghost(0)
+            ]
+        );
+
+        // Enabling the outline draws the mesh once more, right before the skeleton
+        let outlined = mesh_passes(true, &instances);
+        assert_eq!(outlined.len(), plain.len() + 1);
+        let outline = outlined
+            .iter()
+            .position(|p| *p == MeshPass::Outline)
+            .unwrap();
+        assert_eq!(outlined[outline + 1], MeshPass::Skeleton);
+
+        let mut uniforms = Uniforms::default();
+        uniforms.set_outline(0.02, [1.0, 0.0, 0.0, 1.0]).unwrap();
+        assert_eq!(uniforms.outline_thickness, 0.02);
+        assert_eq!(uniforms.outline_color, [1.0, 0.0, 0.0, 1.0]);
+        assert!(uniforms.set_outline(-0.01, DEFAULT_OUTLINE_COLOR).is_err());
+        assert!(uniforms
+            .set_outline(f32::NAN, DEFAULT_OUTLINE_COLOR)
+            .is_err());
+        assert_eq!(uniforms.outline_thickness, 0.02);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_translucent_instance_is_blended_last() {
+        let half = [1.0, 0.5, 0.5, 0.5];
+        // Main skeleton at half alpha, an opaque ghost, and a default (translucent) ghost
+        let instances = [
+            (half, 2.0),
+            ([1.0; 4], 3.0),
+            (DEFAULT_INSTANCE_TINTS[2], 4.0),
+        ];
+        assert_eq!(
+            mesh_passes(true, &instances),
+            vec![
+                MeshPass::Shadow,
+                MeshPass::Ghost(0),
+                // Props with the opaque draws, before anything blended over them
+                MeshPass::Props,
+                // Translucent, farthest first; no outline around the see-through skeleton
+                MeshPass::Ghost(1),
+                MeshPass::Skeleton,
+            ]
+        );
+        // Fully transparent instances are not drawn
+        let hidden = [([1.0, 1.0, 1.0, 0.0], 2.0)];
+        assert_eq!(
+            mesh_passes(false, &hidden),
+            vec![MeshPass::Shadow, MeshPass::Props]
+        );
+
+        let mut tints = DEFAULT_INSTANCE_TINTS;
+        tints[0] = half;
+        let bytes = instance_tint_bytes(&tints);
+        assert_eq!(
+            bytes.len() as u64,
+            INSTANCE_COUNT as u64 * INSTANCE_TINT_STRIDE
+        );
+        for (instance, tint) in tints.iter().enumerate() {
+            let offset = instance_tint_offset(instance) as usize;
+            assert_eq!(offset % 256, 0);
+            let written: &[f32] = bytemuck::cast_slice(&bytes[offset..offset + 16]);
+            assert_eq!(written, tint);
+        }
+        assert!(is_translucent(tints[0]) && is_translucent(tints[1]));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_grid_line_width_is_written_to_uniforms() {
+        let mut grid = GridUniforms::default();
+        grid.set_line_width(0.05).unwrap();
+        let written: &[f32] = bytemuck::cast_slice(bytemuck::bytes_of(&grid));
+        assert_eq!(written[0], 0.05);
+        assert_eq!(written[1], 0.05 * MAJOR_GRID_LINE_RATIO);
+        assert_eq!(written[2], GridUniforms::default().aa_strength);
+
+        assert!(grid.set_line_width(-0.01).is_err());
+        assert!(grid.set_line_width(0.8).is_err());
+        assert!(grid.set_line_width(f32::NAN).is_err());
+        assert_eq!(grid.line_width, 0.05);
+    }
+}
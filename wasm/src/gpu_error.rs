@@ -0,0 +1,102 @@
+//! GPU initialization errors
+//!
+//! Kept outside the wasm-only `gpu` module so the stage tagging can be tested
+//! natively. Each variant carries the underlying wgpu/browser error text; it is
+//! turned into a JS error string only at the `init_gpu` boundary.
+
+use wasm_bindgen::JsValue;
+
+/// Which stage of `init_gpu` failed, and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpuInitError {
+    /// No browser window or document
+    NoWindow,
+    /// No `<canvas>` element with this id
+    NoCanvas(String),
+    /// The canvas could not be turned into a wgpu surface
+    SurfaceCreation(String),
+    /// No adapter matched the requested backends
+    NoAdapter(String),
+    /// The requested depth format cannot be rendered to with MSAA
+    UnsupportedDepthFormat(String),
+    /// The adapter refused to create a device with the required features/limits
+    DeviceRequest(String),
+}
+
+impl std::fmt::Display for GpuInitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GpuInitError::NoWindow => f.write_str("No window"),
+            GpuInitError::NoCanvas(id) => write!(f, "Canvas not found: {}", id),
+            GpuInitError::SurfaceCreation(e) => write!(f, "Failed to create surface: {}", e),
+            GpuInitError::NoAdapter(e) => write!(f, "Failed to find GPU adapter: {}", e),
+            GpuInitError::UnsupportedDepthFormat(format) => write!(
+                f,
+                "Depth format {} is not supported by this adapter",
+                format
+            ),
+            GpuInitError::DeviceRequest(e) => write!(f, "Failed to create device: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for GpuInitError {}
+
+impl From<GpuInitError> for JsValue {
+    fn from(error: GpuInitError) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+/// Create a surface, keeping the wgpu error text on failure
+pub fn create_surface<'window>(
+    instance: &wgpu::Instance,
+    target: impl Into<wgpu::SurfaceTarget<'window>>,
+) -> Result<wgpu::Surface<'window>, GpuInitError> {
+    instance
+        .create_surface(target)
+        .map_err(|e| GpuInitError::SurfaceCreation(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Window with placeholder X11 handles; never dereferenced without a backend
+    #[cfg(not(target_arch = "wasm32"))]
+    struct FakeWindow;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl wgpu::rwh::HasWindowHandle for FakeWindow {
+        fn window_handle(&self) -> Result<wgpu::rwh::WindowHandle<'_>, wgpu::rwh::HandleError> {
+            let raw = wgpu::rwh::XlibWindowHandle::new(1);
+            // SAFETY: no backend is enabled, so the handle is never used
+            Ok(unsafe { wgpu::rwh::WindowHandle::borrow_raw(raw.into()) })
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    impl wgpu::rwh::HasDisplayHandle for FakeWindow {
+        fn display_handle(&self) -> Result<wgpu::rwh::DisplayHandle<'_>, wgpu::rwh::HandleError> {
+            let raw = wgpu::rwh::XlibDisplayHandle::new(None, 0);
+            // SAFETY: as above
+            Ok(unsafe { wgpu::rwh::DisplayHandle::borrow_raw(raw.into()) })
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_surface_failure_is_tagged() {
+        // An instance without backends cannot create a surface for any window
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::empty(),
+            ..Default::default()
+        });
+        let error = create_surface(&instance, FakeWindow).err().unwrap();
+        let GpuInitError::SurfaceCreation(ref reason) = error else {
+            panic!("expected SurfaceCreation, got {:?}", error);
+        };
+        assert!(!reason.is_empty());
+        assert!(error.to_string().starts_with("Failed to create surface: "));
+    }
+}
@@ -10,8 +10,30 @@ use glam::Vec3;
 /// # Returns
 /// * `Vec<Vec3>` - New world positions for the joints
 pub fn solve_fabrik(
+    joints: Vec<Vec3>,
+    lengths: &[f32],
+    target: Vec3,
+    max_iterations: usize,
+    tolerance: f32,
+) -> Vec<Vec3> {
+    solve_fabrik_weighted(joints, lengths, &[], 0.0, target, max_iterations, tolerance)
+}
+
+/// FABRIK where some segments resist turning
+///
+/// `stiffness[i]` (0..=1) holds segment i (joint i to i + 1) toward its starting
+/// direction after every forward pass: 0 turns freely, 1 never turns. Missing
+/// entries count as 0. Stiff segments leave more of the reach to the others, and
+/// an unreachable target is approached iteratively instead of by stretching.
+///
+/// `damping` (0..1) blends each backward pass toward the previous iteration's
+/// joints, trading convergence speed for stability on hard-to-reach targets.
+/// 0 is plain FABRIK.
+pub fn solve_fabrik_weighted(
     mut joints: Vec<Vec3>,
     lengths: &[f32],
+    stiffness: &[f32],
+    damping: f32,
     target: Vec3,
     max_iterations: usize,
     tolerance: f32,
@@ -21,12 +43,20 @@ pub fn solve_fabrik(
         return joints;
     }
 
+    let stiffness_of = |i: usize| stiffness.get(i).copied().unwrap_or(0.0).clamp(0.0, 1.0);
+    let start_dirs: Vec<Vec3> = joints
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).normalize_or_zero())
+        .collect();
+    let any_stiff = (0..n - 1).any(|i| stiffness_of(i) > 0.0);
+    let damping = damping.clamp(0.0, 1.0);
+
     // Check reachability
     let dist = joints[0].distance(target);
     let total_len: f32 = lengths.iter().sum();
 
     // If unreachable, stretch straight towards target
-    if dist > total_len {
+    if dist > total_len && !any_stiff {
         let dir = (target - joints[0]).normalize_or_zero();
         for i in 0..n - 1 {
             joints[i + 1] = joints[i] + dir * lengths[i];
@@ -40,6 +70,8 @@ pub fn solve_fabrik(
                 break;
             }
 
+            let previous = (damping > 0.0).then(|| joints.clone());
+
             // Backward pass (end -> start)
             joints[n - 1] = target;
             for i in (0..n - 1).rev() {
@@ -47,10 +79,24 @@ pub fn solve_fabrik(
                 joints[i] = joints[i + 1] + dir * lengths[i];
             }
 
+            // Damping: hold back the new positions; the forward pass below
+            // restores the base and bone lengths
+            if let Some(previous) = previous {
+                for (joint, prev) in joints.iter_mut().zip(previous) {
+                    *joint = prev.lerp(*joint, 1.0 - damping);
+                }
+            }
+
             // Forward pass (start -> end)
             joints[0] = base_pos;
             for i in 0..n - 1 {
-                let dir = (joints[i + 1] - joints[i]).normalize_or_zero();
+                let mut dir = (joints[i + 1] - joints[i]).normalize_or_zero();
+                let stiff = stiffness_of(i);
+                if stiff > 0.0 {
+                    dir = start_dirs[i]
+                        .lerp(dir, 1.0 - stiff)
+                        .normalize_or(start_dirs[i]);
+                }
                 joints[i + 1] = joints[i] + dir * lengths[i];
             }
         }
@@ -137,6 +183,85 @@ mod tests {
         assert_eq!(result[0], Vec3::ZERO);
     }
 
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fabrik_stiff_segment_turns_less() {
+        let joints = vec![
+            Vec3::ZERO,
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let lengths = vec![1.0, 1.0];
+        let target = Vec3::new(1.0, 1.2, 0.0);
+        let first_turn = |result: &[Vec3]| (result[1] - result[0]).angle_between(Vec3::X);
+
+        let free = solve_fabrik_weighted(joints.clone(), &lengths, &[], 0.0, target, 20, 0.001);
+        let stiff = solve_fabrik_weighted(joints.clone(), &lengths, &[0.8], 0.0, target, 20, 0.001);
+        let rigid = solve_fabrik_weighted(joints, &lengths, &[1.0], 0.0, target, 20, 0.001);
+        assert!(first_turn(&stiff) < first_turn(&free));
+        assert!(first_turn(&rigid) < 1e-5);
+        assert!((stiff[1].distance(stiff[2]) - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_fabrik_damping_settles_monotonically() {
+        // Folded chain reaching almost its full length: undamped, the first pass
+        // overshoots and later iterations grow again before settling
+        let joints = vec![
+            Vec3::ZERO,
+            Vec3::new(-0.6, -0.5, -0.6),
+            Vec3::new(0.0, 0.1, 0.0),
+            Vec3::new(0.1, -0.8, -0.4),
+        ];
+        let lengths: Vec<f32> = joints
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .collect();
+        let reach: f32 = lengths.iter().sum();
+        let target = Vec3::new(2.0, 1.2, 1.7).normalize() * (reach - 0.02);
+
+        // Largest joint movement made by each iteration
+        let steps = |damping: f32| {
+            let mut previous = joints.clone();
+            (1..16)
+                .map(|iterations| {
+                    let solved = solve_fabrik_weighted(
+                        joints.clone(),
+                        &lengths,
+                        &[],
+                        damping,
+                        target,
+                        iterations,
+                        0.0,
+                    );
+                    let step = solved
+                        .iter()
+                        .zip(&previous)
+                        .map(|(a, b)| a.distance(*b))
+                        .fold(0.0, f32::max);
+                    previous = solved;
+                    step
+                })
+                .collect::<Vec<f32>>()
+        };
+        let decreasing = |steps: &[f32]| steps.windows(2).all(|pair| pair[1] <= pair[0]);
+
+        let undamped = steps(0.0);
+        let damped = steps(0.5);
+        assert!(!decreasing(&undamped), "{:?}", undamped);
+        assert!(decreasing(&damped), "{:?}", damped);
+        assert!(damped[0] < undamped[0]);
+
+        // Damping only slows the solve down; lengths and base are kept
+        let solved = solve_fabrik_weighted(joints.clone(), &lengths, &[], 0.5, target, 200, 1e-4);
+        assert_eq!(solved[0], Vec3::ZERO);
+        assert!(solved[3].distance(target) < 1e-3);
+        for (i, length) in lengths.iter().enumerate() {
+            assert!((solved[i].distance(solved[i + 1]) - length).abs() < 1e-4);
+        }
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_fabrik_preserves_base() {
@@ -12,15 +12,26 @@ pub mod bone;
 pub use bone as bone_hierarchy;
 pub use bone::AnimationId;
 pub mod camera;
+pub mod editor;
+pub mod engine;
 
 #[cfg(target_arch = "wasm32")]
 pub mod gpu;
+pub mod gpu_error;
 pub mod ik;
 mod math;
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod native_gpu;
+pub mod profiler;
+pub mod render_targets;
 pub mod skeleton;
+// FBX joints; the mesh follows the hierarchy (see `skeleton::rest_joint_position`)
+#[allow(dead_code)]
 mod skeleton_constants;
+pub mod skinning;
 #[cfg(target_arch = "wasm32")]
 pub mod state;
+pub mod time;
 
 use wasm_bindgen::prelude::*;
 
@@ -34,23 +45,94 @@ pub const EPSILON: f32 = 1e-6;
 
 // Re-exports for WASM API
 #[cfg(target_arch = "wasm32")]
-pub use gpu::init_gpu;
+pub use gpu::{init_gpu, init_gpu_with_depth_format};
 #[cfg(target_arch = "wasm32")]
 pub use state::App;
 
 pub use math::Mat4;
 pub use math::Mat4Extended;
 
-use crate::animation::{sample_animation, AnimationLibrary, PlaybackState};
+use crate::animation::{
+    sample_animation, AnimationLibrary, FloorSmoothing, PlaybackState, SecondaryMotion,
+    SpringSettings, WindSettings,
+};
 use crate::skeleton::RENDER_BONE_COUNT;
 
+/// Sample playback, with ambient wind layered on if enabled
+fn sample_with_wind(
+    library: &AnimationLibrary,
+    playback: &PlaybackState,
+    wind: Option<WindSettings>,
+) -> bone::RotationPose {
+    let pose = sample_animation(library, playback);
+    match wind {
+        Some(wind) => pose.apply_wind(playback.time, wind.strength, wind.direction),
+        None => pose,
+    }
+}
+
+/// Longest step fed to the secondary motion springs; larger gaps restart them
+const MAX_SPRING_DT: f32 = 0.1;
+
+/// Keep the pose above the floor, easing the lift if `floor` has a time constant
+fn constrain_to_floor(
+    pose: bone::RotationPose,
+    playback: &PlaybackState,
+    floor: &mut FloorSmoothing,
+    floor_radius: f32,
+) -> bone::RotationPose {
+    if floor.time_constant <= 0.0 {
+        return pose.apply_floor_constraint_with_radius(floor_radius);
+    }
+
+    // Like the springs, jumps in time snap to the needed lift
+    let dt = playback.time - floor.last_time.unwrap_or(playback.time);
+    if !(0.0..=MAX_SPRING_DT).contains(&dt) {
+        floor.reset();
+    }
+    floor.last_time = Some(playback.time);
+    floor.step(pose, floor_radius, dt.clamp(0.0, MAX_SPRING_DT))
+}
+
 /// Compute bone matrices from animation playback state
 pub fn compute_matrices_from_playback(
     library: &AnimationLibrary,
     playback: &PlaybackState,
+    wind: Option<WindSettings>,
+    floor: &mut FloorSmoothing,
+    floor_radius: f32,
 ) -> [glam::Mat4; RENDER_BONE_COUNT] {
-    let pose = sample_animation(library, playback);
-    let pose = pose.apply_floor_constraint();
+    let pose = sample_with_wind(library, playback, wind);
+    let pose = constrain_to_floor(pose, playback, floor, floor_radius);
+    pose.compute_bone_matrices()
+}
+
+/// Compute bone matrices from playback, with spring-driven secondary motion
+pub fn compute_matrices_with_secondary_motion(
+    library: &AnimationLibrary,
+    playback: &PlaybackState,
+    motion: &mut SecondaryMotion,
+    spring: SpringSettings,
+    wind: Option<WindSettings>,
+    floor: &mut FloorSmoothing,
+    floor_radius: f32,
+) -> [glam::Mat4; RENDER_BONE_COUNT] {
+    let target = sample_with_wind(library, playback, wind);
+
+    // Time jumping backwards or far ahead (exercise switch, tab in background) restarts the springs
+    let dt = playback.time - motion.last_time.unwrap_or(playback.time);
+    if !(0.0..=MAX_SPRING_DT).contains(&dt) {
+        motion.reset();
+    }
+    motion.last_time = Some(playback.time);
+
+    let pose = motion.step(
+        &target,
+        dt.clamp(0.0, MAX_SPRING_DT),
+        spring.stiffness,
+        spring.damping,
+    );
+    let pose = constrain_to_floor(pose, playback, floor, floor_radius);
     pose.compute_bone_matrices()
 }
 
@@ -60,12 +142,32 @@ pub fn compute_matrices_from_playback(
 impl App {
     /// Update skeleton from the current animation playback state.
     /// Call this every frame before render_frame().
-    pub fn update_skeleton_from_playback(&self) {
-        let matrices =
-            compute_matrices_from_playback(&self.state.animation_library, &self.state.playback);
+    pub fn update_skeleton_from_playback(&mut self) {
+        let matrices = self.state.engine.playback_matrices();
         self.update_bone_uniforms(&matrices);
     }
 }
+
+/// Get a bone's snake_case name (as in the JSON schema), or "" if out of range
+#[wasm_bindgen]
+pub fn bone_name(index: usize) -> String {
+    bone::BoneId::from_index(index)
+        .map(|bone| bone.name().to_string())
+        .unwrap_or_default()
+}
+
+/// Get a bone's index from its snake_case name, or -1 if unknown
+#[wasm_bindgen]
+pub fn bone_index_from_name(name: &str) -> i32 {
+    bone::BoneId::from_name(name).map_or(-1, |bone| bone.index() as i32)
+}
+
+/// Bone hierarchy (parents, lengths, rest directions, names) and root layout as JSON
+#[wasm_bindgen]
+pub fn export_skeleton_definition_json() -> String {
+    bone::export_skeleton_definition_json()
+}
+
 /// Simple test function
 #[wasm_bindgen]
 pub fn add(a: i32, b: i32) -> i32 {
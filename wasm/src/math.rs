@@ -1,6 +1,7 @@
 //! Linear algebra primitives using glam with preserved handwritten kernels.
 
 pub use glam::Mat4;
+use glam::{Quat, Vec3};
 // Extension of glam Mat4, with hand written kernels for multiplication and transpose
 pub trait Mat4Extended {
     /// Scalar implementation of matrix multiplication
@@ -184,6 +185,40 @@ impl Mat4Extended for Mat4 {
     }
 }
 
+/// Split a rotation into `(swing, twist)` with `q = swing * twist`
+///
+/// `twist` turns about the unit `axis` and `swing` about an axis perpendicular
+/// to it, so a bone's bend and its roll along `axis` can be edited apart
+/// without the gimbal lock of Euler angles. A half turn about a perpendicular
+/// axis has no defined twist; it is then all swing.
+pub fn swing_twist(q: Quat, axis: Vec3) -> (Quat, Quat) {
+    let projected = axis * Vec3::new(q.x, q.y, q.z).dot(axis);
+    let twist = Quat::from_xyzw(projected.x, projected.y, projected.z, q.w);
+    let twist = if twist.length_squared() < crate::EPSILON {
+        Quat::IDENTITY
+    } else {
+        twist.normalize()
+    };
+    (q * twist.inverse(), twist)
+}
+
+/// Signed angle in radians of a twist from `swing_twist` about its `axis`
+/// In -PI..=PI, positive counter-clockwise looking down `axis`.
+pub fn twist_angle(twist: Quat, axis: Vec3) -> f32 {
+    let angle = 2.0
+        * Vec3::new(twist.x, twist.y, twist.z)
+            .dot(axis)
+            .atan2(twist.w);
+    // Wrap q and -q (the same rotation) to the same angle
+    if angle > std::f32::consts::PI {
+        angle - std::f32::consts::TAU
+    } else if angle < -std::f32::consts::PI {
+        angle + std::f32::consts::TAU
+    } else {
+        angle
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,4 +292,42 @@ mod tests {
             assert_matrix_approx_eq(multiplied_fast, multiplied_glam);
         }
     }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_swing_twist_recombines() {
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            let axis = Vec3::new(
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            )
+            .normalize_or(Vec3::Y);
+            let q = Quat::from_euler(
+                glam::EulerRot::XYZ,
+                rng.random_range(-3.0..3.0),
+                rng.random_range(-3.0..3.0),
+                rng.random_range(-3.0..3.0),
+            );
+            let (swing, twist) = swing_twist(q, axis);
+            assert!((swing * twist).dot(q).abs() > 1.0 - 1e-5);
+
+            // Twist turns about the axis only, swing moves it off-axis without rolling
+            assert!((twist * axis).abs_diff_eq(axis, 1e-4));
+            let (twist_axis, angle) = twist.to_axis_angle();
+            assert!(angle.abs() < 1e-3 || twist_axis.cross(axis).length() < 1e-3);
+            let (_, swing_twist_part) = swing_twist(swing, axis);
+            assert!(swing_twist_part.dot(Quat::IDENTITY).abs() > 1.0 - 1e-5);
+        }
+
+        // A bend about X after a roll about Y splits back into the two
+        let (swing, twist) = swing_twist(
+            Quat::from_rotation_x(0.3) * Quat::from_rotation_y(0.7),
+            Vec3::Y,
+        );
+        assert!(swing.abs_diff_eq(Quat::from_rotation_x(0.3), 1e-5));
+        assert!((twist_angle(twist, Vec3::Y) - 0.7).abs() < 1e-3);
+        assert!((twist_angle(twist.inverse(), Vec3::Y) + 0.7).abs() < 1e-3);
+    }
 }
@@ -0,0 +1,21 @@
+//! Native GPU harness for tests
+//!
+//! Tests that need a device return early when the machine has no adapter.
+
+/// Block on a future without an async runtime (wgpu native resolves them during polls)
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = std::task::Context::from_waker(std::task::Waker::noop());
+    loop {
+        if let std::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+/// The default adapter, or None when there is none to test on
+pub fn adapter() -> Option<wgpu::Adapter> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    block_on(instance.request_adapter(&Default::default())).ok()
+}
@@ -0,0 +1,227 @@
+//! GPU profiling: how long the render pass takes on the GPU
+//!
+//! `GpuProfiler` writes a timestamp at the start and end of a pass, resolves
+//! them into a buffer and reads it back asynchronously. The result arrives a
+//! frame or more later and is kept in `last_gpu_time_ms`.
+//!
+//! Timestamp queries are an optional feature that browsers often withhold;
+//! check `supports_timestamps` and leave the profiler out where it is missing.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Timestamps per frame: start and end of the render pass
+const QUERY_COUNT: u32 = 2;
+
+/// Bytes of resolved timestamps
+const RESOLVE_SIZE: u64 = QUERY_COUNT as u64 * wgpu::QUERY_SIZE as u64;
+
+/// Readback buffer is free for the next resolve
+const READBACK_IDLE: u8 = 0;
+/// Readback buffer is waiting on `map_async`
+const READBACK_PENDING: u8 = 1;
+/// Readback buffer is mapped and holds a result
+const READBACK_READY: u8 = 2;
+
+/// Check if the adapter can write timestamps around passes
+pub fn supports_timestamps(adapter: &wgpu::Adapter) -> bool {
+    adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY)
+}
+
+/// Convert a pair of raw timestamps to milliseconds
+/// `period` is nanoseconds per tick (`Queue::get_timestamp_period`).
+/// Returns None if the end is before the start (e.g. the counter was reset).
+pub fn timestamp_delta_ms(begin: u64, end: u64, period: f32) -> Option<f32> {
+    let ticks = end.checked_sub(begin)?;
+    Some((ticks as f64 * period as f64 / 1_000_000.0) as f32)
+}
+
+/// Render pass timestamp queries with asynchronous readback
+pub struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// `READBACK_*` state, shared with the `map_async` callback
+    readback_state: Arc<AtomicU8>,
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    /// Most recent measured GPU time of the pass, 0 until the first readback
+    pub last_gpu_time_ms: f32,
+}
+
+impl GpuProfiler {
+    /// Create the query set and buffers
+    /// The device must have been created with `Features::TIMESTAMP_QUERY`.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: RESOLVE_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Readback Buffer"),
+            size: RESOLVE_SIZE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            readback_state: Arc::new(AtomicU8::new(READBACK_IDLE)),
+            period: queue.get_timestamp_period(),
+            last_gpu_time_ms: 0.0,
+        }
+    }
+
+    /// Timestamp writes for the start and end of the profiled pass
+    pub fn timestamp_writes(&self) -> wgpu::RenderPassTimestampWrites<'_> {
+        wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    /// Record resolving this frame's timestamps into the readback buffer
+    /// Skipped while the previous readback is in flight; returns whether it was
+    /// recorded, in which case call `request_readback` after submitting.
+    pub fn resolve(&self, encoder: &mut wgpu::CommandEncoder) -> bool {
+        if self.readback_state.load(Ordering::Acquire) != READBACK_IDLE {
+            return false;
+        }
+        encoder.resolve_query_set(&self.query_set, 0..QUERY_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            RESOLVE_SIZE,
+        );
+        true
+    }
+
+    /// Start mapping the readback buffer (after the resolving submit)
+    pub fn request_readback(&self) {
+        let state = Arc::clone(&self.readback_state);
+        state.store(READBACK_PENDING, Ordering::Release);
+        self.readback_buffer
+            .map_async(wgpu::MapMode::Read, .., move |result| {
+                let next = if result.is_ok() {
+                    READBACK_READY
+                } else {
+                    READBACK_IDLE
+                };
+                state.store(next, Ordering::Release);
+            });
+    }
+
+    /// Take a finished readback into `last_gpu_time_ms`, freeing the buffer
+    /// Call once per frame before `resolve`; does nothing while none is ready.
+    pub fn collect(&mut self) {
+        if self.readback_state.load(Ordering::Acquire) != READBACK_READY {
+            return;
+        }
+        {
+            let data = self.readback_buffer.slice(..).get_mapped_range();
+            let timestamps: &[u64] = bytemuck::cast_slice(&data);
+            if let Some(ms) = timestamp_delta_ms(timestamps[0], timestamps[1], self.period) {
+                self.last_gpu_time_ms = ms;
+            }
+        }
+        self.readback_buffer.unmap();
+        self.readback_state.store(READBACK_IDLE, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_timestamp_delta_ms() {
+        assert_eq!(timestamp_delta_ms(1_000, 3_000_000, 1.0), Some(2.999));
+        assert_eq!(timestamp_delta_ms(0, 1_000, 1_000.0), Some(1.0));
+        assert_eq!(timestamp_delta_ms(5, 4, 1.0), None);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::native_gpu::{adapter, block_on};
+
+    /// Profiles a render pass on a native adapter; skipped without timestamp support
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_render_pass_has_gpu_time() {
+        let Some(adapter) = adapter() else {
+            return;
+        };
+        if !supports_timestamps(&adapter) {
+            return;
+        }
+        let (device, queue) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            required_features: wgpu::Features::TIMESTAMP_QUERY,
+            ..Default::default()
+        }))
+        .expect("device");
+
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Profiled Target"),
+            size: wgpu::Extent3d {
+                width: 512,
+                height: 512,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let view = target.create_view(&Default::default());
+
+        let mut profiler = GpuProfiler::new(&device, &queue);
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Profiled Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLUE),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: Some(profiler.timestamp_writes()),
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+        assert!(profiler.resolve(&mut encoder));
+        queue.submit(std::iter::once(encoder.finish()));
+        profiler.request_readback();
+
+        // A second resolve waits for the readback
+        let mut encoder = device.create_command_encoder(&Default::default());
+        assert!(!profiler.resolve(&mut encoder));
+
+        device
+            .poll(wgpu::PollType::Wait {
+                submission_index: None,
+                timeout: None,
+            })
+            .expect("poll");
+        profiler.collect();
+        assert!(profiler.last_gpu_time_ms > 0.0);
+        assert!(profiler.resolve(&mut encoder));
+    }
+}
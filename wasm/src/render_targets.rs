@@ -0,0 +1,311 @@
+//! Render targets sized to the surface, and recovery from surface loss
+//!
+//! The MSAA color target and the depth buffer are recreated whenever the
+//! surface is (re)configured, on resize or after it was lost. Kept apart from
+//! `gpu`, which needs a canvas, so the descriptors and recovery decisions can be
+//! tested on a native adapter.
+
+use wasm_bindgen::prelude::*;
+
+/// MSAA sample count (4x anti-aliasing)
+/// Use 1 to disable, 4 for high quality
+pub const MSAA_SAMPLE_COUNT: u32 = 4;
+
+/// Depth buffer format
+///
+/// Both options carry a stencil aspect, which the shadow pass needs to avoid
+/// blending overlapping shadow triangles twice.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepthFormat {
+    /// 24-bit depth, supported everywhere
+    #[default]
+    Depth24PlusStencil8,
+    /// 32-bit float depth for more precision in large scenes (optional feature)
+    Depth32FloatStencil8,
+}
+
+impl DepthFormat {
+    /// Matching wgpu texture format
+    pub fn texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            DepthFormat::Depth24PlusStencil8 => wgpu::TextureFormat::Depth24PlusStencil8,
+            DepthFormat::Depth32FloatStencil8 => wgpu::TextureFormat::Depth32FloatStencil8,
+        }
+    }
+
+    /// Device features that must be enabled to use this format
+    pub fn required_features(self) -> wgpu::Features {
+        match self {
+            DepthFormat::Depth24PlusStencil8 => wgpu::Features::empty(),
+            DepthFormat::Depth32FloatStencil8 => wgpu::Features::DEPTH32FLOAT_STENCIL8,
+        }
+    }
+}
+
+/// Descriptor for the multisampled depth target
+/// Shared by initialization and resizing so the format always matches the pipelines.
+pub fn depth_texture_descriptor(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> wgpu::TextureDescriptor<'static> {
+    wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: MSAA_SAMPLE_COUNT,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    }
+}
+
+/// Depth-stencil state of every pipeline: depth tested against `format`
+/// Shared by all pipelines so they match the depth texture
+/// (`depth_texture_descriptor`) whichever `DepthFormat` was configured.
+pub fn depth_stencil_state(
+    format: wgpu::TextureFormat,
+    depth_write_enabled: bool,
+    stencil: wgpu::StencilState,
+) -> wgpu::DepthStencilState {
+    wgpu::DepthStencilState {
+        format,
+        depth_write_enabled,
+        depth_compare: wgpu::CompareFunction::Less,
+        stencil,
+        bias: wgpu::DepthBiasState::default(),
+    }
+}
+
+/// Descriptor for the multisampled color target, resolved into the surface
+/// Shared by initialization and resizing like `depth_texture_descriptor`.
+pub fn msaa_texture_descriptor(
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> wgpu::TextureDescriptor<'static> {
+    wgpu::TextureDescriptor {
+        label: Some("MSAA Texture"),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: MSAA_SAMPLE_COUNT,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    }
+}
+
+/// Size-dependent render targets: the MSAA color target and the depth buffer
+pub struct RenderTargets {
+    pub depth_texture: wgpu::Texture,
+    pub depth_view: wgpu::TextureView,
+    pub msaa_texture: wgpu::Texture,
+    pub msaa_view: wgpu::TextureView,
+}
+
+impl RenderTargets {
+    /// Create both targets at `width` x `height`
+    /// Called at initialization and whenever the surface is reconfigured.
+    pub fn new(
+        device: &wgpu::Device,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let depth_texture =
+            device.create_texture(&depth_texture_descriptor(depth_format, width, height));
+        let msaa_texture =
+            device.create_texture(&msaa_texture_descriptor(color_format, width, height));
+        Self {
+            depth_view: depth_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            depth_texture,
+            msaa_view: msaa_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            msaa_texture,
+        }
+    }
+}
+
+/// How `acquire_frame` reacts to a failed `get_current_texture`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceRecovery {
+    /// Reconfigure the surface and try again right away
+    Reconfigure,
+    /// Skip this frame and reconfigure before the next one
+    ReconfigureNextFrame,
+    /// Skip this frame; the surface itself is fine
+    SkipFrame,
+}
+
+/// Recovery for a surface acquisition `error`
+/// Lost and outdated surfaces are reconfigured and retried once per frame
+/// (`retried` after the first attempt); other errors only drop the frame.
+pub fn surface_recovery(error: &wgpu::SurfaceError, retried: bool) -> SurfaceRecovery {
+    match error {
+        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated if retried => {
+            SurfaceRecovery::ReconfigureNextFrame
+        }
+        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => SurfaceRecovery::Reconfigure,
+        wgpu::SurfaceError::Timeout
+        | wgpu::SurfaceError::OutOfMemory
+        | wgpu::SurfaceError::Other => SurfaceRecovery::SkipFrame,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_depth_stencil_state_matches_configured_format() {
+        for depth_format in [
+            DepthFormat::Depth24PlusStencil8,
+            DepthFormat::Depth32FloatStencil8,
+        ] {
+            let format = depth_format.texture_format();
+            let texture = depth_texture_descriptor(format, 640, 480);
+            assert_eq!(texture.sample_count, MSAA_SAMPLE_COUNT);
+            // The shadow pass relies on a stencil aspect
+            assert!(texture.format.has_stencil_aspect());
+
+            for depth_write_enabled in [true, false] {
+                let state =
+                    depth_stencil_state(format, depth_write_enabled, wgpu::StencilState::default());
+                assert_eq!(state.format, texture.format);
+                assert_eq!(state.depth_write_enabled, depth_write_enabled);
+                assert_eq!(state.depth_compare, wgpu::CompareFunction::Less);
+                assert!(!state.stencil.is_enabled());
+                assert_eq!(state.bias, wgpu::DepthBiasState::default());
+            }
+
+            let stencil = wgpu::StencilState {
+                front: wgpu::StencilFaceState {
+                    compare: wgpu::CompareFunction::NotEqual,
+                    fail_op: wgpu::StencilOperation::Keep,
+                    depth_fail_op: wgpu::StencilOperation::Keep,
+                    pass_op: wgpu::StencilOperation::Replace,
+                },
+                back: wgpu::StencilFaceState::IGNORE,
+                read_mask: 0xFF,
+                write_mask: 0xFF,
+            };
+            let state = depth_stencil_state(format, false, stencil.clone());
+            assert_eq!(state.format, format);
+            assert_eq!(state.stencil, stencil);
+        }
+        assert_eq!(
+            DepthFormat::Depth32FloatStencil8.texture_format(),
+            wgpu::TextureFormat::Depth32FloatStencil8
+        );
+        assert!(DepthFormat::default().required_features().is_empty());
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_surface_recovery_branches() {
+        use wgpu::SurfaceError::{Lost, Other, OutOfMemory, Outdated, Timeout};
+
+        for error in [Lost, Outdated] {
+            assert_eq!(
+                surface_recovery(&error, false),
+                SurfaceRecovery::Reconfigure
+            );
+            assert_eq!(
+                surface_recovery(&error, true),
+                SurfaceRecovery::ReconfigureNextFrame
+            );
+        }
+        for error in [Timeout, OutOfMemory, Other] {
+            for retried in [false, true] {
+                assert_eq!(
+                    surface_recovery(&error, retried),
+                    SurfaceRecovery::SkipFrame
+                );
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::native_gpu::{adapter, block_on};
+
+    /// Targets recreated at a new size, as `GpuState::reconfigure_surface` does,
+    /// render and resolve into a frame of that size. A real surface needs a
+    /// canvas, so a texture stands in for the acquired frame.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_reconfigured_targets_render_frame() {
+        let Some(adapter) = adapter() else {
+            return;
+        };
+        let (device, queue) =
+            block_on(adapter.request_device(&Default::default())).expect("device");
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let depth_format = DepthFormat::default().texture_format();
+
+        for (width, height) in [(320, 240), (500, 180)] {
+            let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let frame = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Stand-in Frame"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            let frame_view = frame.create_view(&Default::default());
+            let targets = RenderTargets::new(&device, format, depth_format, width, height);
+            assert_eq!(targets.msaa_texture.width(), width);
+            assert_eq!(targets.depth_texture.height(), height);
+
+            let mut encoder = device.create_command_encoder(&Default::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Reconfigured Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &targets.msaa_view,
+                    resolve_target: Some(&frame_view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &targets.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+            queue.submit(std::iter::once(encoder.finish()));
+            let error = block_on(scope.pop());
+            assert!(error.is_none(), "{}x{}: {:?}", width, height, error);
+        }
+    }
+}
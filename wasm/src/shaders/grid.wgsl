@@ -5,6 +5,18 @@ override SKY_R: f32;
 override SKY_G: f32;
 override SKY_B: f32;
 
+// Set by gpu.rs when the surface format is sRGB (it encodes on write)
+override SRGB_SURFACE: bool = false;
+
+// Colors here are display (gamma-encoded) values, which a linear surface stores
+// as is; for an sRGB surface decode them so its encode restores them
+fn surface_color(display: vec3<f32>) -> vec3<f32> {
+    if (SRGB_SURFACE) {
+        return pow(display, vec3<f32>(2.2));
+    }
+    return display;
+}
+
 // White
 const FLOOR_R: f32 = 1.0;
 const FLOOR_G: f32 = 1.0;
@@ -27,6 +39,16 @@ struct Uniforms {
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
 
+// Line parameters, set at runtime (GridUniforms in gpu.rs)
+struct GridUniforms {
+    line_width: f32,
+    major_line_width: f32,
+    aa_strength: f32,
+    _padding: f32,
+}
+
+@group(0) @binding(1) var<uniform> grid: GridUniforms;
+
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) world_pos: vec2<f32>,
@@ -34,14 +56,11 @@ struct VertexOutput {
 
 // Constants
 const FLOOR_SIZE: f32 = 50.0;
-const MINOR_LINE_THICKNESS: f32 = 0.02;
-const MAJOR_LINE_THICKNESS: f32 = 0.03;
 const MAJOR_GRID_STEP: f32 = 5.0;
 const MINOR_GRID_OPACITY: f32 = 0.3;
 const MAJOR_GRID_OPACITY: f32 = 0.5;
 const FADE_START: f32 = 5.0;
 const FADE_END: f32 = 40.0;
-const AA_STRENGTH: f32 = 1.0;
 
 // Large floor quad vertices in world space (Y=0 plane)
 const FLOOR_VERTICES: array<vec3<f32>, 6> = array<vec3<f32>, 6>(
@@ -67,6 +86,13 @@ fn vs_main(@builtin(vertex_index) vertex_idx: u32) -> VertexOutput {
 }
 
 // Compute grid line intensity
+//
+// Antialiasing works in screen space: fwidth(coord) is how much the grid
+// coordinate changes across one pixel, so the line edge is smoothed over
+// aa_strength pixels whatever the distance or viewing angle. At grazing angles
+// a line can get thinner than a pixel, where it would flicker in and out as it
+// falls between samples; it is widened to one pixel instead and faded by how
+// much it was widened, so its average coverage stays the same.
 fn on_grid_line(coord: f32, line_width: f32) -> f32 {
     // Grid lines are at every 1 unit
     // Wrap the coordinate to [0, 0.5]
@@ -79,22 +105,26 @@ fn on_grid_line(coord: f32, line_width: f32) -> f32 {
     let wrapped = abs(fract(coord - 0.5) - 0.5);
     // How much coord changes per pixel
     let coord_per_pixel = fwidth(coord);
-    // Inside the line: wrapped < line_width * 0.5
-    // At the line edge: wrapped - (line_width * 0.5) = 0 -> start fading
-    // Outside the line: wrapped > coord_per_pixel * AA_STRENGTH
-    return 1.0 - smoothstep(0.0, coord_per_pixel * AA_STRENGTH, wrapped - (line_width * 0.5));
+    // Never thinner than a pixel; fade by the widening instead
+    let drawn_width = max(line_width, coord_per_pixel);
+    let coverage = line_width / max(drawn_width, 1e-6);
+    // Inside the line: wrapped < drawn_width * 0.5
+    // At the line edge: wrapped - (drawn_width * 0.5) = 0 -> start fading
+    // Outside the line: wrapped > coord_per_pixel * aa_strength
+    let edge = coord_per_pixel * max(grid.aa_strength, 1e-3);
+    return (1.0 - smoothstep(0.0, edge, wrapped - (drawn_width * 0.5))) * coverage;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     // Grid lines at every 1 unit
-    let grid_x = on_grid_line(in.world_pos.x, MINOR_LINE_THICKNESS);
-    let grid_z = on_grid_line(in.world_pos.y, MINOR_LINE_THICKNESS);
+    let grid_x = on_grid_line(in.world_pos.x, grid.line_width);
+    let grid_z = on_grid_line(in.world_pos.y, grid.line_width);
     let grid_intensity = max(grid_x, grid_z);
 
     // Thicker lines at every MAJOR_GRID_STEP units
-    let major_x = on_grid_line(in.world_pos.x / MAJOR_GRID_STEP, MAJOR_LINE_THICKNESS);
-    let major_z = on_grid_line(in.world_pos.y / MAJOR_GRID_STEP, MAJOR_LINE_THICKNESS);
+    let major_x = on_grid_line(in.world_pos.x / MAJOR_GRID_STEP, grid.major_line_width);
+    let major_z = on_grid_line(in.world_pos.y / MAJOR_GRID_STEP, grid.major_line_width);
     let major_intensity = max(major_x, major_z);
 
     // Combine grids
@@ -125,5 +155,5 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let gamma = 1.0 / 2.2;
     let gamma_corrected = pow(final_color, vec3<f32>(gamma));
 
-    return vec4<f32>(gamma_corrected, 1.0);
+    return vec4<f32>(surface_color(gamma_corrected), 1.0);
 }
@@ -7,17 +7,33 @@ struct Uniforms {
     projection: mat4x4<f32>,
     aspect: f32,
     screen_height: f32,
-    _padding: vec2<f32>,
-    _padding4: vec4<f32>,
+    skinning_mode: u32,
+    outline_thickness: f32,
+    outline_color: vec4<f32>,
 }
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
 @group(1) @binding(0) var<uniform> bone_matrices: array<mat4x4<f32>, 22>;
 
+// SkinningMode::Linear in skeleton.rs
+const SKINNING_LINEAR: u32 = 1u;
+
+// Skinning matrix for a vertex: its own bone (rigid) or, in linear mode, a
+// blend with its parent bone near the joint. Mirrors `skin_vertex` in skeleton.rs.
+fn skin_matrix(bone_index: u32, blend_index: u32, blend_weight: f32) -> mat4x4<f32> {
+    let rigid = bone_matrices[bone_index];
+    if (uniforms.skinning_mode == SKINNING_LINEAR) {
+        return rigid * (1.0 - blend_weight) + bone_matrices[blend_index] * blend_weight;
+    }
+    return rigid;
+}
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) bone_index: u32,
+    @location(4) blend_index: u32,
+    @location(5) blend_weight: f32,
 }
 
 struct VertexOutput {
@@ -51,7 +67,7 @@ fn shadow_projection_matrix() -> mat4x4<f32> {
 fn vs_main(vertex: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    let bone_matrix = bone_matrices[vertex.bone_index];
+    let bone_matrix = skin_matrix(vertex.bone_index, vertex.blend_index, vertex.blend_weight);
     let shadow_matrix = shadow_projection_matrix();
 
     // Transform by bone, then project to floor
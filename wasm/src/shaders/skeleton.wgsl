@@ -5,10 +5,11 @@
 struct Uniforms {
     view: mat4x4<f32>,          // bytes 0-63
     projection: mat4x4<f32>,    // bytes 64-127
-    aspect: f32,                // byte 132
-    screen_height: f32,         // byte 136
-    _padding: vec2<f32>,        // byte 140-147
-    _padding4: vec4<f32>,       // bytes 148-159
+    aspect: f32,                // byte 128
+    screen_height: f32,         // byte 132
+    skinning_mode: u32,         // byte 136
+    outline_thickness: f32,     // byte 140
+    outline_color: vec4<f32>,   // bytes 144-159
 }
 
 @group(0) @binding(0) var<uniform> uniforms: Uniforms;
@@ -22,10 +23,54 @@ const CAMERA_POS: vec3<f32> = vec3<f32>(2.5, 1.2, 3.0);
 // 22 matrices (22 bones, debug spheres removed)
 @group(1) @binding(0) var<uniform> bone_matrices: array<mat4x4<f32>, 22>;
 
+// Per-bone tint (rgb = color, a = tint strength), used for editor highlighting
+@group(1) @binding(1) var<uniform> bone_colors: array<vec4<f32>, 22>;
+
+// Per-instance tint (rgb multiplies the color, a = opacity in the translucent pass)
+@group(1) @binding(2) var<uniform> instance_tint: vec4<f32>;
+
+// Set by gpu.rs when the surface format is sRGB (it encodes on write)
+override SRGB_SURFACE: bool = false;
+
+// Colors here are display (gamma-encoded) values, which a linear surface stores
+// as is; for an sRGB surface decode them so its encode restores them
+fn surface_color(display: vec3<f32>) -> vec3<f32> {
+    if (SRGB_SURFACE) {
+        return pow(display, vec3<f32>(2.2));
+    }
+    return display;
+}
+
+// Bones hidden by the visibility mask have zero-scale matrices (see
+// `hide_bone_matrix` in skeleton.rs) and zero normals; keep those finite
+fn safe_normalize(v: vec3<f32>) -> vec3<f32> {
+    let len = length(v);
+    if (len > 0.0) {
+        return v / len;
+    }
+    return vec3<f32>(0.0, 1.0, 0.0);
+}
+
+// SkinningMode::Linear in skeleton.rs
+const SKINNING_LINEAR: u32 = 1u;
+
+// Skinning matrix for a vertex: its own bone (rigid) or, in linear mode, a
+// blend with its parent bone near the joint. Mirrors `skin_vertex` in skeleton.rs.
+fn skin_matrix(bone_index: u32, blend_index: u32, blend_weight: f32) -> mat4x4<f32> {
+    let rigid = bone_matrices[bone_index];
+    if (uniforms.skinning_mode == SKINNING_LINEAR) {
+        return rigid * (1.0 - blend_weight) + bone_matrices[blend_index] * blend_weight;
+    }
+    return rigid;
+}
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
     @location(2) bone_index: u32,
+    @location(3) ao: f32,
+    @location(4) blend_index: u32,
+    @location(5) blend_weight: f32,
 }
 
 struct VertexOutput {
@@ -33,13 +78,14 @@ struct VertexOutput {
     @location(0) world_normal: vec3<f32>,
     @location(1) world_pos: vec3<f32>,
     @location(2) bone_index: f32,
+    @location(3) ao: f32,
 }
 
 @vertex
 fn vs_main(vertex: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    let bone_matrix = bone_matrices[vertex.bone_index];
+    let bone_matrix = skin_matrix(vertex.bone_index, vertex.blend_index, vertex.blend_weight);
 
     // Transform position and normal by the bone matrix
     let world_pos = bone_matrix * vec4<f32>(vertex.position, 1.0);
@@ -47,14 +93,15 @@ fn vs_main(vertex: VertexInput) -> VertexOutput {
 
     out.clip_position = uniforms.projection * uniforms.view * world_pos;
     out.world_pos = world_pos.xyz;
-    out.world_normal = normalize(world_normal.xyz);
+    out.world_normal = safe_normalize(world_normal.xyz);
     out.bone_index = f32(vertex.bone_index);
+    out.ao = vertex.ao;
 
     return out;
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+// Shared lighting for the opaque and ghost passes
+fn shade(in: VertexOutput) -> vec3<f32> {
     let normal = normalize(in.world_normal);
     let view_dir = normalize(CAMERA_POS - in.world_pos);
 
@@ -132,6 +179,9 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     // Specular highlight
     lit_color += vec3<f32>(0.8, 0.85, 1.0) * specular;
 
+    // Baked ambient occlusion darkens joint crevices
+    lit_color *= in.ao;
+
     // Tone mapping (Reinhard-style)
     lit_color = lit_color / (lit_color + vec3<f32>(0.3));
 
@@ -139,6 +189,35 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let gamma = 1.0 / 2.2;
     let gamma_corrected = pow(lit_color, vec3<f32>(gamma));
 
-    return vec4<f32>(gamma_corrected, 1.0);
+    // Editor highlight tint (bone_index is constant across a primitive)
+    let tint = bone_colors[u32(in.bone_index + 0.5)];
+    return mix(gamma_corrected, tint.rgb, tint.a) * instance_tint.rgb;
 }
 
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(surface_color(shade(in)), 1.0);
+}
+
+// Translucent variant for onion-skin ghosts and other instances with alpha below 1
+@fragment
+fn fs_ghost(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(surface_color(shade(in)), instance_tint.a);
+}
+
+// Inverted hull outline: the skinned mesh pushed out along its normals.
+// Drawn with front faces culled before the skeleton, so only a rim shows.
+@vertex
+fn vs_outline(vertex: VertexInput) -> @builtin(position) vec4<f32> {
+    let bone_matrix = skin_matrix(vertex.bone_index, vertex.blend_index, vertex.blend_weight);
+    let world_normal = safe_normalize((bone_matrix * vec4<f32>(vertex.normal, 0.0)).xyz);
+    let world_pos = bone_matrix * vec4<f32>(vertex.position, 1.0)
+        + vec4<f32>(world_normal * uniforms.outline_thickness, 0.0);
+    return uniforms.projection * uniforms.view * world_pos;
+}
+
+// Flat outline color (already a display color, no gamma)
+@fragment
+fn fs_outline() -> @location(0) vec4<f32> {
+    return vec4<f32>(surface_color(uniforms.outline_color.rgb), 1.0);
+}
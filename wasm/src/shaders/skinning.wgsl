@@ -0,0 +1,151 @@
+// Skinning compute shader
+// Runs forward kinematics for many skeleton instances and writes their render
+// bone matrices. Mirrors `RotationPose::compute_bone_matrices`; the buffer
+// layouts match the structs in skinning.rs.
+
+// BoneId::COUNT and RENDER_BONE_COUNT
+const BONE_COUNT: u32 = 22u;
+const RENDER_BONE_COUNT: u32 = 22u;
+
+// skeleton::MIN_SEGMENT_LENGTH
+const MIN_SEGMENT_LENGTH: f32 = 0.0001;
+
+struct Bone {
+    // Unit direction of the segment ending at this bone, in the parent's frame
+    direction: vec3<f32>,
+    // Parent bone index, -1 for the root
+    parent: i32,
+}
+
+struct Segment {
+    start: u32,
+    end: u32,
+}
+
+// Matches Rust SkinningTables (880 bytes)
+struct Tables {
+    bones: array<Bone, BONE_COUNT>,
+    rest_positions: array<vec4<f32>, BONE_COUNT>,
+    segments: array<Segment, RENDER_BONE_COUNT>,
+}
+
+// Matches Rust SkinningInstance (464 bytes)
+struct Instance {
+    root: vec4<f32>,
+    rotations: array<vec4<f32>, BONE_COUNT>,
+    lengths: array<f32, BONE_COUNT>,
+    // Render bones to draw (bit i = render bone i)
+    visible_bones: u32,
+}
+
+@group(0) @binding(0) var<storage, read> tables: Tables;
+@group(0) @binding(1) var<storage, read> instances: array<Instance>;
+@group(0) @binding(2) var<storage, read_write> matrices: array<mat4x4<f32>>;
+
+fn quat_mul(a: vec4<f32>, b: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(
+        a.w * b.xyz + b.w * a.xyz + cross(a.xyz, b.xyz),
+        a.w * b.w - dot(a.xyz, b.xyz),
+    );
+}
+
+fn quat_rotate(q: vec4<f32>, v: vec3<f32>) -> vec3<f32> {
+    let t = 2.0 * cross(q.xyz, v);
+    return v + q.w * t + cross(q.xyz, t);
+}
+
+// Rotation matrix columns of a unit quaternion
+fn quat_to_mat3(q: vec4<f32>) -> mat3x3<f32> {
+    return mat3x3<f32>(
+        quat_rotate(q, vec3<f32>(1.0, 0.0, 0.0)),
+        quat_rotate(q, vec3<f32>(0.0, 1.0, 0.0)),
+        quat_rotate(q, vec3<f32>(0.0, 0.0, 1.0)),
+    );
+}
+
+// Unit direction from start to end, or zero for a degenerate segment
+// (skeleton::segment_direction)
+fn segment_direction(start: vec3<f32>, end: vec3<f32>) -> vec3<f32> {
+    let delta = end - start;
+    let len = length(delta);
+    if (len < MIN_SEGMENT_LENGTH) {
+        return vec3<f32>(0.0);
+    }
+    return delta / len;
+}
+
+// Shortest rotation taking unit vector `from_dir` onto `to_dir` (glam Quat::from_rotation_arc)
+fn rotation_arc(from_dir: vec3<f32>, to_dir: vec3<f32>) -> vec4<f32> {
+    let d = dot(from_dir, to_dir);
+    if (d > 1.0 - 2.0 * 1.1920929e-7) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    if (d < -1.0 + 2.0 * 1.1920929e-7) {
+        // Half turn about any axis orthogonal to `from_dir`
+        let s = select(-1.0, 1.0, from_dir.z >= 0.0);
+        let a = -1.0 / (s + from_dir.z);
+        let b = from_dir.x * from_dir.y * a;
+        let axis = vec3<f32>(b, s + from_dir.y * from_dir.y * a, -from_dir.y);
+        return vec4<f32>(axis, 0.0);
+    }
+    return normalize(vec4<f32>(cross(from_dir, to_dir), 1.0 + d));
+}
+
+// skeleton::compute_aligned_matrix: rotate the bind segment onto the current one
+fn aligned_matrix(b_start: vec3<f32>, b_end: vec3<f32>, c_start: vec3<f32>, c_end: vec3<f32>) -> mat4x4<f32> {
+    let b_dir = segment_direction(b_start, b_end);
+    let c_dir = segment_direction(c_start, c_end);
+    var rot = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    if (any(b_dir != vec3<f32>(0.0)) && any(c_dir != vec3<f32>(0.0))) {
+        rot = rotation_arc(b_dir, c_dir);
+    }
+    let m = quat_to_mat3(rot);
+    let translation = c_start - m * b_start;
+    return mat4x4<f32>(
+        vec4<f32>(m[0], 0.0),
+        vec4<f32>(m[1], 0.0),
+        vec4<f32>(m[2], 0.0),
+        vec4<f32>(translation, 1.0),
+    );
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let instance_index = id.x;
+    if (instance_index >= arrayLength(&instances)) {
+        return;
+    }
+    let instance = instances[instance_index];
+
+    // Forward kinematics; parents always precede their children
+    var positions: array<vec3<f32>, BONE_COUNT>;
+    var rotations: array<vec4<f32>, BONE_COUNT>;
+    for (var i = 0u; i < BONE_COUNT; i++) {
+        let bone = tables.bones[i];
+        var parent_pos = instance.root.xyz;
+        var parent_rot = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        if (bone.parent >= 0) {
+            parent_pos = positions[bone.parent];
+            parent_rot = rotations[bone.parent];
+        }
+        rotations[i] = quat_mul(parent_rot, instance.rotations[i]);
+        positions[i] = parent_pos + quat_rotate(parent_rot, bone.direction * instance.lengths[i]);
+    }
+
+    // The head segment is degenerate (head to head), giving the sphere's offset matrix
+    let base = instance_index * RENDER_BONE_COUNT;
+    for (var i = 0u; i < RENDER_BONE_COUNT; i++) {
+        let segment = tables.segments[i];
+        var m = aligned_matrix(
+            tables.rest_positions[segment.start].xyz,
+            tables.rest_positions[segment.end].xyz,
+            positions[segment.start],
+            positions[segment.end],
+        );
+        // Hidden bone: collapse onto its own origin (skeleton::hide_bone_matrix)
+        if ((instance.visible_bones & (1u << i)) == 0u) {
+            m = mat4x4<f32>(vec4<f32>(0.0), vec4<f32>(0.0), vec4<f32>(0.0), m[3]);
+        }
+        matrices[base + i] = m;
+    }
+}
@@ -3,7 +3,10 @@
 //! CPU defines joint positions using glam::Vec3.
 //! GPU generates cylinder/sphere geometry via instanced rendering.
 
-use glam::{Vec3, Vec3A};
+use crate::bone::BoneId;
+use glam::{Mat4, Vec3, Vec3A};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
 
 /// Radius constants for rendering and physics
 ///
@@ -18,6 +21,34 @@ pub const HEAD_RADIUS: f32 = 0.10;
 /// Radius of debug joint spheres (slightly larger than bones for visibility)
 pub const JOINT_RADIUS: f32 = 0.04;
 
+/// Runtime body proportions, defaulting to the radius constants above
+///
+/// Changing these requires regenerating the mesh; `bone` also sets how high
+/// the floor constraint keeps joints.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadiiConfig {
+    pub bone: f32,
+    pub head: f32,
+}
+
+impl Default for RadiiConfig {
+    fn default() -> Self {
+        Self {
+            bone: BONE_RADIUS,
+            head: HEAD_RADIUS,
+        }
+    }
+}
+
+impl RadiiConfig {
+    /// Check all radii are finite and positive
+    pub fn is_valid(&self) -> bool {
+        [self.bone, self.head]
+            .iter()
+            .all(|r| r.is_finite() && *r > 0.0)
+    }
+}
+
 /// Vertex format for skinned mesh
 /// Vertex format for GPU-skinned mesh rendering
 ///
@@ -31,6 +62,55 @@ pub struct SkinnedVertex {
     pub normal: [f32; 3],
     /// Index into bone matrix array (0-22)
     pub bone_index: u32,
+    /// Baked ambient occlusion (1 = fully open, lower = darker crevice)
+    pub ao: f32,
+    /// Second bone matrix blended in by linear skinning
+    pub blend_index: u32,
+    /// Weight of `blend_index` in linear skinning (0 = rigid)
+    pub blend_weight: f32,
+}
+
+impl SkinnedVertex {
+    /// Vertex that follows only `bone`, with ambient occlusion not yet baked
+    pub fn rigid(position: impl Into<Vec3>, normal: impl Into<Vec3>, bone: u32) -> Self {
+        Self {
+            position: position.into().to_array(),
+            normal: normal.into().to_array(),
+            bone_index: bone,
+            ao: 1.0,
+            blend_index: bone,
+            blend_weight: 0.0,
+        }
+    }
+}
+
+/// How vertices follow the bone matrices
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[repr(u32)]
+pub enum SkinningMode {
+    /// Each vertex follows only its own bone (hard creases at joints)
+    #[default]
+    Rigid = 0,
+    /// Vertices near a joint blend with the parent bone (smooth bends)
+    Linear = 1,
+}
+
+/// Skin a vertex on the CPU, mirroring `vs_main` in skeleton.wgsl
+pub fn skin_vertex(
+    vertex: &SkinnedVertex,
+    matrices: &[Mat4; RENDER_BONE_COUNT],
+    mode: SkinningMode,
+) -> Vec3 {
+    let rigid = matrices[vertex.bone_index as usize];
+    let matrix = match mode {
+        SkinningMode::Rigid => rigid,
+        SkinningMode::Linear => {
+            rigid * (1.0 - vertex.blend_weight)
+                + matrices[vertex.blend_index as usize] * vertex.blend_weight
+        }
+    };
+    matrix.transform_point3(Vec3::from_array(vertex.position))
 }
 
 // Total number of renderable parts (bones)
@@ -38,13 +118,155 @@ pub struct SkinnedVertex {
 // Just an estimate for buffer reservation, exact count not critical for constant but good for optimization
 pub const RENDER_BONE_COUNT: usize = 22;
 
+/// Distance within which other bone geometry occludes a vertex
+pub const AO_DISTANCE: f32 = 3.0 * BONE_RADIUS;
+/// How much a fully occluded vertex is darkened (0 = no AO)
+pub const AO_STRENGTH: f32 = 0.6;
+
+/// Parent primitive of each render primitive (the one ending at its start joint)
+/// Order matches `generate_bind_pose_mesh`.
+const PRIMITIVE_PARENTS: [Option<u32>; RENDER_BONE_COUNT] = [
+    None,     // 0: Pelvis -> Spine1
+    Some(0),  // 1: Spine1 -> Spine2
+    Some(1),  // 2: Spine2 -> Spine3
+    Some(2),  // 3: Spine3 -> Neck
+    Some(3),  // 4: Neck -> Head
+    Some(2),  // 5: Spine3 -> Left collar
+    Some(5),  // 6: Left collar -> shoulder
+    Some(6),  // 7: Left shoulder -> elbow
+    Some(7),  // 8: Left elbow -> wrist
+    Some(2),  // 9: Spine3 -> Right collar
+    Some(9),  // 10: Right collar -> shoulder
+    Some(10), // 11: Right shoulder -> elbow
+    Some(11), // 12: Right elbow -> wrist
+    None,     // 13: Pelvis -> Left hip
+    Some(13), // 14: Left hip -> knee
+    Some(14), // 15: Left knee -> ankle
+    Some(15), // 16: Left ankle -> foot
+    None,     // 17: Pelvis -> Right hip
+    Some(17), // 18: Right hip -> knee
+    Some(18), // 19: Right knee -> ankle
+    Some(19), // 20: Right ankle -> foot
+    None,     // 21: Head sphere
+];
+
+/// Start and end joint of each cylinder primitive, in `generate_bind_pose_mesh` order
+/// The head sphere follows as the last primitive, centered on `BoneId::Head`.
+pub const RENDER_SEGMENTS: [(BoneId, BoneId); RENDER_BONE_COUNT - 1] = [
+    (BoneId::Pelvis, BoneId::Spine1),
+    (BoneId::Spine1, BoneId::Spine2),
+    (BoneId::Spine2, BoneId::Spine3),
+    (BoneId::Spine3, BoneId::Neck),
+    (BoneId::Neck, BoneId::Head),
+    (BoneId::Spine3, BoneId::LeftCollar),
+    (BoneId::LeftCollar, BoneId::LeftShoulder),
+    (BoneId::LeftShoulder, BoneId::LeftElbow),
+    (BoneId::LeftElbow, BoneId::LeftWrist),
+    (BoneId::Spine3, BoneId::RightCollar),
+    (BoneId::RightCollar, BoneId::RightShoulder),
+    (BoneId::RightShoulder, BoneId::RightElbow),
+    (BoneId::RightElbow, BoneId::RightWrist),
+    (BoneId::Pelvis, BoneId::LeftHip),
+    (BoneId::LeftHip, BoneId::LeftKnee),
+    (BoneId::LeftKnee, BoneId::LeftAnkle),
+    (BoneId::LeftAnkle, BoneId::LeftFoot),
+    (BoneId::Pelvis, BoneId::RightHip),
+    (BoneId::RightHip, BoneId::RightKnee),
+    (BoneId::RightKnee, BoneId::RightAnkle),
+    (BoneId::RightAnkle, BoneId::RightFoot),
+];
+
+/// Bind pose position of a joint, as the mesh is generated
+///
+/// The FK of `RotationPose::bind_pose` over `BONE_HIERARCHY`, so the bind pose
+/// yields identity bone matrices. The FBX joints in `skeleton_constants` differ
+/// by up to a few cm where the hierarchy straightens a chain (the neck is +Y,
+/// the left arm +X); building the mesh from them would bake that offset into
+/// every bind matrix.
+pub fn rest_joint_position(joint: BoneId) -> Vec3A {
+    static REST_POSITIONS: std::sync::LazyLock<[Vec3A; BoneId::COUNT]> =
+        std::sync::LazyLock::new(|| {
+            let pose = crate::bone::RotationPose::bind_pose();
+            BoneId::ALL.map(|bone| Vec3A::from(pose.get_position(bone)))
+        });
+    REST_POSITIONS[joint.index()]
+}
+
+/// Bind pose reference the per-frame bone matrices are aligned against
+///
+/// Derived only from the rest joint positions the mesh is generated from, not
+/// from radii or per-pose bone lengths (those change the mesh thickness or the
+/// current pose, never the bind alignment), so one shared copy stays valid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindData {
+    /// Bind start joint and unit direction of each `RENDER_SEGMENTS` entry
+    /// (None for a zero-length segment)
+    pub segments: [(Vec3A, Option<Vec3A>); RENDER_BONE_COUNT - 1],
+    /// Bind center of the head sphere
+    pub head: Vec3A,
+}
+
+impl BindData {
+    /// Compute the bind data from the rest joint positions
+    pub fn compute() -> Self {
+        Self {
+            segments: RENDER_SEGMENTS.map(|(start, end)| {
+                let b_start = rest_joint_position(start);
+                (
+                    b_start,
+                    segment_direction(b_start, rest_joint_position(end)),
+                )
+            }),
+            head: rest_joint_position(BoneId::Head),
+        }
+    }
+
+    /// Bone matrices moving the bind mesh onto joints at `positions`
+    pub fn bone_matrices(&self, positions: &[Vec3A; BoneId::COUNT]) -> [Mat4; RENDER_BONE_COUNT] {
+        let mut matrices = [Mat4::IDENTITY; RENDER_BONE_COUNT];
+        let pos = |id: BoneId| positions[id.index()];
+        for ((matrix, &(start, end)), &(b_start, b_dir)) in matrices
+            .iter_mut()
+            .zip(&RENDER_SEGMENTS)
+            .zip(&self.segments)
+        {
+            *matrix = aligned_matrix_from_direction(b_start, b_dir, pos(start), pos(end));
+        }
+        matrices[RENDER_BONE_COUNT - 1] = compute_offset_matrix(self.head, pos(BoneId::Head));
+        matrices
+    }
+}
+
+/// Shared `BindData`, computed on first use
+pub fn bind_data() -> &'static BindData {
+    static BIND_DATA: std::sync::LazyLock<BindData> = std::sync::LazyLock::new(BindData::compute);
+    &BIND_DATA
+}
+
+/// Distance from a joint over which linear skinning fades to the parent bone
+pub const SKIN_BLEND_LENGTH: f32 = 3.0 * BONE_RADIUS;
+
 /// Number of segments for cylinder geometry
 pub const CYLINDER_SEGMENTS: usize = 12;
+/// Number of sections along the length of a cylinder body
+pub const CYLINDER_SECTIONS: usize = 4;
 /// Number of latitude segments for sphere geometry
 pub const SPHERE_LAT_SEGMENTS: usize = 16;
 /// Number of longitude segments for sphere geometry
 pub const SPHERE_LON_SEGMENTS: usize = 24;
 
+/// Segments shorter than this are treated as zero-length (no usable direction)
+pub const MIN_SEGMENT_LENGTH: f32 = 0.0001;
+
+/// Unit direction from `start` to `end`, or None for a zero-length segment
+pub fn segment_direction(start: Vec3A, end: Vec3A) -> Option<Vec3A> {
+    let delta = end - start;
+    if delta.length() < MIN_SEGMENT_LENGTH {
+        return None;
+    }
+    delta.try_normalize()
+}
+
 fn add_cylinder(
     vertices: &mut Vec<SkinnedVertex>,
     start: Vec3A,
@@ -64,9 +286,15 @@ fn add_cylinder_caps(
     start_cap: bool,
     end_cap: bool,
 ) {
-    let dir = (end - start).normalize();
+    let first_vertex = vertices.len();
+    // Coincident endpoints give a stub cylinder along +Y instead of NaN geometry
+    let dir = segment_direction(start, end).unwrap_or(Vec3A::Y);
     let length = start.distance(end);
-    let valid_len = if length < 0.0001 { 0.0001 } else { length };
+    let valid_len = if length < MIN_SEGMENT_LENGTH {
+        MIN_SEGMENT_LENGTH
+    } else {
+        length
+    };
 
     let segments = CYLINDER_SEGMENTS;
 
@@ -87,49 +315,30 @@ fn add_cylinder_caps(
         (start + local_p, local_n)
     };
 
-    // Body
-    for i in 0..segments {
-        let a1 = (i as f32 / segments as f32) * std::f32::consts::TAU;
-        let a2 = ((i + 1) as f32 / segments as f32) * std::f32::consts::TAU;
-
-        let (p1, n1) = get_point(a1, 0.0, radius);
-        let (p2, n2) = get_point(a2, 0.0, radius);
-        let (p3, n3) = get_point(a1, valid_len, radius);
-        let (p4, n4) = get_point(a2, valid_len, radius);
-
-        // Triangle 1
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p1).to_array(),
-            normal: Vec3::from(n1).to_array(),
-            bone_index: bone_idx,
-        });
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p3).to_array(),
-            normal: Vec3::from(n3).to_array(),
-            bone_index: bone_idx,
-        });
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p2).to_array(),
-            normal: Vec3::from(n2).to_array(),
-            bone_index: bone_idx,
-        });
+    // Body, split along its length so per-vertex terms (baked AO) can vary
+    for k in 0..CYLINDER_SECTIONS {
+        let d1 = valid_len * k as f32 / CYLINDER_SECTIONS as f32;
+        let d2 = valid_len * (k + 1) as f32 / CYLINDER_SECTIONS as f32;
 
-        // Triangle 2
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p2).to_array(),
-            normal: Vec3::from(n2).to_array(),
-            bone_index: bone_idx,
-        });
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p3).to_array(),
-            normal: Vec3::from(n3).to_array(),
-            bone_index: bone_idx,
-        });
-        vertices.push(SkinnedVertex {
-            position: Vec3::from(p4).to_array(),
-            normal: Vec3::from(n4).to_array(),
-            bone_index: bone_idx,
-        });
+        for i in 0..segments {
+            let a1 = (i as f32 / segments as f32) * std::f32::consts::TAU;
+            let a2 = ((i + 1) as f32 / segments as f32) * std::f32::consts::TAU;
+
+            let (p1, n1) = get_point(a1, d1, radius);
+            let (p2, n2) = get_point(a2, d1, radius);
+            let (p3, n3) = get_point(a1, d2, radius);
+            let (p4, n4) = get_point(a2, d2, radius);
+
+            // Triangle 1
+            vertices.push(SkinnedVertex::rigid(p1, n1, bone_idx));
+            vertices.push(SkinnedVertex::rigid(p3, n3, bone_idx));
+            vertices.push(SkinnedVertex::rigid(p2, n2, bone_idx));
+
+            // Triangle 2
+            vertices.push(SkinnedVertex::rigid(p2, n2, bone_idx));
+            vertices.push(SkinnedVertex::rigid(p3, n3, bone_idx));
+            vertices.push(SkinnedVertex::rigid(p4, n4, bone_idx));
+        }
     }
 
     // define basis for caps (same as cylinder body)
@@ -172,37 +381,13 @@ fn add_cylinder_caps(
                 let (p4, n4) = get_cap_vertex(r2, y2, lon2);
 
                 // Two triangles
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p1).to_array(),
-                    normal: Vec3::from(n1).to_array(),
-                    bone_index: bone_idx,
-                });
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p3).to_array(),
-                    normal: Vec3::from(n3).to_array(),
-                    bone_index: bone_idx,
-                });
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p2).to_array(),
-                    normal: Vec3::from(n2).to_array(),
-                    bone_index: bone_idx,
-                });
-
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p2).to_array(),
-                    normal: Vec3::from(n2).to_array(),
-                    bone_index: bone_idx,
-                });
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p3).to_array(),
-                    normal: Vec3::from(n3).to_array(),
-                    bone_index: bone_idx,
-                });
-                vertices.push(SkinnedVertex {
-                    position: Vec3::from(p4).to_array(),
-                    normal: Vec3::from(n4).to_array(),
-                    bone_index: bone_idx,
-                });
+                vertices.push(SkinnedVertex::rigid(p1, n1, bone_idx));
+                vertices.push(SkinnedVertex::rigid(p3, n3, bone_idx));
+                vertices.push(SkinnedVertex::rigid(p2, n2, bone_idx));
+
+                vertices.push(SkinnedVertex::rigid(p2, n2, bone_idx));
+                vertices.push(SkinnedVertex::rigid(p3, n3, bone_idx));
+                vertices.push(SkinnedVertex::rigid(p4, n4, bone_idx));
             }
         }
     };
@@ -216,6 +401,15 @@ fn add_cylinder_caps(
     if end_cap {
         add_cap_ring(vertices, start + dir * valid_len, dir, radius);
     }
+
+    // Linear skinning weights: half parent at the start joint, fading out along the bone
+    if let Some(parent) = PRIMITIVE_PARENTS[bone_idx as usize] {
+        for v in &mut vertices[first_vertex..] {
+            let along = (Vec3A::from_array(v.position) - start).dot(dir);
+            v.blend_index = parent;
+            v.blend_weight = 0.5 * (1.0 - (along / SKIN_BLEND_LENGTH).clamp(0.0, 1.0));
+        }
+    }
 }
 
 // Helper to add a sphere
@@ -257,45 +451,28 @@ fn add_sphere(vertices: &mut Vec<SkinnedVertex>, center: Vec3A, radius: f32, bon
             let w4 = center + p4;
 
             // Two triangles
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w1).to_array(),
-                normal: Vec3::from(n1).to_array(),
-                bone_index: bone_idx,
-            });
-
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w2).to_array(),
-                normal: Vec3::from(n2).to_array(),
-                bone_index: bone_idx,
-            });
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w3).to_array(),
-                normal: Vec3::from(n3).to_array(),
-                bone_index: bone_idx,
-            });
-
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w2).to_array(),
-                normal: Vec3::from(n2).to_array(),
-                bone_index: bone_idx,
-            });
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w4).to_array(),
-                normal: Vec3::from(n4).to_array(),
-                bone_index: bone_idx,
-            });
-            vertices.push(SkinnedVertex {
-                position: Vec3::from(w3).to_array(),
-                normal: Vec3::from(n3).to_array(),
-                bone_index: bone_idx,
-            });
+            vertices.push(SkinnedVertex::rigid(w1, n1, bone_idx));
+            vertices.push(SkinnedVertex::rigid(w2, n2, bone_idx));
+            vertices.push(SkinnedVertex::rigid(w3, n3, bone_idx));
+
+            vertices.push(SkinnedVertex::rigid(w2, n2, bone_idx));
+            vertices.push(SkinnedVertex::rigid(w4, n4, bone_idx));
+            vertices.push(SkinnedVertex::rigid(w3, n3, bone_idx));
         }
     }
 }
 
+/// Bind pose mesh with the default radii
+/// Pure CPU geometry, one primitive per `RENDER_BONE_COUNT` index in
+/// `compute_bone_matrices` order; uploaded once at GPU init.
 pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
+    generate_bind_pose_mesh_with_radii(&RadiiConfig::default())
+}
+
+/// Bind pose mesh with the given bone and head radii
+pub fn generate_bind_pose_mesh_with_radii(radii: &RadiiConfig) -> Vec<SkinnedVertex> {
     let mut vertices = Vec::new();
-    use crate::skeleton_constants::*;
+    let rest = rest_joint_position;
 
     // Matrix index - must be incremented for each primitive (cylinder/sphere)
     // This matches the order in pose.rs compute_bone_matrices()
@@ -304,49 +481,49 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     // Spine chain (5 cylinders)
     add_cylinder(
         &mut vertices,
-        DEFAULT_PELVIS,
-        DEFAULT_SPINE1,
-        BONE_RADIUS,
+        rest(BoneId::Pelvis),
+        rest(BoneId::Spine1),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_SPINE1,
-        DEFAULT_SPINE2,
-        BONE_RADIUS,
+        rest(BoneId::Spine1),
+        rest(BoneId::Spine2),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_SPINE2,
-        DEFAULT_SPINE3,
-        BONE_RADIUS,
+        rest(BoneId::Spine2),
+        rest(BoneId::Spine3),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_SPINE3,
-        DEFAULT_NECK,
-        BONE_RADIUS,
+        rest(BoneId::Spine3),
+        rest(BoneId::Neck),
+        radii.bone,
         idx,
     );
     idx += 1;
     // Neck -> Head
     // Shorten the neck so it connects to the base of the head sphere (surface)
     // instead of the center, to avoid visual artifacts inside the head.
-    let neck_dir = (DEFAULT_HEAD - DEFAULT_NECK).normalize();
+    let neck_dir = (rest(BoneId::Head) - rest(BoneId::Neck)).normalize();
     // Stop slightly inside the head to ensure connection (0.8 * radius)
-    let neck_end = DEFAULT_HEAD - neck_dir * (HEAD_RADIUS * 0.8);
+    let neck_end = rest(BoneId::Head) - neck_dir * (radii.head * 0.8);
 
     // Disable end cap (false) to avoid it protruding into the head sphere
     add_cylinder_caps(
         &mut vertices,
-        DEFAULT_NECK,
+        rest(BoneId::Neck),
         neck_end,
-        BONE_RADIUS,
+        radii.bone,
         idx,
         true,
         false,
@@ -356,33 +533,33 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     // Left Arm chain (4 cylinders)
     add_cylinder(
         &mut vertices,
-        DEFAULT_SPINE3,
-        DEFAULT_LEFT_COLLAR,
-        BONE_RADIUS,
+        rest(BoneId::Spine3),
+        rest(BoneId::LeftCollar),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_COLLAR,
-        DEFAULT_LEFT_SHOULDER,
-        BONE_RADIUS,
+        rest(BoneId::LeftCollar),
+        rest(BoneId::LeftShoulder),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_SHOULDER,
-        DEFAULT_LEFT_ELBOW,
-        BONE_RADIUS,
+        rest(BoneId::LeftShoulder),
+        rest(BoneId::LeftElbow),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_ELBOW,
-        DEFAULT_LEFT_WRIST,
-        BONE_RADIUS,
+        rest(BoneId::LeftElbow),
+        rest(BoneId::LeftWrist),
+        radii.bone,
         idx,
     );
     idx += 1;
@@ -390,33 +567,33 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     // Right Arm chain (4 cylinders)
     add_cylinder(
         &mut vertices,
-        DEFAULT_SPINE3,
-        DEFAULT_RIGHT_COLLAR,
-        BONE_RADIUS,
+        rest(BoneId::Spine3),
+        rest(BoneId::RightCollar),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_COLLAR,
-        DEFAULT_RIGHT_SHOULDER,
-        BONE_RADIUS,
+        rest(BoneId::RightCollar),
+        rest(BoneId::RightShoulder),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_SHOULDER,
-        DEFAULT_RIGHT_ELBOW,
-        BONE_RADIUS,
+        rest(BoneId::RightShoulder),
+        rest(BoneId::RightElbow),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_ELBOW,
-        DEFAULT_RIGHT_WRIST,
-        BONE_RADIUS,
+        rest(BoneId::RightElbow),
+        rest(BoneId::RightWrist),
+        radii.bone,
         idx,
     );
     idx += 1;
@@ -424,33 +601,33 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     // Left Leg chain (4 cylinders)
     add_cylinder(
         &mut vertices,
-        DEFAULT_PELVIS,
-        DEFAULT_LEFT_HIP,
-        BONE_RADIUS,
+        rest(BoneId::Pelvis),
+        rest(BoneId::LeftHip),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_HIP,
-        DEFAULT_LEFT_KNEE,
-        BONE_RADIUS,
+        rest(BoneId::LeftHip),
+        rest(BoneId::LeftKnee),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_KNEE,
-        DEFAULT_LEFT_ANKLE,
-        BONE_RADIUS,
+        rest(BoneId::LeftKnee),
+        rest(BoneId::LeftAnkle),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_LEFT_ANKLE,
-        DEFAULT_LEFT_FOOT,
-        BONE_RADIUS,
+        rest(BoneId::LeftAnkle),
+        rest(BoneId::LeftFoot),
+        radii.bone,
         idx,
     );
     idx += 1;
@@ -458,39 +635,39 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     // Right Leg chain (4 cylinders)
     add_cylinder(
         &mut vertices,
-        DEFAULT_PELVIS,
-        DEFAULT_RIGHT_HIP,
-        BONE_RADIUS,
+        rest(BoneId::Pelvis),
+        rest(BoneId::RightHip),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_HIP,
-        DEFAULT_RIGHT_KNEE,
-        BONE_RADIUS,
+        rest(BoneId::RightHip),
+        rest(BoneId::RightKnee),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_KNEE,
-        DEFAULT_RIGHT_ANKLE,
-        BONE_RADIUS,
+        rest(BoneId::RightKnee),
+        rest(BoneId::RightAnkle),
+        radii.bone,
         idx,
     );
     idx += 1;
     add_cylinder(
         &mut vertices,
-        DEFAULT_RIGHT_ANKLE,
-        DEFAULT_RIGHT_FOOT,
-        BONE_RADIUS,
+        rest(BoneId::RightAnkle),
+        rest(BoneId::RightFoot),
+        radii.bone,
         idx,
     );
     idx += 1;
 
     // Head Sphere (1 sphere)
-    add_sphere(&mut vertices, DEFAULT_HEAD, HEAD_RADIUS, idx);
+    add_sphere(&mut vertices, rest(BoneId::Head), radii.head, idx);
     // idx += 1; // Last part, no need to increment
 
     // Debug joints (22 spheres) - REMOVED
@@ -526,18 +703,196 @@ pub fn generate_bind_pose_mesh() -> Vec<SkinnedVertex> {
     }
     */
 
+    bake_ambient_occlusion(&mut vertices, &bind_pose_occluders(radii));
+    vertices
+}
+
+/// Box mesh for a scene prop, centered on the origin
+///
+/// Every vertex uses bone matrix 0 rigidly, so the skeleton shader draws it with
+/// the prop's transform in that slot. Faces are flat-shaded and unoccluded.
+pub fn generate_box_mesh(width: f32, height: f32, depth: f32) -> Vec<SkinnedVertex> {
+    let half = Vec3::new(width, height, depth) * 0.5;
+    let mut vertices = Vec::with_capacity(36);
+    for normal in [
+        Vec3::X,
+        Vec3::NEG_X,
+        Vec3::Y,
+        Vec3::NEG_Y,
+        Vec3::Z,
+        Vec3::NEG_Z,
+    ] {
+        // Two in-face axes with u x v = normal, so triangles wind counter-clockwise
+        let u = normal.any_orthonormal_vector();
+        let v = normal.cross(u);
+        let corner = |a: f32, b: f32| (normal + u * a + v * b) * half;
+        let quad = [
+            corner(-1.0, -1.0),
+            corner(1.0, -1.0),
+            corner(1.0, 1.0),
+            corner(-1.0, 1.0),
+        ];
+        for i in [0, 1, 2, 0, 2, 3] {
+            vertices.push(SkinnedVertex::rigid(quad[i], normal, 0));
+        }
+    }
     vertices
 }
 
+/// Primitive of the bind pose mesh as a capsule, for ambient occlusion
+/// A sphere (the head) has `start == end`.
+#[derive(Debug, Clone, Copy)]
+pub struct Occluder {
+    /// Render primitive (bone index) whose geometry this bounds
+    pub primitive: u32,
+    pub start: Vec3,
+    pub end: Vec3,
+    pub radius: f32,
+}
+
+impl Occluder {
+    /// Closest point on the capsule's axis to `p`
+    fn closest_axis_point(&self, p: Vec3) -> Vec3 {
+        let axis = self.end - self.start;
+        let length_sq = axis.length_squared();
+        if length_sq < MIN_SEGMENT_LENGTH * MIN_SEGMENT_LENGTH {
+            return self.start;
+        }
+        let t = ((p - self.start).dot(axis) / length_sq).clamp(0.0, 1.0);
+        self.start + axis * t
+    }
+}
+
+/// Occluders matching `generate_bind_pose_mesh_with_radii`: one capsule per
+/// `RENDER_SEGMENTS` entry, then the head sphere
+pub fn bind_pose_occluders(radii: &RadiiConfig) -> Vec<Occluder> {
+    let mut occluders: Vec<Occluder> = RENDER_SEGMENTS
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| Occluder {
+            primitive: i as u32,
+            start: rest_joint_position(start).into(),
+            end: rest_joint_position(end).into(),
+            radius: radii.bone,
+        })
+        .collect();
+    let head = rest_joint_position(BoneId::Head).into();
+    occluders.push(Occluder {
+        primitive: RENDER_SEGMENTS.len() as u32,
+        start: head,
+        end: head,
+        radius: radii.head,
+    });
+    occluders
+}
+
+/// Bake a per-vertex ambient occlusion term for the bind pose
+///
+/// A vertex is occluded by the capsules of *other* primitives whose surface
+/// lies in front of it within `AO_DISTANCE`, weighted by proximity and by how
+/// directly it faces them; vertices inside another capsule are fully occluded.
+/// Joints, where neighbouring cylinders meet, end up darker than open limb
+/// surfaces. Each vertex is tested against the capsules analytically, so the
+/// cost is vertices * primitives rather than vertices squared.
+pub fn bake_ambient_occlusion(vertices: &mut [SkinnedVertex], occluders: &[Occluder]) {
+    for v in vertices.iter_mut() {
+        let p = Vec3::from_array(v.position);
+        let n = Vec3::from_array(v.normal);
+        let mut occlusion = 0.0f32;
+        for occluder in occluders {
+            if occluder.primitive == v.bone_index {
+                continue;
+            }
+            let to_axis = occluder.closest_axis_point(p) - p;
+            let axis_distance = to_axis.length();
+            let d = axis_distance - occluder.radius;
+            if d >= AO_DISTANCE {
+                continue;
+            }
+            if d <= 0.0 {
+                occlusion = 1.0;
+                break;
+            }
+            let facing = n.dot(to_axis / axis_distance).max(0.0);
+            occlusion = occlusion.max((1.0 - d / AO_DISTANCE) * facing);
+        }
+        v.ao = 1.0 - AO_STRENGTH * occlusion;
+    }
+}
+
+/// Last bone matrices sent to the GPU, to skip re-uploading an unchanged pose
+/// (paused playback, idle editor). Matrices are compared exactly.
+#[derive(Debug, Default)]
+pub struct BoneUploadCache {
+    last: Option<Vec<Mat4>>,
+}
+
+impl BoneUploadCache {
+    /// Call `write` with `matrices` unless they equal the last uploaded set
+    /// Returns whether `write` was called.
+    pub fn upload_if_changed(&mut self, matrices: &[Mat4], write: impl FnOnce(&[Mat4])) -> bool {
+        if self.last.as_deref() == Some(matrices) {
+            return false;
+        }
+        write(matrices);
+        let last = self.last.get_or_insert_with(Vec::new);
+        last.clear();
+        last.extend_from_slice(matrices);
+        true
+    }
+
+    /// Forget the last upload, e.g. after the buffer was written on the GPU
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Visibility mask with every render bone shown (bit i = render bone i)
+pub const ALL_BONES_VISIBLE: u32 = (1 << RENDER_BONE_COUNT) - 1;
+
+/// Zero-scale version of a bone matrix: its geometry collapses onto the bone's
+/// origin, so the triangles have no area and are not drawn
+pub fn hide_bone_matrix(matrix: Mat4) -> Mat4 {
+    Mat4::from_cols(
+        glam::Vec4::ZERO,
+        glam::Vec4::ZERO,
+        glam::Vec4::ZERO,
+        matrix.w_axis,
+    )
+}
+
+/// Collapse the matrices of bones whose bit is clear in `visible`
+pub fn apply_bone_visibility(matrices: &mut [Mat4], visible: u32) {
+    for (i, matrix) in matrices.iter_mut().enumerate() {
+        if visible & (1 << i) == 0 {
+            *matrix = hide_bone_matrix(*matrix);
+        }
+    }
+}
+
 pub fn compute_aligned_matrix(
     b_start: Vec3A,
     b_end: Vec3A,
     c_start: Vec3A,
     c_end: Vec3A,
 ) -> glam::Mat4 {
-    let b_dir = (b_end - b_start).normalize();
-    let c_dir = (c_end - c_start).normalize();
-    let rot = glam::Quat::from_rotation_arc(Vec3::from(b_dir), Vec3::from(c_dir));
+    aligned_matrix_from_direction(b_start, segment_direction(b_start, b_end), c_start, c_end)
+}
+
+/// `compute_aligned_matrix` with the bind direction already known (see `BindData`)
+pub fn aligned_matrix_from_direction(
+    b_start: Vec3A,
+    b_dir: Option<Vec3A>,
+    c_start: Vec3A,
+    c_end: Vec3A,
+) -> glam::Mat4 {
+    // A zero-length bind or current segment has no direction to align: translate only
+    let rot = match (b_dir, segment_direction(c_start, c_end)) {
+        (Some(b_dir), Some(c_dir)) => {
+            glam::Quat::from_rotation_arc(Vec3::from(b_dir), Vec3::from(c_dir))
+        }
+        _ => glam::Quat::IDENTITY,
+    };
     glam::Mat4::from_translation(Vec3::from(c_start))
         * glam::Mat4::from_quat(rot)
         * glam::Mat4::from_translation(-Vec3::from(b_start))
@@ -547,34 +902,142 @@ pub fn compute_offset_matrix(b_center: Vec3A, c_center: Vec3A) -> glam::Mat4 {
     glam::Mat4::from_translation(Vec3::from(c_center - b_center))
 }
 
+/// Bounding sphere of a skinned skeleton from its bone matrices: (center, radius)
+///
+/// Each primitive's bind endpoints are moved by its matrix, so this bounds the
+/// mesh as drawn; the larger of the bone and head radius covers the primitives'
+/// thickness.
+pub fn matrices_bounding_sphere(
+    matrices: &[glam::Mat4; RENDER_BONE_COUNT],
+    radii: &RadiiConfig,
+) -> (Vec3, f32) {
+    let head = Vec3::from(rest_joint_position(BoneId::Head));
+    let endpoints = RENDER_SEGMENTS
+        .iter()
+        .zip(matrices)
+        .flat_map(|(&(start, end), matrix)| {
+            [start, end]
+                .map(|joint| matrix.transform_point3(Vec3::from(rest_joint_position(joint))))
+        })
+        .chain([matrices[RENDER_BONE_COUNT - 1].transform_point3(head)]);
+    let margin = radii.head.max(radii.bone);
+    crate::camera::bounding_sphere(endpoints, margin).unwrap_or((head, margin))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_matrices_bounding_sphere_contains_joints() {
+        let pose = crate::bone::RotationPose::bind_pose()
+            .with_root_position(Vec3::new(3.0, 1.0, -2.0))
+            .with_rotation(BoneId::LeftShoulder, glam::Quat::from_rotation_z(-1.2))
+            .with_rotation(BoneId::RightKnee, glam::Quat::from_rotation_x(-1.4));
+        let matrices = pose.compute_bone_matrices();
+        let (center, radius) = matrices_bounding_sphere(&matrices, &RadiiConfig::default());
+        for bone in BoneId::ALL {
+            let distance = pose.get_position(bone).distance(center);
+            assert!(distance + HEAD_RADIUS <= radius + 1e-4, "{:?}", bone);
+        }
+        // No looser than the pose's own capsule-based sphere needs to be
+        assert!(radius <= pose.bounding_sphere().1 + 1e-4);
+
+        // Thick bones pad past the head radius: every skinned vertex stays inside
+        let radii = RadiiConfig {
+            bone: 0.25,
+            head: 0.12,
+        };
+        let (center, radius) = matrices_bounding_sphere(&matrices, &radii);
+        for v in generate_bind_pose_mesh_with_radii(&radii) {
+            let p = skin_vertex(&v, &matrices, SkinningMode::Rigid);
+            assert!(p.distance(center) <= radius + 1e-4, "{:?}", p);
+        }
+        let thin = matrices_bounding_sphere(&matrices, &RadiiConfig::default()).1;
+        assert!((radius - thin - (radii.bone - HEAD_RADIUS)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_cached_bind_data_matches_fresh_matrices() {
+        assert_eq!(*bind_data(), BindData::compute());
+
+        let pose = crate::bone::RotationPose::bind_pose()
+            .with_root_position(Vec3::new(0.4, 1.1, -0.3))
+            .with_rotation(BoneId::Spine2, glam::Quat::from_rotation_x(0.4))
+            .with_rotation(BoneId::LeftElbow, glam::Quat::from_rotation_y(-1.1));
+        let positions = BoneId::ALL.map(|bone| Vec3A::from(pose.get_position(bone)));
+
+        // Aligning each segment from scratch, as before the cache
+        let mut fresh = [Mat4::IDENTITY; RENDER_BONE_COUNT];
+        for (matrix, &(start, end)) in fresh.iter_mut().zip(&RENDER_SEGMENTS) {
+            *matrix = compute_aligned_matrix(
+                rest_joint_position(start),
+                rest_joint_position(end),
+                positions[start.index()],
+                positions[end.index()],
+            );
+        }
+        fresh[RENDER_BONE_COUNT - 1] = compute_offset_matrix(
+            rest_joint_position(BoneId::Head),
+            positions[BoneId::Head.index()],
+        );
+
+        assert_eq!(bind_data().bone_matrices(&positions), fresh);
+    }
+
+    #[test]
+    fn test_box_prop_follows_its_transform() {
+        let vertices = generate_box_mesh(0.4, 0.2, 1.2);
+        assert_eq!(vertices.len(), 36);
+
+        let transform = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            glam::Quat::from_rotation_y(0.7),
+            Vec3::new(0.5, 0.1, -1.0),
+        );
+        let mut matrices = [Mat4::IDENTITY; RENDER_BONE_COUNT];
+        matrices[0] = transform;
+        for vertex in &vertices {
+            let local = Vec3::from_array(vertex.position);
+            let normal = Vec3::from_array(vertex.normal);
+            // Corners lie on the box, and faces wind outward
+            assert!(local.abs().abs_diff_eq(Vec3::new(0.2, 0.1, 0.6), 1e-6));
+            assert!(local.dot(normal) > 0.0);
+            for mode in [SkinningMode::Rigid, SkinningMode::Linear] {
+                let world = skin_vertex(vertex, &matrices, mode);
+                assert!(world.abs_diff_eq(transform.transform_point3(local), 1e-5));
+            }
+        }
+        for triangle in vertices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vec3::from_array(triangle[i].position));
+            let face_normal = (b - a).cross(c - a);
+            assert!(face_normal.dot(Vec3::from_array(triangle[0].normal)) > 0.0);
+        }
+    }
+
     #[test]
     fn test_generate_bind_pose_mesh_validity() {
         let vertices = generate_bind_pose_mesh();
 
-        // Basic validity
-        // Bounds check (roughly)
-        // 21 cylinders + 1 head sphere + 22 joint spheres
-        // Each cylinder (12 segments): (12*2 body + 12*2 caps) = 48 tris = 144 vertices
-        // Spine chain (5) + Arms (4*2) + Legs (4*2) = 21 cylinders.
-        // 21 * 144 = 3024 vertices.
-        // Sphere (16 lat * 24 lon * 2 tris) = 768 tris = 2304 vertices.
-        // Debug joints (22) = 22 * 2304 = 50688 vertices.
-        // Total should be around 56k vertices.
-        assert!(!vertices.is_empty(), "Mesh should not be empty");
-        // Let's just check it's substantial.
-        assert!(vertices.len() > 10_000);
+        // 21 capped cylinders (the neck has no end cap) and the head sphere
+        let body = CYLINDER_SECTIONS * CYLINDER_SEGMENTS * 6;
+        let cap = SPHERE_LAT_SEGMENTS / 2 * CYLINDER_SEGMENTS * 6;
+        let sphere = SPHERE_LAT_SEGMENTS * SPHERE_LON_SEGMENTS * 6;
+        let cylinders = RENDER_BONE_COUNT - 1;
+        let expected = cylinders * body + (2 * cylinders - 1) * cap + sphere;
+        assert_eq!(vertices.len(), expected);
         assert_eq!(
             vertices.len() % 3,
             0,
             "Vertex count must be multiple of 3 (triangles)"
         );
 
-        // Check normals are normalized
+        let mut seen = [false; RENDER_BONE_COUNT];
         for v in &vertices {
+            let p = Vec3::from_array(v.position);
+            assert!(p.is_finite(), "Non-finite position {:?}", p);
+
+            // Check normals are normalized
             let n = Vec3::from_array(v.normal);
             let len = n.length();
             assert!(
@@ -583,9 +1046,188 @@ mod tests {
                 len
             );
 
-            // Bone index should be within reason
-            assert!(v.bone_index < 50, "Bone index out of range");
+            assert!(
+                (v.bone_index as usize) < RENDER_BONE_COUNT,
+                "Bone index out of range: {}",
+                v.bone_index
+            );
+            assert!((v.blend_index as usize) < RENDER_BONE_COUNT);
+            assert!((0.0..=1.0).contains(&v.ao), "AO out of range: {}", v.ao);
+            seen[v.bone_index as usize] = true;
         }
+        // Every render primitive has geometry
+        assert!(seen.iter().all(|&s| s), "Missing bone indices: {:?}", seen);
+    }
+
+    #[test]
+    fn test_ao_darker_at_joints() {
+        let (shoulder, elbow) = (
+            rest_joint_position(BoneId::LeftShoulder),
+            rest_joint_position(BoneId::LeftElbow),
+        );
+
+        // Left upper arm (shoulder -> elbow) is primitive 7
+        let vertices = generate_bind_pose_mesh();
+        let mean_ao_near = |point: Vec3A| {
+            let near: Vec<f32> = vertices
+                .iter()
+                .filter(|v| v.bone_index == 7)
+                .filter(|v| Vec3A::from_array(v.position).distance(point) < 2.0 * BONE_RADIUS)
+                .map(|v| v.ao)
+                .collect();
+            assert!(!near.is_empty());
+            near.iter().sum::<f32>() / near.len() as f32
+        };
+
+        let joint = mean_ao_near(elbow);
+        let midpoint = mean_ao_near((shoulder + elbow) * 0.5);
+        assert!(
+            joint < midpoint,
+            "Joint AO {} should be below midpoint AO {}",
+            joint,
+            midpoint
+        );
+        assert!(
+            (midpoint - 1.0).abs() < 1e-6,
+            "Open limb should be unoccluded"
+        );
+    }
+
+    #[test]
+    fn test_ao_darkens_with_occluder_proximity() {
+        // A vertical capsule (primitive 1) at the origin, seen by primitive 0
+        let occluders = [Occluder {
+            primitive: 1,
+            start: Vec3::ZERO,
+            end: Vec3::Y,
+            radius: 0.1,
+        }];
+        let facing = |x: f32| SkinnedVertex::rigid(Vec3::new(x, 0.5, 0.0), Vec3::NEG_X, 0);
+        let mut vertices = vec![
+            facing(0.05),                                                    // inside the capsule
+            facing(0.1 + AO_DISTANCE * 0.25),                                // near
+            facing(0.1 + AO_DISTANCE * 0.75),                                // far
+            facing(0.1 + AO_DISTANCE * 2.0),                                 // out of range
+            SkinnedVertex::rigid(Vec3::new(0.2, 0.5, 0.0), Vec3::X, 0),      // facing away
+            SkinnedVertex::rigid(Vec3::new(0.05, 0.5, 0.0), Vec3::NEG_X, 1), // own capsule
+        ];
+        bake_ambient_occlusion(&mut vertices, &occluders);
+        let ao: Vec<f32> = vertices.iter().map(|v| v.ao).collect();
+
+        assert_eq!(ao[0], 1.0 - AO_STRENGTH);
+        assert!(ao[0] < ao[1] && ao[1] < ao[2] && ao[2] < 1.0, "{:?}", ao);
+        assert_eq!(ao[3], 1.0);
+        assert_eq!(ao[4], 1.0);
+        assert_eq!(ao[5], 1.0);
+    }
+
+    #[test]
+    fn test_bind_pose_ao_darkens_near_torso() {
+        use crate::bone::BoneId;
+
+        let vertices = generate_bind_pose_mesh();
+        let torso = Vec3::from(rest_joint_position(BoneId::Spine3));
+        let wrist = Vec3::from(rest_joint_position(BoneId::LeftWrist));
+        let nearest = |target: Vec3| {
+            vertices
+                .iter()
+                .min_by(|a, b| {
+                    let da = Vec3::from_array(a.position).distance(target);
+                    let db = Vec3::from_array(b.position).distance(target);
+                    da.total_cmp(&db)
+                })
+                .unwrap()
+        };
+
+        // The outstretched hand has nothing nearby
+        assert_eq!(nearest(wrist).ao, 1.0);
+        // The collar stub meets the spine at Spine3
+        let shoulder_ao = vertices
+            .iter()
+            .filter(|v| v.bone_index == 5)
+            .map(|v| v.ao)
+            .fold(1.0f32, f32::min);
+        assert!(shoulder_ao < 1.0, "{}", shoulder_ao);
+        assert!(nearest(torso).ao < nearest(wrist).ao);
+        assert!(vertices
+            .iter()
+            .all(|v| v.ao >= 1.0 - AO_STRENGTH && v.ao <= 1.0));
+    }
+
+    #[test]
+    fn test_linear_skinning_bends_elbow() {
+        use crate::bone::{BoneId, RotationPose};
+
+        // Bend the left forearm (primitive 8) 90 degrees at the elbow
+        let pose = RotationPose::bind_pose()
+            .with_rotation(BoneId::LeftElbow, glam::Quat::from_rotation_y(1.5));
+        let matrices = pose.compute_bone_matrices();
+        let vertices = generate_bind_pose_mesh();
+
+        let forearm = vertices.iter().filter(|v| v.bone_index == 8);
+        let (mut blended, mut far) = (0, 0);
+        for v in forearm {
+            let own = matrices[8].transform_point3(Vec3::from_array(v.position));
+            let parent = matrices[7].transform_point3(Vec3::from_array(v.position));
+            let rigid = skin_vertex(v, &matrices, SkinningMode::Rigid);
+            let linear = skin_vertex(v, &matrices, SkinningMode::Linear);
+
+            // Rigid follows the forearm alone: a hard crease at the elbow
+            assert!(rigid.abs_diff_eq(own, 1e-5));
+
+            let along =
+                (Vec3A::from_array(v.position) - rest_joint_position(BoneId::LeftElbow)).length();
+            if along < BONE_RADIUS {
+                // Near the joint, linear lies between the upper arm and forearm
+                let gap = own.distance(parent);
+                assert!(gap > 1e-3);
+                assert!(linear.distance(own) < gap && linear.distance(parent) < gap);
+                assert!(linear.distance(own) > 1e-4);
+                blended += 1;
+            } else if along > SKIN_BLEND_LENGTH + BONE_RADIUS {
+                assert!(linear.abs_diff_eq(rigid, 1e-5));
+                far += 1;
+            }
+        }
+        assert!(blended > 0 && far > 0);
+    }
+
+    #[test]
+    fn test_bone_upload_skips_unchanged_pose() {
+        let mut cache = BoneUploadCache::default();
+        let mut writes = 0;
+        let bind = [Mat4::IDENTITY; RENDER_BONE_COUNT];
+        let mut moved = bind;
+        moved[3] = Mat4::from_translation(Vec3::X);
+
+        for matrices in [&bind, &bind, &moved, &moved, &bind] {
+            cache.upload_if_changed(matrices, |_| writes += 1);
+        }
+        assert_eq!(writes, 3);
+    }
+
+    #[test]
+    fn test_hidden_bone_collapses() {
+        let mut matrices = [Mat4::IDENTITY; RENDER_BONE_COUNT];
+        matrices[5] = Mat4::from_translation(Vec3::new(0.2, 1.0, 0.0));
+        let visible = ALL_BONES_VISIBLE & !(1 << 3) & !(1 << 5);
+
+        let mut hidden = matrices;
+        apply_bone_visibility(&mut hidden, visible);
+        assert_eq!(hidden[3], hide_bone_matrix(Mat4::IDENTITY));
+        assert_eq!(hidden[3].transform_vector3(Vec3::ONE), Vec3::ZERO);
+        // Collapsed onto the bone origin, not the world origin
+        assert_eq!(
+            hidden[5].transform_point3(Vec3::new(1.0, 2.0, 3.0)),
+            Vec3::new(0.2, 1.0, 0.0)
+        );
+        assert_eq!(hidden[4], Mat4::IDENTITY);
+
+        // Showing all bones again uploads the matrices unchanged
+        let mut shown = matrices;
+        apply_bone_visibility(&mut shown, ALL_BONES_VISIBLE);
+        assert_eq!(shown, matrices);
+        assert_eq!(shown[3], Mat4::IDENTITY);
     }
 
     #[test]
@@ -605,4 +1247,31 @@ mod tests {
         assert!((transformed_vector.y - 1.0).abs() < 0.001);
         assert!(transformed_vector.x.abs() < 0.001);
     }
+
+    #[test]
+    fn test_zero_length_segments_are_finite() {
+        let point = Vec3A::new(0.0, 1.5, 0.0);
+        let target = Vec3A::new(1.0, 1.0, 0.0);
+
+        // Degenerate current segment: translation only
+        let mat = compute_aligned_matrix(point, point + Vec3A::Y, target, target);
+        assert!(mat.is_finite());
+        assert!(mat
+            .transform_point3(Vec3::from(point))
+            .abs_diff_eq(Vec3::from(target), 1e-6));
+
+        // Degenerate bind segment
+        let mat = compute_aligned_matrix(point, point, target, target + Vec3A::X);
+        assert!(mat.is_finite());
+
+        // Zero-length cylinder still yields valid geometry
+        let mut vertices = Vec::new();
+        add_cylinder(&mut vertices, point, point, BONE_RADIUS, 0);
+        assert!(!vertices.is_empty());
+        for v in &vertices {
+            assert!(Vec3::from_array(v.position).is_finite());
+            assert!(Vec3::from_array(v.normal).is_finite());
+            assert!(v.blend_weight.is_finite());
+        }
+    }
 }
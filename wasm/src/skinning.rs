@@ -0,0 +1,456 @@
+//! GPU skinning: forward kinematics and bone matrices in a compute pass
+//!
+//! With many skeletons on screen, running `RotationPose::compute_bone_matrices`
+//! for each on the CPU becomes the bottleneck. `GpuSkinning` uploads each
+//! instance's root, local rotations and segment lengths to a storage buffer and
+//! `skinning.wgsl` writes `RENDER_BONE_COUNT` matrices per instance, ready to be
+//! copied into the bone matrix buffers before the draw.
+//!
+//! WebGL2 has neither compute shaders nor storage buffers; check
+//! `supports_compute` and fall back to the CPU path there.
+
+use static_assertions::const_assert_eq;
+
+use crate::bone::{BoneId, RotationPose, BONE_HIERARCHY};
+use crate::skeleton::{
+    apply_bone_visibility, compute_aligned_matrix, rest_joint_position, ALL_BONES_VISIBLE,
+    RENDER_BONE_COUNT, RENDER_SEGMENTS,
+};
+
+pub const SKINNING_SHADER: &str = include_str!("shaders/skinning.wgsl");
+
+/// Invocations per workgroup, one instance each (`@workgroup_size` in skinning.wgsl)
+pub const SKINNING_WORKGROUP_SIZE: u32 = 64;
+
+/// Storage buffers bound by the compute pass
+const STORAGE_BUFFER_COUNT: u32 = 3;
+
+/// One bone of the hierarchy (WGSL `Bone`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinningBone {
+    /// Unit direction of the segment ending at this bone, in the parent's frame
+    pub direction: [f32; 3],
+    /// Parent bone index, -1 for the root
+    pub parent: i32,
+}
+
+/// Start and end joint of a render primitive (WGSL `Segment`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinningSegment {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Skeleton layout shared by every instance (WGSL `Tables`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinningTables {
+    pub bones: [SkinningBone; BoneId::COUNT],
+    /// Bind pose joint positions (w unused)
+    pub rest_positions: [[f32; 4]; BoneId::COUNT],
+    /// `RENDER_SEGMENTS`, then the head sphere as a zero-length head-to-head segment
+    pub segments: [SkinningSegment; RENDER_BONE_COUNT],
+}
+const_assert_eq!(std::mem::size_of::<SkinningTables>(), 880);
+
+impl Default for SkinningTables {
+    fn default() -> Self {
+        let bones = BoneId::ALL.map(|bone| {
+            let def = &BONE_HIERARCHY[bone.index()];
+            SkinningBone {
+                direction: def.direction.normalize().to_array(),
+                parent: def.parent.map_or(-1, |parent| parent.index() as i32),
+            }
+        });
+        let rest_positions = BoneId::ALL.map(|bone| rest_joint_position(bone).extend(0.0).into());
+        let head = (BoneId::Head, BoneId::Head);
+        let segments = std::array::from_fn(|i| {
+            let (start, end) = RENDER_SEGMENTS.get(i).copied().unwrap_or(head);
+            SkinningSegment {
+                start: start.index() as u32,
+                end: end.index() as u32,
+            }
+        });
+        Self {
+            bones,
+            rest_positions,
+            segments,
+        }
+    }
+}
+
+/// Per-instance pose input (WGSL `Instance`)
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkinningInstance {
+    /// Root position (w unused)
+    pub root: [f32; 4],
+    /// Local rotations as (x, y, z, w)
+    pub rotations: [[f32; 4]; BoneId::COUNT],
+    pub lengths: [f32; BoneId::COUNT],
+    /// Render bones to draw (bit i = render bone i); hidden ones collapse as in
+    /// `apply_bone_visibility`
+    pub visible_bones: u32,
+    pub _padding: f32, // -> total 464 bytes
+}
+const_assert_eq!(std::mem::size_of::<SkinningInstance>(), 464);
+
+impl From<&RotationPose> for SkinningInstance {
+    fn from(pose: &RotationPose) -> Self {
+        // The shader's FK has no correctives, so fold them into the rotations
+        Self {
+            root: pose.root_position.extend(0.0).to_array(),
+            rotations: pose.pose_correctives().map(|q| q.to_array()),
+            lengths: pose.bone_lengths,
+            visible_bones: ALL_BONES_VISIBLE,
+            _padding: 0.0,
+        }
+    }
+}
+
+impl SkinningInstance {
+    /// Same instance drawing only the render bones set in `visible`
+    pub fn with_visible_bones(mut self, visible: u32) -> Self {
+        self.visible_bones = visible;
+        self
+    }
+}
+
+/// Run the compute shader's algorithm on the CPU
+/// Used to check the packed layout; matches `RotationPose::compute_bone_matrices`.
+pub fn compute_instance_matrices(
+    tables: &SkinningTables,
+    instance: &SkinningInstance,
+) -> [glam::Mat4; RENDER_BONE_COUNT] {
+    let mut positions = [glam::Vec3A::ZERO; BoneId::COUNT];
+    let mut rotations = [glam::Quat::IDENTITY; BoneId::COUNT];
+    for (i, bone) in tables.bones.iter().enumerate() {
+        let (parent_pos, parent_rot) = match usize::try_from(bone.parent) {
+            Ok(parent) => (positions[parent], rotations[parent]),
+            Err(_) => (
+                glam::Vec4::from_array(instance.root).truncate().into(),
+                glam::Quat::IDENTITY,
+            ),
+        };
+        rotations[i] = parent_rot * glam::Quat::from_array(instance.rotations[i]);
+        let bone_vector = glam::Vec3::from_array(bone.direction) * instance.lengths[i];
+        positions[i] = parent_pos + glam::Vec3A::from(parent_rot * bone_vector);
+    }
+
+    let rest = |joint: u32| glam::Vec4::from_array(tables.rest_positions[joint as usize]);
+    let mut matrices = tables.segments.map(|segment| {
+        compute_aligned_matrix(
+            rest(segment.start).truncate().into(),
+            rest(segment.end).truncate().into(),
+            positions[segment.start as usize],
+            positions[segment.end as usize],
+        )
+    });
+    apply_bone_visibility(&mut matrices, instance.visible_bones);
+    matrices
+}
+
+/// Whether `device` can run the skinning compute pass
+/// False on WebGL2 (no compute shaders, no storage buffers).
+pub fn supports_compute(adapter: &wgpu::Adapter, device: &wgpu::Device) -> bool {
+    adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        && device.limits().max_storage_buffers_per_shader_stage >= STORAGE_BUFFER_COUNT
+}
+
+/// Compute pipeline and buffers for GPU skinning
+pub struct GpuSkinning {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    tables: wgpu::Buffer,
+    instances: wgpu::Buffer,
+    /// Output: `RENDER_BONE_COUNT` matrices per instance, see `matrix_offset`
+    pub matrices: wgpu::Buffer,
+    /// Instances the buffers can hold
+    capacity: usize,
+}
+
+impl GpuSkinning {
+    /// Build the pipeline; only call when `supports_compute` is true
+    pub fn new(device: &wgpu::Device) -> Self {
+        use wgpu::util::DeviceExt;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Skinning Shader"),
+            source: wgpu::ShaderSource::Wgsl(SKINNING_SHADER.into()),
+        });
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Skinning Bind Group Layout"),
+            entries: &[
+                storage_entry(0, true),
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Skinning Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Skinning Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+
+        let tables = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skinning Tables Buffer"),
+            contents: bytemuck::bytes_of(&SkinningTables::default()),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+
+        let capacity = 1;
+        let (instances, matrices) = Self::create_buffers(device, capacity);
+        Self {
+            pipeline,
+            bind_group_layout,
+            tables,
+            instances,
+            matrices,
+            capacity,
+        }
+    }
+
+    fn create_buffers(device: &wgpu::Device, capacity: usize) -> (wgpu::Buffer, wgpu::Buffer) {
+        let instances = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Skinning Instances Buffer"),
+            size: (capacity * std::mem::size_of::<SkinningInstance>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let matrices = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Skinning Matrices Buffer"),
+            size: Self::matrix_offset(capacity),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        (instances, matrices)
+    }
+
+    /// Byte offset of an instance's first matrix in `matrices`
+    pub fn matrix_offset(instance: usize) -> u64 {
+        (instance * RENDER_BONE_COUNT * std::mem::size_of::<glam::Mat4>()) as u64
+    }
+
+    /// Upload `instances` and record the compute pass into `encoder`
+    /// Buffers grow as needed; results are valid once the encoder is submitted.
+    pub fn dispatch(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        instances: &[SkinningInstance],
+    ) {
+        if instances.is_empty() {
+            return;
+        }
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            (self.instances, self.matrices) = Self::create_buffers(device, self.capacity);
+        }
+        queue.write_buffer(&self.instances, 0, bytemuck::cast_slice(instances));
+
+        // Bind exactly the uploaded instances so arrayLength() is the instance count
+        let instances_size = std::mem::size_of_val(instances) as u64;
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Skinning Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: self.tables.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &self.instances,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(instances_size),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.matrices.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Skinning Pass"),
+            timestamp_writes: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        let workgroups = (instances.len() as u32).div_ceil(SKINNING_WORKGROUP_SIZE);
+        pass.dispatch_workgroups(workgroups, 1, 1);
+    }
+
+    /// Record a copy of one instance's matrices into a bone matrix buffer
+    pub fn copy_matrices(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        instance: usize,
+        destination: &wgpu::Buffer,
+    ) {
+        encoder.copy_buffer_to_buffer(
+            &self.matrices,
+            Self::matrix_offset(instance),
+            destination,
+            0,
+            Self::matrix_offset(1),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::{Quat, Vec3};
+    use wasm_bindgen_test::*;
+
+    fn test_poses() -> Vec<RotationPose> {
+        vec![
+            RotationPose::bind_pose(),
+            RotationPose::bind_pose()
+                .with_root_position(Vec3::new(0.5, 0.2, -1.0))
+                .with_rotation(BoneId::Spine1, Quat::from_rotation_x(0.4))
+                .with_rotation(BoneId::LeftShoulder, Quat::from_rotation_z(-0.9))
+                .with_rotation(BoneId::RightElbow, Quat::from_rotation_y(1.2))
+                .with_rotation(BoneId::LeftKnee, Quat::from_rotation_x(-1.0)),
+            RotationPose::bind_pose()
+                .with_rotation(BoneId::Pelvis, Quat::from_rotation_y(2.0))
+                .with_rotation(BoneId::Neck, Quat::from_rotation_x(0.3))
+                .with_bone_lengths({
+                    let mut lengths = crate::bone::rest_bone_lengths();
+                    lengths[BoneId::LeftElbow.index()] *= 1.5;
+                    lengths
+                }),
+        ]
+    }
+
+    fn assert_matrices_close(actual: &[glam::Mat4], expected: &[glam::Mat4], tolerance: f32) {
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!(a.abs_diff_eq(*e, tolerance), "matrix {i}: {a:?} != {e:?}");
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_packed_instances_match_compute_bone_matrices() {
+        let tables = SkinningTables::default();
+        for pose in test_poses() {
+            let matrices = compute_instance_matrices(&tables, &SkinningInstance::from(&pose));
+            assert_matrices_close(&matrices, &pose.compute_bone_matrices(), 1e-4);
+        }
+    }
+
+    /// Hidden bones collapse onto their own origin, as `update_bone_uniforms` does on the CPU
+    fn hidden_bone_matrices(pose: &RotationPose, visible: u32) -> [glam::Mat4; RENDER_BONE_COUNT] {
+        let mut matrices = pose.compute_bone_matrices();
+        apply_bone_visibility(&mut matrices, visible);
+        matrices
+    }
+
+    const HIDDEN_TEST_MASK: u32 = ALL_BONES_VISIBLE & !(1 << 3) & !(1 << 8) & !(1 << 21);
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn test_packed_hidden_bones_match_cpu_visibility() {
+        let tables = SkinningTables::default();
+        for pose in test_poses() {
+            let instance = SkinningInstance::from(&pose).with_visible_bones(HIDDEN_TEST_MASK);
+            let matrices = compute_instance_matrices(&tables, &instance);
+            let expected = hidden_bone_matrices(&pose, HIDDEN_TEST_MASK);
+            assert_matrices_close(&matrices, &expected, 1e-4);
+            // Collapsed, but still at the bone's own translation
+            assert_eq!(matrices[8].transform_vector3(Vec3::ONE), Vec3::ZERO);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::native_gpu::{adapter, block_on};
+
+    /// Runs the compute pass on a native adapter; skipped when none is available
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_gpu_matrices_match_cpu() {
+        let Some(adapter) = adapter() else {
+            return;
+        };
+        let (device, queue) = block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .expect("device");
+        if !supports_compute(&adapter, &device) {
+            return;
+        }
+
+        // Every other instance hides some bones, as `update_skeleton_poses` does
+        let poses = test_poses();
+        let visible = |i: usize| {
+            if i % 2 == 1 {
+                HIDDEN_TEST_MASK
+            } else {
+                ALL_BONES_VISIBLE
+            }
+        };
+        let instances: Vec<SkinningInstance> = poses
+            .iter()
+            .enumerate()
+            .map(|(i, pose)| SkinningInstance::from(pose).with_visible_bones(visible(i)))
+            .collect();
+        let mut skinning = GpuSkinning::new(&device);
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Skinning Readback"),
+            size: GpuSkinning::matrix_offset(instances.len()),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&Default::default());
+        skinning.dispatch(&device, &queue, &mut encoder, &instances);
+        encoder.copy_buffer_to_buffer(&skinning.matrices, 0, &readback, 0, readback.size());
+        queue.submit(std::iter::once(encoder.finish()));
+
+        readback.slice(..).map_async(wgpu::MapMode::Read, |result| {
+            result.expect("map readback buffer")
+        });
+        device
+            .poll(wgpu::PollType::Wait {
+                submission_index: None,
+                timeout: None,
+            })
+            .expect("poll");
+        let data = readback.slice(..).get_mapped_range();
+        let gpu_matrices: &[glam::Mat4] = bytemuck::cast_slice(&data);
+        // Looser than the CPU check: near-parallel segments can land on either side of
+        // the identity cutoff in `from_rotation_arc`
+        for (i, gpu) in gpu_matrices.chunks(RENDER_BONE_COUNT).enumerate() {
+            assert_matrices_close(gpu, &hidden_bone_matrices(&poses[i], visible(i)), 2e-3);
+        }
+    }
+}
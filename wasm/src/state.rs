@@ -1,7 +1,8 @@
 //! Centralized application state with context passing pattern
 //!
 //! Implements a Context Passing pattern where:
-//! 1. `AppState` is a single struct containing all application state
+//! 1. `AppState` is a single struct containing all application state: the GPU
+//!    context plus a platform-agnostic `Engine` (see `engine.rs`)
 //! 2. Core functions take explicit references (e.g., `&GpuContext`, `&Camera`)
 //! 3. WASM bindings are thin wrappers that extract from AppState and call pure functions
 //!
@@ -9,8 +10,7 @@
 //! - Unit testing of core logic without global state
 //! - Clear dependency graphs
 
-use crate::animation::{AnimationLibrary, PlaybackState};
-use crate::camera::Camera;
+use crate::engine::Engine;
 use crate::gpu::GpuContext;
 use wasm_bindgen::prelude::*;
 
@@ -19,12 +19,8 @@ use wasm_bindgen::prelude::*;
 pub struct AppState {
     /// WebGPU context - device, queue, pipelines, buffers
     pub gpu: GpuContext,
-    /// Loaded animation clips (read-only during playback)
-    pub animation_library: AnimationLibrary,
-    /// Current animation playback state (exercise, time)
-    pub playback: PlaybackState,
-    /// Camera orientation and distance
-    pub camera: Camera,
+    /// Everything else: clips, playback, camera, editor sessions
+    pub engine: Engine,
 }
 
 impl AppState {
@@ -33,9 +29,7 @@ impl AppState {
     pub fn new(gpu: GpuContext) -> Self {
         Self {
             gpu,
-            animation_library: AnimationLibrary::new(),
-            playback: PlaybackState::default(),
-            camera: Camera::default(),
+            engine: Engine::new(),
         }
     }
 }
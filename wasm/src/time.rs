@@ -0,0 +1,66 @@
+//! Clocks for playback and benchmarks
+//!
+//! Code that reads the current time takes a `TimeSource` instead of calling
+//! `performance.now()` directly, so tests can drive it with a `MockTimeSource`
+//! and get the same result on every run.
+
+use std::cell::Cell;
+
+/// Monotonic clock in milliseconds
+pub trait TimeSource {
+    /// Current time in milliseconds since an arbitrary origin
+    fn now_ms(&self) -> f64;
+}
+
+#[cfg(target_arch = "wasm32")]
+/// The browser's high resolution clock (`performance.now()`)
+pub struct PerformanceTimeSource {
+    performance: web_sys::Performance,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl PerformanceTimeSource {
+    /// Returns None outside a window (e.g. in a worker without `performance`)
+    pub fn new() -> Option<Self> {
+        let performance = web_sys::window()?.performance()?;
+        Some(Self { performance })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl TimeSource for PerformanceTimeSource {
+    fn now_ms(&self) -> f64 {
+        self.performance.now()
+    }
+}
+
+/// Clock that only moves when told to
+#[derive(Debug, Default)]
+pub struct MockTimeSource {
+    now_ms: Cell<f64>,
+}
+
+impl MockTimeSource {
+    /// Create a clock reading `start_ms`
+    pub fn new(start_ms: f64) -> Self {
+        Self {
+            now_ms: Cell::new(start_ms),
+        }
+    }
+
+    /// Jump to `now_ms`
+    pub fn set(&self, now_ms: f64) {
+        self.now_ms.set(now_ms);
+    }
+
+    /// Move forward by `delta_ms`
+    pub fn advance(&self, delta_ms: f64) {
+        self.now_ms.set(self.now_ms.get() + delta_ms);
+    }
+}
+
+impl TimeSource for MockTimeSource {
+    fn now_ms(&self) -> f64 {
+        self.now_ms.get()
+    }
+}